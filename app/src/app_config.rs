@@ -0,0 +1,117 @@
+use crate::nvm::{swapping_buffer::RAM_SIZE, NVM_DATA_SIZE};
+use crate::settings::{
+    is_blind_signing_enabled, is_cross_group_warning_disabled, is_display_tx_id_enabled,
+    is_expert_mode_enabled, is_mainnet_only_enabled, is_response_mac_enabled,
+    is_unknown_token_warning_enabled,
+};
+
+// The bits of the supported features, a bit is set if the feature is supported
+pub const FEATURE_TOKEN_METADATA: u32 = 1 << 0;
+pub const FEATURE_SCHNORR: u32 = 1 << 1;
+pub const FEATURE_SWEEP_TX: u32 = 1 << 2;
+pub const FEATURE_RESPONSE_MAC: u32 = 1 << 3;
+pub const FEATURE_ESTIMATED_OUTPUTS: u32 = 1 << 4;
+pub const FEATURE_NFT_METADATA: u32 = 1 << 5;
+pub const FEATURE_CHANGE_ADDRESS: u32 = 1 << 6;
+pub const FEATURE_TESTNET_PATHS: u32 = 1 << 7;
+pub const FEATURE_SIGN_MESSAGE: u32 = 1 << 8;
+pub const FEATURE_SIGN_TX_STATUS: u32 = 1 << 9;
+pub const FEATURE_EXTENDED_PUBLIC_KEY: u32 = 1 << 10;
+pub const FEATURE_BUFFER_CAPACITY: u32 = 1 << 11;
+pub const FEATURE_ABORT: u32 = 1 << 12;
+pub const FEATURE_SIGN_CHALLENGE: u32 = 1 << 13;
+pub const FEATURE_AMOUNT_THRESHOLD: u32 = 1 << 14;
+pub const FEATURE_SIGN_TYPED_DATA: u32 = 1 << 15;
+// The features above bit 15 are returned after the settings, so that older hosts still find
+// the first 16 bits and the settings at the same offsets
+pub const FEATURE_MULTI_SIG_ADDRESS: u32 = 1 << 16;
+pub const FEATURE_WALLET_FINGERPRINT: u32 = 1 << 17;
+pub const FEATURE_INPUT_PATHS: u32 = 1 << 18;
+pub const FEATURE_CHAINED_TXS: u32 = 1 << 19;
+
+const FEATURES: u32 = FEATURE_TOKEN_METADATA
+    | FEATURE_SCHNORR
+    | FEATURE_SWEEP_TX
+    | FEATURE_RESPONSE_MAC
+    | FEATURE_ESTIMATED_OUTPUTS
+    | FEATURE_NFT_METADATA
+    | FEATURE_CHANGE_ADDRESS
+    | FEATURE_TESTNET_PATHS
+    | FEATURE_SIGN_MESSAGE
+    | FEATURE_SIGN_TX_STATUS
+    | FEATURE_EXTENDED_PUBLIC_KEY
+    | FEATURE_BUFFER_CAPACITY
+    | FEATURE_ABORT
+    | FEATURE_SIGN_CHALLENGE
+    | FEATURE_AMOUNT_THRESHOLD
+    | FEATURE_SIGN_TYPED_DATA
+    | FEATURE_MULTI_SIG_ADDRESS
+    | FEATURE_WALLET_FINGERPRINT
+    | FEATURE_INPUT_PATHS
+    | FEATURE_CHAINED_TXS;
+
+// The bits of the current settings, a bit is set if the setting is enabled
+pub const SETTING_BLIND_SIGNING: u8 = 1 << 0;
+pub const SETTING_RESPONSE_MAC: u8 = 1 << 1;
+pub const SETTING_MAINNET_ONLY: u8 = 1 << 2;
+pub const SETTING_DISPLAY_TX_ID: u8 = 1 << 3;
+pub const SETTING_EXPERT_MODE: u8 = 1 << 4;
+pub const SETTING_UNKNOWN_TOKEN_WARNING: u8 = 1 << 5;
+pub const SETTING_CROSS_GROUP_WARNING_DISABLED: u8 = 1 << 6;
+
+pub const APP_CONFIGURATION_SIZE: usize = 8;
+
+// The app version, the supported features and the current settings
+pub fn get_app_configuration() -> [u8; APP_CONFIGURATION_SIZE] {
+    let settings = [
+        (SETTING_BLIND_SIGNING, is_blind_signing_enabled()),
+        (SETTING_RESPONSE_MAC, is_response_mac_enabled()),
+        (SETTING_MAINNET_ONLY, is_mainnet_only_enabled()),
+        (SETTING_DISPLAY_TX_ID, is_display_tx_id_enabled()),
+        (SETTING_EXPERT_MODE, is_expert_mode_enabled()),
+        (
+            SETTING_UNKNOWN_TOKEN_WARNING,
+            is_unknown_token_warning_enabled(),
+        ),
+        (
+            SETTING_CROSS_GROUP_WARNING_DISABLED,
+            is_cross_group_warning_disabled(),
+        ),
+    ]
+    .iter()
+    .filter(|(_, enabled)| *enabled)
+    .fold(0, |result, (bit, _)| result | bit);
+    let features = FEATURES.to_be_bytes();
+    [
+        env!("CARGO_PKG_VERSION_MAJOR").parse::<u8>().unwrap(),
+        env!("CARGO_PKG_VERSION_MINOR").parse::<u8>().unwrap(),
+        env!("CARGO_PKG_VERSION_PATCH").parse::<u8>().unwrap(),
+        features[2],
+        features[3],
+        settings,
+        features[0],
+        features[1],
+    ]
+}
+
+pub const BUFFER_CAPACITY_SIZE: usize = 7;
+// The maximum data size of a tx frame
+const MAX_FRAME_SIZE: u8 = u8::MAX;
+
+// The RAM and NVM sizes of the swapping buffers, the available capacity, which is the RAM size
+// once an NVM write fails, and the maximum frame size. The tx is decoded in a stream, so its
+// size is not limited, but each script or multisig lockup has to fit in the available capacity.
+pub fn get_buffer_capacity(available: usize) -> [u8; BUFFER_CAPACITY_SIZE] {
+    let ram_size = (RAM_SIZE as u16).to_be_bytes();
+    let nvm_size = (NVM_DATA_SIZE as u16).to_be_bytes();
+    let available = (available as u16).to_be_bytes();
+    [
+        ram_size[0],
+        ram_size[1],
+        nvm_size[0],
+        nvm_size[1],
+        available[0],
+        available[1],
+        MAX_FRAME_SIZE,
+    ]
+}
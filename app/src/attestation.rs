@@ -0,0 +1,32 @@
+use crate::{
+    blake2b_hasher::Blake2bHasher,
+    error_code::ErrorCode,
+    public_key::{compress_public_key, derive_pub_key_by_path, sign_hash, Address},
+    ui::{bytes_to_string, review_pairing},
+};
+use ledger_device_sdk::io::Reply;
+use utils::get_key_type;
+
+// The prefix of the signed attestation, which separates it from tx ids and signed messages
+const ATTESTATION_PREFIX: &[u8] = b"Alephium Device Attestation: ";
+pub const MIN_CHALLENGE_SIZE: usize = 16;
+pub const MAX_CHALLENGE_SIZE: usize = 64;
+
+// Sign the Blake2b hash of the prefixed challenge and compressed public key of the path,
+// so that the host can check that the device holds the key of the account
+pub fn sign_challenge(path: &[u32], challenge: &[u8]) -> Result<([u8; 72], u32, u32), Reply> {
+    if challenge.len() < MIN_CHALLENGE_SIZE || challenge.len() > MAX_CHALLENGE_SIZE {
+        return Err(ErrorCode::BadLen.into());
+    }
+    let key_type = get_key_type::<Reply>(path, ErrorCode::HDPathDecodingFailed.into())?;
+    let pub_key = derive_pub_key_by_path(path)?;
+    let address = Address::from_pub_key(&pub_key, key_type)?;
+    review_pairing(bytes_to_string(address.get_address_bytes())?)?;
+
+    let hash = Blake2bHasher::hash_inputs(&[
+        ATTESTATION_PREFIX,
+        challenge,
+        &compress_public_key(pub_key.as_ref()),
+    ])?;
+    sign_hash(path, &hash).map_err(|code| code.into())
+}
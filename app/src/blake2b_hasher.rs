@@ -19,6 +19,14 @@ impl Blake2bHasher {
         hasher.finalize()
     }
 
+    pub fn hash_inputs(inputs: &[&[u8]]) -> Result<Blake2bHash, ErrorCode> {
+        let mut hasher = Blake2bHasher::new();
+        for input in inputs {
+            hasher.update(input)?;
+        }
+        hasher.finalize()
+    }
+
     pub fn reset(&mut self) {
         unsafe { cx_blake2b_init_no_throw(&mut self.0, BLAKE2B_HASH_SIZE * 8) };
     }
@@ -20,6 +20,15 @@ pub enum ErrorCode {
     InvalidMetadataVersion = 0xE007,
     InvalidTokenProofSize = 0xE008,
     InvalidTokenMetadata = 0xE009,
+    ResponseMacDisabled = 0xE00A,
+    MainnetOnly = 0xE00B,
+    InvalidState = 0xE00C,
+    NvmWriteFailed = 0xE00D,
+    UnsupportedTxVersion = 0xE00E,
+    TypedDataDecodingFailed = 0xE00F,
+    UnsupportedLockupScript = 0xE010,
+    UnsupportedUnlockScript = 0xE011,
+    MalformedField = 0xE012,
     InternalError = 0xEF00,
 }
 
@@ -0,0 +1,53 @@
+use crate::error_code::ErrorCode;
+use ledger_device_sdk::nvm::{AtomicStorage, SingleStorage};
+use ledger_device_sdk::NVMData;
+
+// The last error is encoded as: status word (2 bytes), instruction (1 byte),
+// tx decode step (1 byte), decode stage step (2 bytes) and decode stage index (2 bytes)
+pub const ERROR_LOG_SIZE: usize = 8;
+#[link_section = ".nvm_data"]
+static mut ERROR_LOG: NVMData<AtomicStorage<[u8; ERROR_LOG_SIZE]>> =
+    NVMData::new(AtomicStorage::new(&[0u8; ERROR_LOG_SIZE]));
+
+// Only the failures to decode or sign are recorded. The errors of malformed commands and the
+// errors caused by the user rejecting a review can be triggered in a loop and would wear the NVM
+const FATAL_ERRORS: [u16; 10] = [
+    ErrorCode::TxDecodingFailed as u16,
+    ErrorCode::TxSigningFailed as u16,
+    ErrorCode::Overflow as u16,
+    ErrorCode::DerivingPublicKeyFailed as u16,
+    ErrorCode::NvmWriteFailed as u16,
+    ErrorCode::TypedDataDecodingFailed as u16,
+    ErrorCode::UnsupportedLockupScript as u16,
+    ErrorCode::UnsupportedUnlockScript as u16,
+    ErrorCode::MalformedField as u16,
+    ErrorCode::InternalError as u16,
+];
+
+// The position of the tx decoder when the failed command was received
+pub struct DecodePosition {
+    pub tx_step: u8,
+    pub stage_step: u16,
+    pub stage_index: u16,
+}
+
+// Keep the last fatal error in NVM so that it can be read after the error is reported
+pub fn record_error(status_word: u16, ins: u8, position: &DecodePosition) {
+    if !FATAL_ERRORS.contains(&status_word) {
+        return;
+    }
+    let mut log = [0u8; ERROR_LOG_SIZE];
+    log[0..2].copy_from_slice(&status_word.to_be_bytes());
+    log[2] = ins;
+    log[3] = position.tx_step;
+    log[4..6].copy_from_slice(&position.stage_step.to_be_bytes());
+    log[6..8].copy_from_slice(&position.stage_index.to_be_bytes());
+    // The same error is not written again
+    if get_last_error() != log {
+        unsafe { ERROR_LOG.get_mut().update(&log) }
+    }
+}
+
+pub fn get_last_error() -> [u8; ERROR_LOG_SIZE] {
+    unsafe { *ERROR_LOG.get_mut().get_ref() }
+}
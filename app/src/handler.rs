@@ -1,20 +1,49 @@
 use ledger_device_sdk::io::{self, ApduHeader};
-use utils::deserialize_path;
+use utils::{
+    deserialize_partial_path, deserialize_path, get_key_type,
+    instruction_state::{Command, InstructionState},
+    is_above_address_level, is_standard_path, to_hex_fixed,
+    types::{
+        public_key::{PublicKeyEncoding, UNCOMPRESSED_PUBLIC_KEY_SIZE},
+        u256::{MAX_ALPH_AMOUNT_LENGTH, THOUSANDS_SEPARATOR},
+        unsigned_tx::{is_supported_tx_version, MAX_TX_VERSION, MIN_TX_VERSION},
+        U256,
+    },
+};
 
 use crate::{
+    app_config::{get_app_configuration, get_buffer_capacity},
+    attestation::sign_challenge,
     debug::print::{println, println_slice},
     error_code::ErrorCode,
-    public_key::{derive_pub_key, Address},
-    sign_tx_context::SignTxContext,
-    ui::{bytes_to_string, review_address, sign_hash_ui, tx_reviewer::TxReviewer},
+    error_log::{get_last_error, record_error},
+    public_key::{
+        derive_extended_pub_key, derive_pub_key, derive_pub_keys, derive_wallet_fingerprint,
+        Address, BATCH_PUBLIC_KEY_SIZE, COMPRESSED_PUBKEY_SIZE, MAX_BATCH_PUBLIC_KEYS,
+        WALLET_FINGERPRINT_SIZE,
+    },
+    response_mac::{derive_mac_key, ResponseMac},
+    self_test::run_self_test,
+    settings::{is_mainnet_only_enabled, is_response_mac_enabled, set_amount_threshold},
+    sign_message_context::SignMessageContext,
+    sign_tx_context::{SignTxContext, MAX_CHAINED_SIGNATURES_SIZE},
+    ui::{
+        bytes_to_string, review_address, review_amount_threshold, review_change_address,
+        review_extended_key, review_path, review_response_mac_key, review_wallet_fingerprint,
+        sign_hash_ui,
+        tx_reviewer::{TxReviewer, MAX_MULTI_SIG_ADDRESS_KEYS},
+    },
 };
 
 const MAX_TOKEN_SIZE: u8 = 5;
 const PATH_LENGTH: usize = 20;
+const AMOUNT_THRESHOLD_LENGTH: usize = 16;
 const HASH_LENGTH: usize = 32;
+const ACCOUNT_KEY_DEPTH: usize = 3;
 const PATH_HEX_LENGTH: usize = PATH_LENGTH * 2;
 const CALL_CONTRACT_FLAG: u8 = 0x01;
 const SCRIPT_OFFSET: usize = 3; // the encoded script offset in the tx
+const PREVIEW_TX_P2: u8 = 3;
 pub const TOKEN_METADATA_SIZE: usize = 46;
 
 #[repr(u8)]
@@ -23,6 +52,56 @@ pub enum Ins {
     GetPubKey,
     SignHash,
     SignTx,
+    GetResponseMacKey,
+    GetLastError,
+    SelfTest,
+    GetChangeAddress,
+    GetAppConfiguration,
+    SignMessage,
+    GetSignTxStatus,
+    GetExtendedPubKey,
+    GetBufferCapacity,
+    Abort,
+    SignChallenge,
+    SetAmountThreshold,
+    SignTypedData,
+    GetMultiSigAddress,
+    GetWalletFingerprint,
+}
+
+impl Ins {
+    // The command of the instruction state machine, `None` for invalid `SignTx` frames
+    fn command(&self, apdu_header: &ApduHeader, data: &[u8]) -> Option<Command> {
+        match self {
+            // Keys that are not displayed can be derived while a transaction is in progress,
+            // unless the path is unusual and has to be shown to the user
+            Ins::GetPubKey | Ins::GetChangeAddress
+                if data.get(PATH_LENGTH) == Some(&0) && has_standard_path(&data[..PATH_LENGTH]) =>
+            {
+                Some(Command::Info)
+            }
+            // The wallet fingerprint is only displayed on request
+            Ins::GetWalletFingerprint if apdu_header.p1 == 0 => Some(Command::Info),
+            Ins::GetVersion
+            | Ins::GetLastError
+            | Ins::GetAppConfiguration
+            | Ins::GetSignTxStatus
+            | Ins::GetBufferCapacity => Some(Command::Info),
+            Ins::GetPubKey
+            | Ins::GetExtendedPubKey
+            | Ins::SignHash
+            | Ins::GetResponseMacKey
+            | Ins::SelfTest
+            | Ins::GetChangeAddress
+            | Ins::SignChallenge
+            | Ins::SetAmountThreshold
+            | Ins::GetMultiSigAddress
+            | Ins::GetWalletFingerprint => Some(Command::Standalone),
+            Ins::SignTx => Command::from_sign_tx(apdu_header.p1, apdu_header.p2),
+            Ins::SignMessage | Ins::SignTypedData => Command::from_sign_message(apdu_header.p1),
+            Ins::Abort => Some(Command::Abort),
+        }
+    }
 }
 
 impl TryFrom<io::ApduHeader> for Ins {
@@ -33,6 +112,21 @@ impl TryFrom<io::ApduHeader> for Ins {
             1 => Ok(Ins::GetPubKey),
             2 => Ok(Ins::SignHash),
             3 => Ok(Ins::SignTx),
+            4 => Ok(Ins::GetResponseMacKey),
+            5 => Ok(Ins::GetLastError),
+            6 => Ok(Ins::SelfTest),
+            7 => Ok(Ins::GetChangeAddress),
+            8 => Ok(Ins::GetAppConfiguration),
+            9 => Ok(Ins::SignMessage),
+            10 => Ok(Ins::GetSignTxStatus),
+            11 => Ok(Ins::GetExtendedPubKey),
+            12 => Ok(Ins::GetBufferCapacity),
+            13 => Ok(Ins::Abort),
+            14 => Ok(Ins::SignChallenge),
+            15 => Ok(Ins::SetAmountThreshold),
+            16 => Ok(Ins::SignTypedData),
+            17 => Ok(Ins::GetMultiSigAddress),
+            18 => Ok(Ins::GetWalletFingerprint),
             _ => Err(ErrorCode::BadIns),
         }
     }
@@ -41,6 +135,39 @@ impl TryFrom<io::ApduHeader> for Ins {
 pub fn handle_apdu(
     comm: &mut io::Comm,
     ins: Ins,
+    state: &mut InstructionState,
+    sign_message_context: &mut SignMessageContext,
+    sign_tx_context: &mut SignTxContext,
+    tx_reviewer: &mut TxReviewer,
+) -> Result<bool, io::Reply> {
+    // The context is reset when a command fails, so the decode position is taken beforehand
+    let position = sign_tx_context.decode_position();
+    let ins_code = comm.get_apdu_metadata().ins;
+    let result = _handle_apdu(
+        comm,
+        ins,
+        state,
+        sign_message_context,
+        sign_tx_context,
+        tx_reviewer,
+    );
+    if let Err(reply) = &result {
+        record_error(reply.0, ins_code, &position);
+    }
+    result
+}
+
+fn has_standard_path(data: &[u8]) -> bool {
+    let mut path = [0u32; 5];
+    deserialize_partial_path(data, 1, &mut path, ())
+        .is_ok_and(|depth| is_standard_path(&path[..depth]))
+}
+
+fn _handle_apdu(
+    comm: &mut io::Comm,
+    ins: Ins,
+    state: &mut InstructionState,
+    sign_message_context: &mut SignMessageContext,
     sign_tx_context: &mut SignTxContext,
     tx_reviewer: &mut TxReviewer,
 ) -> Result<bool, io::Reply> {
@@ -54,20 +181,47 @@ pub fn handle_apdu(
         return Err(ErrorCode::BadCla.into());
     }
 
+    // Out of order instructions are rejected without discarding the current transaction
+    let data = comm.get_data().unwrap_or_default();
+    if let Some(command) = ins.command(apdu_header, data) {
+        match state.next(command) {
+            Some(next_state) => *state = next_state,
+            None => return Err(ErrorCode::InvalidState.into()),
+        }
+    }
+
     // Common instructions
     match ins {
         Ins::GetVersion => {
             let version_major = env!("CARGO_PKG_VERSION_MAJOR").parse::<u8>().unwrap();
             let version_minor = env!("CARGO_PKG_VERSION_MINOR").parse::<u8>().unwrap();
             let version_patch = env!("CARGO_PKG_VERSION_PATCH").parse::<u8>().unwrap();
+            // The host can declare the tx encoding version it streams, the supported range of
+            // versions is returned if the version is supported
+            let tx_version = match comm.get_data()? {
+                [] => None,
+                [version] => Some(*version),
+                _ => return Err(ErrorCode::BadLen.into()),
+            };
+            if let Some(version) = tx_version {
+                if !is_supported_tx_version(version) {
+                    return Err(ErrorCode::UnsupportedTxVersion.into());
+                }
+            }
             comm.append([version_major, version_minor, version_patch].as_slice());
+            if tx_version.is_some() {
+                comm.append(&[MIN_TX_VERSION, MAX_TX_VERSION]);
+            }
         }
         Ins::GetPubKey => {
             let data = comm.get_data()?;
-            // 1 byte flag indicating whether address verification is needed
-            if data.len() != PATH_LENGTH + 1 {
-                return Err(ErrorCode::BadLen.into());
-            }
+            // 1 byte flag indicating whether address verification is needed, optionally followed
+            // by the number of keys to derive from consecutive indexes
+            let batch_size = match data.len() {
+                len if len == PATH_LENGTH + 1 => None,
+                len if len == PATH_LENGTH + 2 => Some(data[PATH_LENGTH + 1] as usize),
+                _ => return Err(ErrorCode::BadLen.into()),
+            };
             let raw_path = &data[..PATH_LENGTH];
             deserialize_path::<io::Reply>(
                 raw_path,
@@ -77,21 +231,48 @@ pub fn handle_apdu(
 
             println("raw path");
             println_slice::<PATH_HEX_LENGTH>(raw_path);
+            review_path(&path)?;
             let p1 = apdu_header.p1; // Group number: 0 for all groups
-            let p2 = apdu_header.p2; // Target group
-            let (pk, hd_index) = derive_pub_key(&mut path, p1, p2)?;
+            let (encoding, p2) = PublicKeyEncoding::from_p2(apdu_header.p2); // Target group
 
+            // The key type is selected by the account node of the path
+            let key_type =
+                get_key_type::<io::Reply>(&path, ErrorCode::HDPathDecodingFailed.into())?;
             let need_to_display = data[PATH_LENGTH] != 0;
+
+            // The keys of a batch are used for account discovery, so they are not displayed
+            if let Some(count) = batch_size {
+                if need_to_display || count == 0 || count > MAX_BATCH_PUBLIC_KEYS {
+                    return Err(ErrorCode::BadLen.into());
+                }
+                let mut output = [0u8; MAX_BATCH_PUBLIC_KEYS * BATCH_PUBLIC_KEY_SIZE];
+                let size = derive_pub_keys(&mut path, p1, p2, key_type, count, &mut output)?;
+                append_response(comm, Ins::GetPubKey, &[&output[..size]])?;
+                return Ok(*state == InstructionState::Idle);
+            }
+
+            let (pk, hd_index) = derive_pub_key(&mut path, p1, p2, key_type)?;
             if need_to_display {
-                let address = Address::from_pub_key(&pk)?;
+                let address = Address::from_pub_key(&pk, key_type)?;
                 let address_str = bytes_to_string(address.get_address_bytes())?;
-                review_address(address_str)?;
+                review_address(address_str, key_type, &path)?;
             }
 
-            comm.append(pk.as_ref());
-            comm.append(hd_index.to_be_bytes().as_slice());
+            let mut encoded_key = [0u8; UNCOMPRESSED_PUBLIC_KEY_SIZE];
+            append_response(
+                comm,
+                Ins::GetPubKey,
+                &[
+                    encoding.encode(pk.as_ref(), &mut encoded_key),
+                    hd_index.to_be_bytes().as_slice(),
+                ],
+            )?;
         }
         Ins::SignHash => {
+            // The hash cannot be checked against the network id
+            if is_mainnet_only_enabled() {
+                return Err(ErrorCode::MainnetOnly.into());
+            }
             let data = comm.get_data()?;
             if data.len() != PATH_LENGTH + HASH_LENGTH {
                 return Err(ErrorCode::BadLen.into());
@@ -102,9 +283,14 @@ pub fn handle_apdu(
                 &mut path,
                 ErrorCode::HDPathDecodingFailed.into(),
             )?;
+            // A raw hash cannot be decoded and reviewed, so it is only signed with blind signing
+            tx_reviewer.check_blind_signing()?;
+            review_path(&path)?;
 
             match sign_hash_ui(&path, &data[PATH_LENGTH..]) {
-                Ok((signature_buf, length, _)) => comm.append(&signature_buf[..length as usize]),
+                Ok((signature_buf, length, _)) => {
+                    append_response(comm, Ins::SignHash, &[&signature_buf[..length as usize]])?
+                }
                 Err(code) => return Err(code.into()),
             }
         }
@@ -112,38 +298,300 @@ pub fn handle_apdu(
             let data = match comm.get_data() {
                 Ok(data) => data,
                 Err(code) => {
-                    reset(sign_tx_context, tx_reviewer);
+                    reset(state, sign_tx_context, tx_reviewer);
                     return Err(code.into());
                 }
             };
+            // The signatures of the remaining paths of an approved sweep transaction
+            if apdu_header.p1 == 2 {
+                let result = sign_tx_context.sign_next(apdu_header.p2 as usize);
+                if result.is_err() || !sign_tx_context.has_pending_signatures() {
+                    reset(state, sign_tx_context, tx_reviewer);
+                }
+                let (signature_buf, length, _) = result?;
+                append_response(comm, Ins::SignTx, &[&signature_buf[..length as usize]])?;
+                return Ok(true);
+            }
             match handle_sign_tx(apdu_header, data, sign_tx_context, tx_reviewer) {
                 Ok(()) if !sign_tx_context.is_complete() => {
+                    let is_first_frame = apdu_header.p1 == 0 && apdu_header.p2 == 0;
+                    sign_tx_context.on_frame_processed(is_first_frame);
                     return Ok(false);
                 }
+                Ok(()) if tx_reviewer.is_preview() => {
+                    // A preview is reviewed like a transaction to sign, but only the tx id is returned
+                    let preview_result = sign_tx_context.get_tx_id().and_then(|tx_id| {
+                        tx_reviewer.approve_tx(&tx_id)?;
+                        append_response(comm, Ins::SignTx, &[&tx_id])
+                    });
+                    reset(state, sign_tx_context, tx_reviewer);
+                    return match preview_result {
+                        Ok(()) => Ok(true),
+                        Err(code) => Err(code.into()),
+                    };
+                }
+                Ok(()) if sign_tx_context.is_chained_tx() => {
+                    // The txs of a chained session are signed together once the last tx is approved
+                    // The tx id of a tx that is not the last one is returned as an acknowledgment
+                    let chained_result = sign_tx_context.get_tx_id().and_then(|tx_id| {
+                        // Only the first path signs a chained tx, so the tx cannot be approved
+                        // if any of its inputs is spent from another address
+                        if !tx_reviewer.is_spent_by_first_path() {
+                            return Err(ErrorCode::TxSigningFailed);
+                        }
+                        tx_reviewer.approve_tx(&tx_id)?;
+                        sign_tx_context.approve_chained_tx(tx_reviewer.total_sent())?;
+                        if !sign_tx_context.is_chain_complete() {
+                            return append_response(comm, Ins::SignTx, &[&tx_id]);
+                        }
+                        // Nothing is signed until the user approves all the txs together
+                        tx_reviewer.review_chained_txs(
+                            sign_tx_context.chained_tx_count(),
+                            sign_tx_context.chained_total_sent(),
+                        )?;
+                        let mut signatures = [0u8; MAX_CHAINED_SIGNATURES_SIZE];
+                        let size = sign_tx_context.sign_chained_txs(&mut signatures)?;
+                        append_response(comm, Ins::SignTx, &[&signatures[..size]])
+                    });
+                    // The approved txs are kept for the next tx of the session
+                    if chained_result.is_ok() && !sign_tx_context.is_chain_complete() {
+                        *state = InstructionState::Idle;
+                        sign_tx_context.reset_tx();
+                        tx_reviewer.reset();
+                    } else {
+                        reset(state, sign_tx_context, tx_reviewer);
+                    }
+                    return match chained_result {
+                        Ok(()) => Ok(true),
+                        Err(code) => Err(code.into()),
+                    };
+                }
                 Ok(()) => {
                     // The transaction is signed when all the data is processed
                     // The signature is returned in the response
-                    let sign_result = tx_reviewer
-                        .approve_tx()
-                        .and_then(|_| sign_tx_context.sign_tx());
+                    let sign_result = sign_tx_context
+                        .get_tx_id()
+                        .and_then(|tx_id| tx_reviewer.approve_tx(&tx_id))
+                        .and_then(|_| sign_tx_context.sign_tx())
+                        .and_then(|(signature_buf, length, _)| {
+                            append_response(comm, Ins::SignTx, &[&signature_buf[..length as usize]])
+                        });
                     let result = match sign_result {
-                        Ok((signature_buf, length, _)) => {
-                            comm.append(&signature_buf[..length as usize]);
-                            Ok(true)
-                        }
+                        Ok(()) => Ok(true),
                         Err(code) => Err(code.into()),
                     };
-                    reset(sign_tx_context, tx_reviewer);
+                    // Keep the approved tx id until all the sweep signatures are returned
+                    if result.is_err() || !sign_tx_context.has_pending_signatures() {
+                        reset(state, sign_tx_context, tx_reviewer);
+                    } else {
+                        *state = state.approved(true);
+                    }
                     return result;
                 }
                 Err(code) => {
-                    reset(sign_tx_context, tx_reviewer);
+                    reset(state, sign_tx_context, tx_reviewer);
                     return Err(code.into());
                 }
             }
         }
+        Ins::GetResponseMacKey => {
+            // The host learns the key once during onboarding, the user has to confirm sharing it
+            if !is_response_mac_enabled() {
+                return Err(ErrorCode::ResponseMacDisabled.into());
+            }
+            review_response_mac_key()?;
+            let key = derive_mac_key()?;
+            comm.append(key.as_bytes());
+        }
+        Ins::GetChangeAddress => {
+            let data = comm.get_data()?;
+            // 1 byte flag indicating whether address verification is needed
+            if data.len() != PATH_LENGTH + 1 {
+                return Err(ErrorCode::BadLen.into());
+            }
+            deserialize_path::<io::Reply>(
+                &data[..PATH_LENGTH],
+                &mut path,
+                ErrorCode::HDPathDecodingFailed.into(),
+            )?;
+            review_path(&path)?;
+            let p1 = apdu_header.p1; // Group number: 0 for all groups
+            let p2 = apdu_header.p2; // Target group
+            let key_type =
+                get_key_type::<io::Reply>(&path, ErrorCode::HDPathDecodingFailed.into())?;
+            let (pk, hd_index) = derive_pub_key(&mut path, p1, p2, key_type)?;
+            let address = Address::from_pub_key(&pk, key_type)?;
+
+            let need_to_display = data[PATH_LENGTH] != 0;
+            if need_to_display {
+                let address_str = bytes_to_string(address.get_address_bytes())?;
+                review_change_address(address_str, key_type, &path)?;
+            }
+
+            append_response(
+                comm,
+                Ins::GetChangeAddress,
+                &[
+                    hd_index.to_be_bytes().as_slice(),
+                    address.get_address_bytes(),
+                ],
+            )?;
+        }
+        Ins::GetLastError => comm.append(&get_last_error()),
+        Ins::SelfTest => comm.append(&[run_self_test()]),
+        Ins::GetAppConfiguration => comm.append(&get_app_configuration()),
+        Ins::GetExtendedPubKey => {
+            // The path has at least the purpose, coin type and account nodes, the children of
+            // the returned key are derived by the host
+            let depth = deserialize_partial_path::<io::Reply>(
+                comm.get_data()?,
+                ACCOUNT_KEY_DEPTH,
+                &mut path,
+                ErrorCode::HDPathDecodingFailed.into(),
+            )?;
+            review_path(&path[..depth])?;
+            // The keys above the address level reveal all the addresses below them
+            if is_above_address_level(&path[..depth]) {
+                review_extended_key(&path[..depth])?;
+            }
+            let extended_pub_key = derive_extended_pub_key(&path[..depth])?;
+            append_response(comm, Ins::GetExtendedPubKey, &[&extended_pub_key])?;
+        }
+        Ins::SignChallenge => {
+            let data = comm.get_data()?;
+            if data.len() < PATH_LENGTH {
+                return Err(ErrorCode::BadLen.into());
+            }
+            deserialize_path::<io::Reply>(
+                &data[..PATH_LENGTH],
+                &mut path,
+                ErrorCode::HDPathDecodingFailed.into(),
+            )?;
+            review_path(&path)?;
+            let (signature_buf, length, _) = sign_challenge(&path, &data[PATH_LENGTH..])?;
+            append_response(
+                comm,
+                Ins::SignChallenge,
+                &[&signature_buf[..length as usize]],
+            )?;
+        }
+        Ins::SetAmountThreshold => {
+            // The threshold protects against approving high amounts by habit, so the user
+            // has to confirm any change of it
+            let data = comm.get_data()?;
+            if data.len() != AMOUNT_THRESHOLD_LENGTH {
+                return Err(ErrorCode::BadLen.into());
+            }
+            let mut threshold_bytes = [0u8; AMOUNT_THRESHOLD_LENGTH];
+            threshold_bytes.copy_from_slice(data);
+            let threshold = u128::from_be_bytes(threshold_bytes);
+            let mut amount_output = [0u8; MAX_ALPH_AMOUNT_LENGTH];
+            let amount = if threshold == 0 {
+                "Disabled"
+            } else {
+                let amount = U256::encode_u128(threshold)
+                    .to_alph(&mut amount_output, Some(THOUSANDS_SEPARATOR))
+                    .ok_or(ErrorCode::Overflow)?;
+                bytes_to_string(amount)?
+            };
+            review_amount_threshold(amount)?;
+            set_amount_threshold(threshold);
+        }
+        Ins::Abort => {
+            // Discard the transaction or the message in progress, the state is already idle
+            reset(state, sign_tx_context, tx_reviewer);
+            sign_message_context.reset();
+        }
+        Ins::GetBufferCapacity => {
+            let available = sign_tx_context
+                .buffer_capacity()
+                .min(tx_reviewer.buffer_capacity());
+            comm.append(&get_buffer_capacity(available));
+        }
+        Ins::GetSignTxStatus => {
+            // The state and the number of processed frames, so that the host can resume a tx
+            let frame_count = sign_tx_context.frame_count().to_be_bytes();
+            comm.append(&[*state as u8, frame_count[0], frame_count[1]]);
+        }
+        Ins::GetWalletFingerprint => {
+            let fingerprint = derive_wallet_fingerprint()?;
+            if apdu_header.p1 != 0 {
+                let hex: [u8; WALLET_FINGERPRINT_SIZE * 2] = to_hex_fixed(&fingerprint);
+                review_wallet_fingerprint(bytes_to_string(&hex)?)?;
+            }
+            comm.append(&fingerprint);
+        }
+        Ins::GetMultiSigAddress => {
+            // The compressed public keys of the co-signers, the threshold is the number of
+            // required signatures
+            let public_keys = comm.get_data()?;
+            let key_size = public_keys.len() / COMPRESSED_PUBKEY_SIZE;
+            if public_keys.len() % COMPRESSED_PUBKEY_SIZE != 0
+                || key_size == 0
+                || key_size > MAX_MULTI_SIG_ADDRESS_KEYS
+                || public_keys
+                    .chunks(COMPRESSED_PUBKEY_SIZE)
+                    .any(|public_key| public_key[0] != 0x02 && public_key[0] != 0x03)
+            {
+                return Err(ErrorCode::BadLen.into());
+            }
+            let threshold = apdu_header.p1 as usize;
+            if threshold == 0 || threshold > key_size {
+                return Err(ErrorCode::BadP1P2.into());
+            }
+            let result = tx_reviewer
+                .review_multi_sig_address(public_keys, threshold)
+                .and_then(|address| append_response(comm, Ins::GetMultiSigAddress, &[address]));
+            tx_reviewer.reset();
+            result?;
+        }
+        Ins::SignMessage | Ins::SignTypedData => {
+            // Typed data is uploaded like a message, a first frame of either kind discards the
+            // message in progress
+            let is_typed_data = matches!(ins, Ins::SignTypedData);
+            let result = comm
+                .get_data()
+                .map_err(|_| ErrorCode::BadLen)
+                .and_then(|data| {
+                    if apdu_header.p1 == 0 {
+                        sign_message_context.init(data, is_typed_data)
+                    } else if sign_message_context.is_typed_data() != is_typed_data {
+                        Err(ErrorCode::InvalidState)
+                    } else {
+                        sign_message_context.handle_data(data)
+                    }
+                });
+            if let Err(code) = result {
+                sign_message_context.reset();
+                *state = InstructionState::Idle;
+                return Err(code.into());
+            }
+            if !sign_message_context.is_complete() {
+                return Ok(false);
+            }
+            let result = sign_message_context.review_and_sign();
+            sign_message_context.reset();
+            *state = InstructionState::Idle;
+            let (signature_buf, length, _) = result?;
+            append_response(comm, ins, &[&signature_buf[..length as usize]])?;
+        }
     }
-    Ok(true)
+    // The review of a transaction in progress is kept on the screen
+    Ok(*state == InstructionState::Idle)
+}
+
+// Append the response data, followed by the MAC of the response if the response MAC is enabled
+fn append_response(comm: &mut io::Comm, ins: Ins, data: &[&[u8]]) -> Result<(), ErrorCode> {
+    data.iter().for_each(|bytes| comm.append(bytes));
+    if !is_response_mac_enabled() {
+        return Ok(());
+    }
+    let mut mac = ResponseMac::new(ins as u8)?;
+    for bytes in data {
+        mac.update(bytes)?;
+    }
+    comm.append(&mac.finalize()?);
+    Ok(())
 }
 
 // The transaction is split into multiple APDU commands, consisting of token metadata APDU and tx APDU commands
@@ -153,6 +601,17 @@ pub fn handle_apdu(
 // * `p1` = 0 and `p2` = 2 indicates the remaining token proof APDU frame
 // * `p1` = 1 and `p2` = 0 indicates the first tx APDU frame
 // * `p1` = 1 and `p2` = 1 indicates subsequent tx APDU frames
+// * `p1` = 1 and `p2` = 2 indicates the first tx APDU frame of a sweep tx, which starts with
+//   the number of paths and the paths that the tx spends from
+// * `p1` = 1 and `p2` = 3 indicates the first tx APDU frame of a preview, the tx is reviewed
+//   but not signed
+// * `p1` = 1 and `p2` = 4 indicates the end of a tx of an unrecognized format, whose rest
+//   is only hashed
+// * `p1` = 1 and `p2` = 5 indicates the first tx APDU frame of a chained tx, which starts with
+//   the index of the tx and the number of txs of the chained session
+// * `p1` = 2 and `p2` = i requests the signature of the i-th path of an approved sweep tx
+// * `p1` = 3 and `p2` = 0 indicates the outputs of a script tx simulated by the host, which
+//   are displayed as unverified estimates
 fn handle_sign_tx(
     apdu_header: &ApduHeader,
     data: &[u8],
@@ -175,26 +634,90 @@ fn handle_sign_tx(
         }
         (0, 1) => tx_reviewer.handle_token_metadata(data), // token metadata and proof frame
         (0, 2) => tx_reviewer.handle_token_proof(data),    // the following token proof frame
-        (1, 0) => {
+        (3, 0) => tx_reviewer.handle_estimated_outputs(data), // the simulated outputs of a script tx
+        (4, 0) => tx_reviewer.handle_nft_metadata(data),      // the collection and path of an NFT
+        (5, 0) => tx_reviewer.handle_input_paths(data),       // the device paths of the inputs
+        (1, 0) | (1, 3) => {
             // the first unsigned tx frame
             if data.len() < PATH_LENGTH + SCRIPT_OFFSET {
                 return Err(ErrorCode::BadLen);
             }
-            let tx_data = &data[PATH_LENGTH..];
-            let is_tx_execute_script = tx_data[SCRIPT_OFFSET - 1] == CALL_CONTRACT_FLAG;
-            if is_tx_execute_script {
-                tx_reviewer.check_blind_signing()?;
+            let (paths, tx_data) = data.split_at(PATH_LENGTH);
+            handle_first_tx_frame(
+                apdu_header,
+                paths,
+                tx_data,
+                None,
+                sign_tx_context,
+                tx_reviewer,
+            )
+        }
+        (1, 2) => {
+            // the first unsigned tx frame of a sweep tx
+            if data.is_empty() {
+                return Err(ErrorCode::BadLen);
             }
-            tx_reviewer.set_tx_execute_script(is_tx_execute_script);
-
-            sign_tx_context.init(&data[..PATH_LENGTH])?;
-            sign_tx_context.handle_tx_data(apdu_header, tx_data, tx_reviewer)
+            let paths_length = (data[0] as usize) * PATH_LENGTH;
+            if data.len() < 1 + paths_length + SCRIPT_OFFSET {
+                return Err(ErrorCode::BadLen);
+            }
+            let (paths, tx_data) = data[1..].split_at(paths_length);
+            handle_first_tx_frame(
+                apdu_header,
+                paths,
+                tx_data,
+                None,
+                sign_tx_context,
+                tx_reviewer,
+            )
+        }
+        (1, 5) => {
+            // the first unsigned tx frame of a chained tx
+            if data.len() < 2 + PATH_LENGTH + SCRIPT_OFFSET {
+                return Err(ErrorCode::BadLen);
+            }
+            let chained_tx = Some((data[0], data[1]));
+            let (paths, tx_data) = data[2..].split_at(PATH_LENGTH);
+            handle_first_tx_frame(
+                apdu_header,
+                paths,
+                tx_data,
+                chained_tx,
+                sign_tx_context,
+                tx_reviewer,
+            )
         }
         (1, 1) => sign_tx_context.handle_tx_data(apdu_header, data, tx_reviewer), // the following unsigned tx frame
+        (1, 4) => sign_tx_context.handle_tx_data(apdu_header, data, tx_reviewer), // the end of an unrecognized tx
         _ => Err(ErrorCode::BadP1P2),
     }
 }
 
+fn handle_first_tx_frame(
+    apdu_header: &ApduHeader,
+    paths: &[u8],
+    tx_data: &[u8],
+    chained_tx: Option<(u8, u8)>,
+    sign_tx_context: &mut SignTxContext,
+    tx_reviewer: &mut TxReviewer,
+) -> Result<(), ErrorCode> {
+    let is_tx_execute_script = tx_data[SCRIPT_OFFSET - 1] == CALL_CONTRACT_FLAG;
+    if is_tx_execute_script {
+        tx_reviewer.check_blind_signing()?;
+    } else if tx_reviewer.has_estimated_outputs() {
+        // Only the outputs of a script tx can be estimated
+        return Err(ErrorCode::BadP1P2);
+    }
+    tx_reviewer.set_tx_execute_script(is_tx_execute_script);
+    tx_reviewer.set_preview(apdu_header.p2 == PREVIEW_TX_P2);
+    sign_tx_context.set_chained_tx(chained_tx)?;
+    tx_reviewer.set_chained_tx(chained_tx);
+
+    sign_tx_context.init(paths)?;
+    tx_reviewer.set_testnet_key(sign_tx_context.has_testnet_path());
+    sign_tx_context.handle_tx_data(apdu_header, tx_data, tx_reviewer)
+}
+
 #[inline]
 fn check_token_size(size: u8) -> Result<(), ErrorCode> {
     if size > MAX_TOKEN_SIZE {
@@ -205,7 +728,12 @@ fn check_token_size(size: u8) -> Result<(), ErrorCode> {
 }
 
 #[inline]
-fn reset(sign_tx_context: &mut SignTxContext, tx_reviewer: &mut TxReviewer) {
+fn reset(
+    state: &mut InstructionState,
+    sign_tx_context: &mut SignTxContext,
+    tx_reviewer: &mut TxReviewer,
+) {
+    *state = InstructionState::Idle;
     sign_tx_context.reset();
     tx_reviewer.reset();
 }
@@ -4,15 +4,24 @@
 use crate::ui::tx_reviewer::TxReviewer;
 use handler::handle_apdu;
 use ledger_device_sdk::io;
+use sign_message_context::SignMessageContext;
 use sign_tx_context::SignTxContext;
+use utils::instruction_state::InstructionState;
 
+mod app_config;
+mod attestation;
 mod blake2b_hasher;
 mod debug;
 mod error_code;
+mod error_log;
 mod handler;
 mod nvm;
 mod public_key;
+mod response_mac;
+mod secret;
+mod self_test;
 mod settings;
+mod sign_message_context;
 mod sign_tx_context;
 mod token_verifier;
 mod ui;
@@ -26,7 +35,9 @@ extern "C" fn sample_main() {
 
     // Initialize the sign tx context and tx reviewer
     let mut sign_tx_context: SignTxContext = SignTxContext::new();
+    let mut sign_message_context = SignMessageContext::new();
     let mut tx_reviewer: TxReviewer = TxReviewer::new();
+    let mut state = InstructionState::default();
 
     #[cfg(not(any(target_os = "stax", target_os = "flex")))]
     {
@@ -38,7 +49,14 @@ extern "C" fn sample_main() {
             // Wait for either a specific button push to exit the app
             // or an APDU command
             if let io::Event::Command(ins) = main_pages.show::<Ins>(&mut comm) {
-                match handle_apdu(&mut comm, ins, &mut sign_tx_context, &mut tx_reviewer) {
+                match handle_apdu(
+                    &mut comm,
+                    ins,
+                    &mut state,
+                    &mut sign_message_context,
+                    &mut sign_tx_context,
+                    &mut tx_reviewer,
+                ) {
                     Ok(_) => comm.reply_ok(),
                     Err(sw) => comm.reply(sw),
                 }
@@ -55,7 +73,18 @@ extern "C" fn sample_main() {
         use ledger_device_sdk::nbgl::{NbglGlyph, NbglHomeAndSettings, PageIndex};
 
         const APP_ICON: NbglGlyph = NbglGlyph::from_include(include_gif!("alph_64x64.gif", NBGL));
-        let settings_strings: &[[&str; 2]] = &[["Blind signing", "Enable blind signing"]];
+        let settings_strings: &[[&str; 2]] = &[
+            ["Blind signing", "Enable blind signing"],
+            ["Response MAC", "Authenticate public keys and signatures"],
+            ["Mainnet only", "Refuse to sign for other networks"],
+            ["Display tx ID", "Show the transaction ID before signing"],
+            ["Expert mode", "Show gas, input refs and raw output details"],
+            ["Unknown tokens", "Warn about all tokens without metadata"],
+            [
+                "Skip cross-group",
+                "No warning about transfers to other groups",
+            ],
+        ];
         let mut home_and_settings = NbglHomeAndSettings::new()
             .glyph(&APP_ICON)
             .settings(unsafe { SETTINGS_DATA.get_mut() }, settings_strings)
@@ -70,17 +99,23 @@ extern "C" fn sample_main() {
 
         loop {
             if let io::Event::Command(ins) = comm.next_event() {
-                let display_home =
-                    match handle_apdu(&mut comm, ins, &mut sign_tx_context, &mut tx_reviewer) {
-                        Ok(result) => {
-                            comm.reply_ok();
-                            result
-                        }
-                        Err(sw) => {
-                            comm.reply(sw);
-                            true
-                        }
-                    };
+                let display_home = match handle_apdu(
+                    &mut comm,
+                    ins,
+                    &mut state,
+                    &mut sign_message_context,
+                    &mut sign_tx_context,
+                    &mut tx_reviewer,
+                ) {
+                    Ok(result) => {
+                        comm.reply_ok();
+                        result
+                    }
+                    Err(sw) => {
+                        comm.reply(sw);
+                        true
+                    }
+                };
                 if tx_reviewer.display_settings() {
                     tx_reviewer.reset_display_settings();
                     home_and_settings = home_and_settings.set_start_page(PageIndex::Settings(0));
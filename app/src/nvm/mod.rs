@@ -6,6 +6,8 @@ use crate::error_code::ErrorCode;
 pub mod swapping_buffer;
 
 pub const NVM_DATA_SIZE: usize = 2048;
+// The flash pages of the supported devices are at most 512 bytes
+pub const NVM_PAGE_SIZE: usize = 512;
 
 #[allow(clippy::upper_case_acronyms)]
 #[repr(align(64))]
@@ -16,20 +18,23 @@ impl<const N: usize> NVM<N> {
         Self([0; N])
     }
 
-    pub fn write(&mut self, from: usize, slice: &[u8]) -> bool {
+    // The written data is read back, the write fails if it does not match
+    pub fn write(&mut self, from: usize, slice: &[u8]) -> Result<(), ErrorCode> {
         let len = slice.len();
         if from + len > N {
-            return false;
+            return Err(ErrorCode::Overflow);
         }
 
         unsafe {
             let dst = self.0[from..].as_mut_ptr() as *mut _;
             let src = slice.as_ptr() as *mut u8 as *mut _;
             nvm_write(dst, src, len as u32);
-
-            assert_eq!(&self.0[from..(from + len)], slice);
         };
-        true
+        if &self.0[from..(from + len)] == slice {
+            Ok(())
+        } else {
+            Err(ErrorCode::NvmWriteFailed)
+        }
     }
 }
 
@@ -38,10 +43,5 @@ pub fn write_from<const N: usize>(
     from_index: usize,
     bytes: &[u8],
 ) -> Result<(), ErrorCode> {
-    let data = nvm_data.get_mut();
-    if data.write(from_index, bytes) {
-        Ok(())
-    } else {
-        Err(ErrorCode::Overflow)
-    }
+    nvm_data.get_mut().write(from_index, bytes)
 }
@@ -3,11 +3,14 @@ use utils::buffer::Writable;
 
 use crate::{
     error_code::ErrorCode,
-    nvm::{write_from, NVM},
+    nvm::{write_from, NVM, NVM_PAGE_SIZE},
 };
 use ledger_device_sdk::NVMData;
 
 pub const RAM_SIZE: usize = 512;
+// A failed flash write is retried once at a fresh offset
+const FLASH_WRITE_ATTEMPTS: usize = 2;
+const FLASH_COPY_CHUNK_SIZE: usize = 64;
 
 #[derive(Clone, Copy)]
 enum BufferState {
@@ -25,6 +28,12 @@ pub struct SwappingBuffer<'a, const RAM: usize, const FLASH: usize> {
     ram: [u8; RAM],
     flash: &'a mut NVMData<NVM<FLASH>>,
     state: BufferState,
+    // The flash offset of the index 0, it is moved past a region whose write fails, so that
+    // the indexes held by the callers remain valid
+    flash_offset: usize,
+    // Set once a flash write fails at a fresh offset as well, only the data that fits in RAM
+    // can be written afterwards
+    is_flash_failed: bool,
 }
 
 impl<'a, const RAM: usize, const FLASH: usize> SwappingBuffer<'a, RAM, FLASH> {
@@ -33,6 +42,8 @@ impl<'a, const RAM: usize, const FLASH: usize> SwappingBuffer<'a, RAM, FLASH> {
             ram: [0u8; RAM],
             flash,
             state: BufferState::default(),
+            flash_offset: 0,
+            is_flash_failed: false,
         }
     }
 
@@ -43,8 +54,9 @@ impl<'a, const RAM: usize, const FLASH: usize> SwappingBuffer<'a, RAM, FLASH> {
                 &self.ram[from_index..to_index]
             }
             BufferState::WritingToFlash(_) => {
-                assert!(from_index < to_index && to_index <= FLASH);
-                &self.flash.get_ref().0[from_index..to_index]
+                assert!(from_index < to_index && self.flash_offset + to_index <= FLASH);
+                &self.flash.get_ref().0
+                    [(self.flash_offset + from_index)..(self.flash_offset + to_index)]
             }
         }
     }
@@ -52,7 +64,9 @@ impl<'a, const RAM: usize, const FLASH: usize> SwappingBuffer<'a, RAM, FLASH> {
     pub fn read_all(&self) -> &[u8] {
         match self.state {
             BufferState::WritingToRam(index) => &self.ram[..index],
-            BufferState::WritingToFlash(index) => &self.flash.get_ref().0[..index],
+            BufferState::WritingToFlash(index) => {
+                &self.flash.get_ref().0[self.flash_offset..(self.flash_offset + index)]
+            }
         }
     }
 
@@ -71,15 +85,22 @@ impl<'a, const RAM: usize, const FLASH: usize> SwappingBuffer<'a, RAM, FLASH> {
 
     #[inline]
     fn write_to_nvm(&mut self, data: &[u8], from: usize) -> Result<(), ErrorCode> {
-        write_from(self.flash, from, data)?;
+        self.write_to_flash(from, data, from)?;
         self.state = BufferState::WritingToFlash(from + data.len());
         Ok(())
     }
 
     #[inline]
     fn switch_to_nvm(&mut self, ram_length: usize, data: &[u8]) -> Result<(), ErrorCode> {
-        write_from(self.flash, 0, &self.ram[..ram_length])?;
-        write_from(self.flash, ram_length, data)?;
+        write_to_flash(
+            self.flash,
+            &mut self.flash_offset,
+            &mut self.is_flash_failed,
+            0,
+            &self.ram[..ram_length],
+            0,
+        )?;
+        self.write_to_flash(ram_length, data, ram_length)?;
         self.state = BufferState::WritingToFlash(ram_length + data.len());
         Ok(())
     }
@@ -118,16 +139,17 @@ impl<'a, const RAM: usize, const FLASH: usize> SwappingBuffer<'a, RAM, FLASH> {
         }
     }
 
-    pub fn update(&mut self, from_index: usize, data: &[u8]) {
+    pub fn update(&mut self, from_index: usize, data: &[u8]) -> Result<(), ErrorCode> {
         let size = data.len();
         match self.state {
             BufferState::WritingToRam(_) => {
                 assert!(from_index + size <= RAM);
                 self.ram[from_index..(from_index + size)].copy_from_slice(data);
+                Ok(())
             }
-            BufferState::WritingToFlash(_) => {
-                assert!(from_index + size <= FLASH);
-                write_from(self.flash, from_index, data).unwrap();
+            BufferState::WritingToFlash(index) => {
+                assert!(self.flash_offset + from_index + size <= FLASH);
+                self.write_to_flash(from_index, data, index)
             }
         }
     }
@@ -135,6 +157,98 @@ impl<'a, const RAM: usize, const FLASH: usize> SwappingBuffer<'a, RAM, FLASH> {
     pub fn reset(&mut self, from_index: usize) {
         self.state = BufferState::WritingToRam(from_index);
     }
+
+    // Reset the buffer for a new session, the flash is written from the offset 0 again even
+    // if a write failed in the previous session
+    pub fn clear(&mut self) {
+        self.state = BufferState::default();
+        self.flash_offset = 0;
+        self.is_flash_failed = false;
+    }
+
+    // The size of the data that can be stored, only RAM is used once a flash write fails
+    pub fn capacity(&self) -> usize {
+        if self.is_flash_failed {
+            RAM
+        } else {
+            FLASH - self.flash_offset
+        }
+    }
+
+    #[inline]
+    fn write_to_flash(
+        &mut self,
+        from: usize,
+        data: &[u8],
+        valid_length: usize,
+    ) -> Result<(), ErrorCode> {
+        write_to_flash(
+            self.flash,
+            &mut self.flash_offset,
+            &mut self.is_flash_failed,
+            from,
+            data,
+            valid_length,
+        )
+    }
+}
+
+// Write to the flash at the index `from`, the first `valid_length` bytes are already written.
+// If the write cannot be verified, the valid bytes are copied to the page after the failed
+// region and the write is retried there. The buffer falls back to RAM if it fails again.
+fn write_to_flash<const FLASH: usize>(
+    flash: &mut NVMData<NVM<FLASH>>,
+    flash_offset: &mut usize,
+    is_flash_failed: &mut bool,
+    from: usize,
+    data: &[u8],
+    valid_length: usize,
+) -> Result<(), ErrorCode> {
+    if *is_flash_failed {
+        return Err(ErrorCode::NvmWriteFailed);
+    }
+    let mut result = write_from(flash, *flash_offset + from, data);
+    for _ in 1..FLASH_WRITE_ATTEMPTS {
+        if !matches!(result, Err(ErrorCode::NvmWriteFailed)) {
+            break;
+        }
+        // The fresh region starts after both the failed write and the valid bytes, so that the
+        // copied bytes do not overlap
+        let length = valid_length.max(from + data.len());
+        let new_offset = (*flash_offset + length).div_ceil(NVM_PAGE_SIZE) * NVM_PAGE_SIZE;
+        if new_offset + length > FLASH {
+            break;
+        }
+        result = copy_flash(flash, *flash_offset, new_offset, valid_length);
+        if result.is_ok() {
+            *flash_offset = new_offset;
+            result = write_from(flash, new_offset + from, data);
+        }
+    }
+    if let Err(ErrorCode::NvmWriteFailed) = result {
+        *is_flash_failed = true;
+    }
+    result
+}
+
+// Copy the bytes in chunks, the source is not passed to the NVM write directly as it is in
+// the same flash data
+fn copy_flash<const FLASH: usize>(
+    flash: &mut NVMData<NVM<FLASH>>,
+    from_offset: usize,
+    to_offset: usize,
+    length: usize,
+) -> Result<(), ErrorCode> {
+    let mut chunk = [0u8; FLASH_COPY_CHUNK_SIZE];
+    let mut index = 0;
+    while index < length {
+        let size = FLASH_COPY_CHUNK_SIZE.min(length - index);
+        let from = from_offset + index;
+        chunk[..size].copy_from_slice(&flash.get_ref().0[from..(from + size)]);
+        write_from(flash, to_offset + index, &chunk[..size])?;
+        index += size;
+    }
+    Ok(())
 }
 
 impl<'a, const RAM: usize, const FLASH: usize> Writable for SwappingBuffer<'a, RAM, FLASH> {
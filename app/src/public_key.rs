@@ -1,25 +1,39 @@
 use crate::blake2b_hasher::{Blake2bHasher, BLAKE2B_HASH_SIZE};
 use crate::error_code::ErrorCode;
+use crate::secret::{PrivateKey, Secret};
 use ledger_device_sdk::ecc::SeedDerive;
 use ledger_device_sdk::ecc::{ECPublicKey, Secp256k1};
 use ledger_device_sdk::io::Reply;
+use ledger_secure_sdk_sys::*;
 use utils::base58::base58_encode_inputs;
-use utils::{check_group, djb_hash, xor_bytes};
+pub use utils::types::public_key::compress_public_key;
+use utils::{check_group, djb_hash, get_key_type, xor_bytes, KeyType};
 
 const RAW_PUBKEY_SIZE: usize = 65;
-const COMPRESSED_PUBKEY_SIZE: usize = 33;
+pub const COMPRESSED_PUBKEY_SIZE: usize = 33;
+const PRIVATE_KEY_SIZE: usize = 32;
+const SCHNORR_SIGNATURE_SIZE: usize = 64;
+const P2PKH_PREFIX: u8 = 0x00;
+const P2SH_PREFIX: u8 = 0x02;
+// A Schnorr address is the P2SH address of the script that verifies the BIP340 signature
+// of the tx id with the x-only public key
+const SCHNORR_SCRIPT_PREFIX: [u8; 11] = [
+    0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x04, 0x58, 0x14, 0x40, 0x20,
+];
+const SCHNORR_SCRIPT_SUFFIX: [u8; 2] = [0x86, 0x85];
 
 pub fn derive_pub_key(
     path: &mut [u32],
     group_num: u8,
     target_group: u8,
+    key_type: KeyType,
 ) -> Result<(ECPublicKey<65, 'W'>, u32), Reply> {
     check_group::<Reply>(group_num, target_group, ErrorCode::BadP1P2.into())?;
     if group_num == 0 {
         let pub_key = derive_pub_key_by_path(path)?;
         Ok((pub_key, path[path.len() - 1]))
     } else {
-        derive_pub_key_for_group(path, group_num, target_group)
+        derive_pub_key_for_group(path, group_num, target_group, key_type)
     }
 }
 
@@ -36,64 +50,256 @@ fn derive_pub_key_for_group(
     path: &mut [u32],
     group_num: u8,
     target_group: u8,
+    key_type: KeyType,
 ) -> Result<(ECPublicKey<65, 'W'>, u32), Reply> {
     loop {
         let pk = derive_pub_key_by_path(path)?;
-        if get_pub_key_group(pk.as_ref(), group_num) == target_group {
+        if get_pub_key_group(pk.as_ref(), group_num, key_type)? == target_group {
             return Ok((pk, path[path.len() - 1]));
         }
         path[path.len() - 1] += 1;
     }
 }
 
-pub fn hash_of_public_key(pub_key: &[u8]) -> [u8; BLAKE2B_HASH_SIZE] {
-    assert!(pub_key.len() == RAW_PUBKEY_SIZE);
-    let mut compressed = [0_u8; COMPRESSED_PUBKEY_SIZE];
-    compressed[1..COMPRESSED_PUBKEY_SIZE].copy_from_slice(&pub_key[1..COMPRESSED_PUBKEY_SIZE]);
-    if pub_key.last().unwrap() % 2 == 0 {
-        compressed[0] = 0x02
+// The maximum number of public keys of a batch, the response of a batch with the response MAC
+// has to fit in a single APDU
+pub const MAX_BATCH_PUBLIC_KEYS: usize = 6;
+// Each key of a batch is the compressed public key followed by the derivation index
+pub const BATCH_PUBLIC_KEY_SIZE: usize = COMPRESSED_PUBKEY_SIZE + 4;
+
+// Derive the public keys of consecutive indexes starting from the index of the path,
+// and return the size of the encoded keys
+pub fn derive_pub_keys(
+    path: &mut [u32],
+    group_num: u8,
+    target_group: u8,
+    key_type: KeyType,
+    count: usize,
+    output: &mut [u8; MAX_BATCH_PUBLIC_KEYS * BATCH_PUBLIC_KEY_SIZE],
+) -> Result<usize, Reply> {
+    assert!(count <= MAX_BATCH_PUBLIC_KEYS);
+    let last = path.len() - 1;
+    for index in 0..count {
+        if index > 0 {
+            path[last] = path[last]
+                .checked_add(1)
+                .ok_or(ErrorCode::HDPathDecodingFailed)?;
+        }
+        let (pk, hd_index) = derive_pub_key(path, group_num, target_group, key_type)?;
+        let from = index * BATCH_PUBLIC_KEY_SIZE;
+        output[from..(from + COMPRESSED_PUBKEY_SIZE)]
+            .copy_from_slice(&compress_public_key(pk.as_ref()));
+        output[(from + COMPRESSED_PUBKEY_SIZE)..(from + BATCH_PUBLIC_KEY_SIZE)]
+            .copy_from_slice(&hd_index.to_be_bytes());
+    }
+    Ok(count * BATCH_PUBLIC_KEY_SIZE)
+}
+
+pub const CHAIN_CODE_SIZE: usize = 32;
+const FINGERPRINT_SIZE: usize = 4;
+// The compressed public key, the chain code and the fingerprint of the parent key
+pub const EXTENDED_PUBLIC_KEY_SIZE: usize =
+    COMPRESSED_PUBKEY_SIZE + CHAIN_CODE_SIZE + FINGERPRINT_SIZE;
+
+// Derive the BIP32 extended public key of a path, so that the host can derive the
+// non-hardened child keys without the device
+pub fn derive_extended_pub_key(path: &[u32]) -> Result<[u8; EXTENDED_PUBLIC_KEY_SIZE], Reply> {
+    assert!(path.len() > 1);
+    let pub_key = derive_pub_key_by_path(path)?;
+    let chain_code = derive_chain_code(path)?;
+    let parent_pub_key = derive_pub_key_by_path(&path[..path.len() - 1])?;
+    let fingerprint = hash160(&compress_public_key(parent_pub_key.as_ref()))?;
+
+    let mut output = [0u8; EXTENDED_PUBLIC_KEY_SIZE];
+    let (key, rest) = output.split_at_mut(COMPRESSED_PUBKEY_SIZE);
+    key.copy_from_slice(&compress_public_key(pub_key.as_ref()));
+    rest[..CHAIN_CODE_SIZE].copy_from_slice(&chain_code);
+    rest[CHAIN_CODE_SIZE..].copy_from_slice(&fingerprint[..FINGERPRINT_SIZE]);
+    Ok(output)
+}
+
+// The root of the Alephium keys, all the account keys are derived from it
+const WALLET_ROOT_PATH: [u32; 2] = [0x8000002c, 0x800004d2];
+pub const WALLET_FINGERPRINT_SIZE: usize = FINGERPRINT_SIZE;
+
+// The wallet fingerprint is the first 4 bytes of the HASH160 of the compressed public key of
+// `m/44'/1234'`, users compare it with the wallet id shown by the coordinator software
+pub fn derive_wallet_fingerprint() -> Result<[u8; WALLET_FINGERPRINT_SIZE], Reply> {
+    let pub_key = derive_pub_key_by_path(&WALLET_ROOT_PATH)?;
+    let hash = hash160(&compress_public_key(pub_key.as_ref()))?;
+    let mut fingerprint = [0u8; WALLET_FINGERPRINT_SIZE];
+    fingerprint.copy_from_slice(&hash[..WALLET_FINGERPRINT_SIZE]);
+    Ok(fingerprint)
+}
+
+fn derive_chain_code(path: &[u32]) -> Result<[u8; CHAIN_CODE_SIZE], ErrorCode> {
+    let mut raw_key = Secret::<PRIVATE_KEY_SIZE>::new();
+    let mut chain_code = [0u8; CHAIN_CODE_SIZE];
+    let rc = unsafe {
+        os_derive_bip32_no_throw(
+            CX_CURVE_SECP256K1,
+            path.as_ptr(),
+            path.len() as u32,
+            raw_key.as_mut_bytes().as_mut_ptr(),
+            chain_code.as_mut_ptr(),
+        )
+    };
+    if rc == CX_OK {
+        Ok(chain_code)
+    } else {
+        Err(ErrorCode::DerivingPublicKeyFailed)
+    }
+}
+
+// The RIPEMD160 hash of the SHA256 hash, the BIP32 fingerprint is the first 4 bytes
+fn hash160(input: &[u8]) -> Result<[u8; 20], ErrorCode> {
+    let mut sha256 = [0u8; 32];
+    let mut result = [0u8; 20];
+    let mut ripemd160 = cx_ripemd160_t::default();
+    let rc = unsafe {
+        cx_hash_sha256(
+            input.as_ptr(),
+            input.len(),
+            sha256.as_mut_ptr(),
+            sha256.len(),
+        );
+        let mut rc = cx_ripemd160_init_no_throw(&mut ripemd160);
+        if rc == CX_OK {
+            rc = cx_hash_no_throw(
+                &mut ripemd160 as *mut cx_ripemd160_t as *mut cx_hash_t,
+                CX_LAST,
+                sha256.as_ptr(),
+                sha256.len(),
+                result.as_mut_ptr(),
+                result.len(),
+            );
+        }
+        rc
+    };
+    if rc == CX_OK {
+        Ok(result)
     } else {
-        compressed[0] = 0x03
+        Err(ErrorCode::DerivingPublicKeyFailed)
     }
+}
+
+pub fn hash_of_public_key(pub_key: &[u8]) -> [u8; BLAKE2B_HASH_SIZE] {
+    Blake2bHasher::hash(&compress_public_key(pub_key)).unwrap()
+}
+
+// Schnorr keys use the x-only public key, which is the x coordinate of the raw public key
+fn hash_of_schnorr_script(pub_key: &[u8]) -> Result<[u8; BLAKE2B_HASH_SIZE], ErrorCode> {
+    assert!(pub_key.len() == RAW_PUBKEY_SIZE);
+    let mut hasher = Blake2bHasher::new();
+    hasher.update(&SCHNORR_SCRIPT_PREFIX)?;
+    hasher.update(&pub_key[1..COMPRESSED_PUBKEY_SIZE])?;
+    hasher.update(&SCHNORR_SCRIPT_SUFFIX)?;
+    hasher.finalize()
+}
 
-    Blake2bHasher::hash(&compressed).unwrap()
+// Whether the script is the Schnorr script of an x-only public key
+pub fn is_schnorr_script(script: &[u8]) -> bool {
+    script.len()
+        == SCHNORR_SCRIPT_PREFIX.len() + COMPRESSED_PUBKEY_SIZE - 1 + SCHNORR_SCRIPT_SUFFIX.len()
+        && script.starts_with(&SCHNORR_SCRIPT_PREFIX)
+        && script.ends_with(&SCHNORR_SCRIPT_SUFFIX)
+}
+
+// Get the address prefix and the lockup script hash of a public key
+fn lockup_script_of_public_key(
+    pub_key: &[u8],
+    key_type: KeyType,
+) -> Result<(u8, [u8; BLAKE2B_HASH_SIZE]), ErrorCode> {
+    match key_type {
+        KeyType::Default => Ok((P2PKH_PREFIX, hash_of_public_key(pub_key))),
+        KeyType::Schnorr => Ok((P2SH_PREFIX, hash_of_schnorr_script(pub_key)?)),
+    }
 }
 
-fn get_pub_key_group(pub_key: &[u8], group_num: u8) -> u8 {
-    let pub_key_hash = hash_of_public_key(pub_key);
-    let script_hint = djb_hash(&pub_key_hash) | 1;
+fn get_pub_key_group(pub_key: &[u8], group_num: u8, key_type: KeyType) -> Result<u8, ErrorCode> {
+    let (_, lockup_script_hash) = lockup_script_of_public_key(pub_key, key_type)?;
+    let script_hint = djb_hash(&lockup_script_hash) | 1;
     let group_index = xor_bytes(script_hint);
-    group_index % group_num
+    Ok(group_index % group_num)
 }
 
 pub fn sign_hash(path: &[u32], message: &[u8]) -> Result<([u8; 72], u32, u32), ErrorCode> {
-    Secp256k1::derive_from_path(path)
-        .deterministic_sign(message)
-        .map_err(|_| ErrorCode::TxSigningFailed)
+    match get_key_type(path, ErrorCode::HDPathDecodingFailed)? {
+        KeyType::Default => Secp256k1::derive_from_path(path)
+            .deterministic_sign(message)
+            .map_err(|_| ErrorCode::TxSigningFailed),
+        KeyType::Schnorr => schnorr_sign_hash(path, message),
+    }
+}
+
+// Sign the hash with BIP340 Schnorr, the signature is 64 bytes
+fn schnorr_sign_hash(path: &[u32], message: &[u8]) -> Result<([u8; 72], u32, u32), ErrorCode> {
+    let mut raw_key = Secret::<PRIVATE_KEY_SIZE>::new();
+    let mut private_key = PrivateKey::new();
+    let mut signature = [0u8; 72];
+    let mut signature_length = signature.len();
+    let rc = unsafe {
+        let mut rc = os_derive_bip32_no_throw(
+            CX_CURVE_SECP256K1,
+            path.as_ptr(),
+            path.len() as u32,
+            raw_key.as_mut_bytes().as_mut_ptr(),
+            core::ptr::null_mut(),
+        );
+        if rc == CX_OK {
+            rc = cx_ecfp_init_private_key_no_throw(
+                CX_CURVE_SECP256K1,
+                raw_key.as_bytes().as_ptr(),
+                PRIVATE_KEY_SIZE,
+                &mut private_key.0,
+            );
+        }
+        if rc == CX_OK {
+            rc = cx_ecschnorr_sign_no_throw(
+                &private_key.0,
+                CX_ECSCHNORR_BIP0340 | CX_RND_TRNG,
+                CX_SHA256,
+                message.as_ptr(),
+                message.len(),
+                signature.as_mut_ptr(),
+                &mut signature_length,
+            );
+        }
+        rc
+    };
+    if rc != CX_OK || signature_length != SCHNORR_SIGNATURE_SIZE {
+        return Err(ErrorCode::TxSigningFailed);
+    }
+    Ok((signature, SCHNORR_SIGNATURE_SIZE as u32, 0))
 }
 
 pub struct Address {
     bytes: [u8; 46],
     length: usize,
+    pub key_type: KeyType,
 }
 
 impl Address {
     pub fn from_path(path: &[u32]) -> Result<Self, ErrorCode> {
-        let mut bytes = [0u8; 46];
+        let key_type = get_key_type(path, ErrorCode::HDPathDecodingFailed)?;
         let device_public_key =
             derive_pub_key_by_path(path).map_err(|_| ErrorCode::DerivingPublicKeyFailed)?;
-        let public_key_hash = hash_of_public_key(device_public_key.as_ref());
-        let device_address = to_base58_address(0u8, &public_key_hash, &mut bytes)?;
-        let length = device_address.len();
-        Ok(Self { bytes, length })
+        Self::from_pub_key(&device_public_key, key_type)
     }
 
-    pub fn from_pub_key(pub_key: &ECPublicKey<65, 'W'>) -> Result<Self, ErrorCode> {
+    pub fn from_pub_key(
+        pub_key: &ECPublicKey<65, 'W'>,
+        key_type: KeyType,
+    ) -> Result<Self, ErrorCode> {
         let mut bytes = [0u8; 46];
-        let public_key_hash = hash_of_public_key(pub_key.as_ref());
-        let device_address = to_base58_address(0u8, &public_key_hash, &mut bytes)?;
+        let (prefix, lockup_script_hash) = lockup_script_of_public_key(pub_key.as_ref(), key_type)?;
+        let device_address = to_base58_address(prefix, &lockup_script_hash, &mut bytes)?;
         let length = device_address.len();
-        Ok(Self { bytes, length })
+        Ok(Self {
+            bytes,
+            length,
+            key_type,
+        })
     }
 
     pub fn get_address_bytes(&self) -> &[u8] {
@@ -105,6 +311,100 @@ impl Address {
     }
 }
 
+// The maximum number of paths that a sweep transaction can be signed with
+pub const MAX_SIGNING_PATHS: usize = 4;
+
+// The addresses of all the paths that are used to sign a transaction
+// The addresses are derived once per signing session, and are kept for the next txs of the
+// session as long as they are signed with the same paths
+pub struct DeviceAddresses {
+    addresses: [Option<Address>; MAX_SIGNING_PATHS],
+    paths: [[u32; 5]; MAX_SIGNING_PATHS],
+    path_size: usize,
+}
+
+impl DeviceAddresses {
+    pub fn new() -> Self {
+        Self {
+            addresses: [None, None, None, None],
+            paths: [[0; 5]; MAX_SIGNING_PATHS],
+            path_size: 0,
+        }
+    }
+
+    pub fn init(&mut self, paths: &[[u32; 5]]) -> Result<(), ErrorCode> {
+        assert!(paths.len() <= MAX_SIGNING_PATHS);
+        if self.path_size == paths.len() && self.paths[..self.path_size] == *paths {
+            return Ok(());
+        }
+        self.reset();
+        for (address, path) in self.addresses.iter_mut().zip(paths) {
+            *address = Some(Address::from_path(path)?);
+        }
+        self.paths[..paths.len()].copy_from_slice(paths);
+        self.path_size = paths.len();
+        Ok(())
+    }
+
+    pub fn reset(&mut self) {
+        self.addresses = [None, None, None, None];
+        self.paths = [[0; 5]; MAX_SIGNING_PATHS];
+        self.path_size = 0;
+    }
+
+    pub fn contains(&self, addr: &[u8]) -> bool {
+        self.addresses
+            .iter()
+            .flatten()
+            .any(|address| address.eq(addr))
+    }
+
+    // The index of the path of the address
+    pub fn index_of(&self, addr: &[u8]) -> Option<usize> {
+        self.addresses
+            .iter()
+            .position(|address| matches!(address, Some(address) if address.eq(addr)))
+    }
+}
+
+// The maximum number of other device paths that the inputs of a tx can be spent from
+pub const MAX_INPUT_PATHS: usize = 6;
+
+// The addresses of the device paths that the host attaches for the inputs of a tx, they are
+// only used to recognize the inputs from the device, the tx is not signed with them
+pub struct InputAddresses {
+    addresses: [Option<Address>; MAX_INPUT_PATHS],
+}
+
+impl InputAddresses {
+    pub fn new() -> Self {
+        Self {
+            addresses: [const { None }; MAX_INPUT_PATHS],
+        }
+    }
+
+    pub fn add(&mut self, path: &[u32]) -> Result<(), ErrorCode> {
+        match self.addresses.iter_mut().find(|address| address.is_none()) {
+            Some(address) => {
+                *address = Some(Address::from_path(path)?);
+                Ok(())
+            }
+            None => Err(ErrorCode::BadLen),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.addresses = [const { None }; MAX_INPUT_PATHS];
+    }
+
+    pub fn contains(&self, addr: &[u8]) -> bool {
+        self.addresses
+            .iter()
+            .flatten()
+            .any(|address| address.eq(addr))
+    }
+}
+
 #[inline]
 pub fn to_base58_address<'a>(
     prefix: u8,
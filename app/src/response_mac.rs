@@ -0,0 +1,97 @@
+use crate::error_code::ErrorCode;
+use crate::secret::{wipe, Secret};
+use ledger_secure_sdk_sys::*;
+
+pub const MAC_KEY_SIZE: usize = 32;
+pub const MAC_SIZE: usize = 32;
+// The SLIP-21 label of the response MAC key, the leading zero byte is required by SLIP-21
+const SLIP21_LABEL: &[u8] = b"\0ALEPHIUM RESPONSE MAC";
+const SLIP21_NODE_SIZE: usize = 64;
+
+// Derive the SLIP-21 key that is used to authenticate the responses, the key is the
+// right half of the SLIP-21 node
+pub fn derive_mac_key() -> Result<Secret<MAC_KEY_SIZE>, ErrorCode> {
+    let mut node = Secret::<SLIP21_NODE_SIZE>::new();
+    unsafe {
+        os_perso_derive_node_with_seed_key(
+            HDW_SLIP21,
+            CX_CURVE_SECP256K1,
+            SLIP21_LABEL.as_ptr() as *const u32,
+            SLIP21_LABEL.len() as u32,
+            node.as_mut_bytes().as_mut_ptr(),
+            core::ptr::null_mut(),
+            core::ptr::null_mut(),
+            0,
+        )
+    };
+    let mut key = Secret::<MAC_KEY_SIZE>::new();
+    key.as_mut_bytes()
+        .copy_from_slice(&node.as_bytes()[(SLIP21_NODE_SIZE - MAC_KEY_SIZE)..]);
+    Ok(key)
+}
+
+// A wrapper around the Ledger SDK's HMAC-SHA256 implementation
+pub struct ResponseMac(cx_hmac_sha256_t);
+
+impl ResponseMac {
+    // The MAC of a response is computed over the instruction and the response data,
+    // so that a response cannot be used as the response of another instruction
+    pub fn new(ins: u8) -> Result<Self, ErrorCode> {
+        let key = derive_mac_key()?;
+        let mut context = cx_hmac_sha256_t::default();
+        let rc = unsafe {
+            cx_hmac_sha256_init_no_throw(&mut context, key.as_bytes().as_ptr(), MAC_KEY_SIZE)
+        };
+        if rc != CX_OK {
+            return Err(ErrorCode::InternalError);
+        }
+        let mut mac = Self(context);
+        mac.update(&[ins])?;
+        Ok(mac)
+    }
+
+    pub fn update(&mut self, input: &[u8]) -> Result<(), ErrorCode> {
+        let rc = unsafe {
+            cx_hmac_update(
+                &mut self.0 as *mut cx_hmac_sha256_t as *mut cx_hmac_t,
+                input.as_ptr(),
+                input.len(),
+            )
+        };
+        if rc == CX_OK {
+            Ok(())
+        } else {
+            Err(ErrorCode::InternalError)
+        }
+    }
+
+    pub fn finalize(&mut self) -> Result<[u8; MAC_SIZE], ErrorCode> {
+        let mut result = [0u8; MAC_SIZE];
+        let mut length = MAC_SIZE;
+        let rc = unsafe {
+            cx_hmac_final(
+                &mut self.0 as *mut cx_hmac_sha256_t as *mut cx_hmac_t,
+                result.as_mut_ptr(),
+                &mut length,
+            )
+        };
+        if rc == CX_OK && length == MAC_SIZE {
+            Ok(result)
+        } else {
+            Err(ErrorCode::InternalError)
+        }
+    }
+}
+
+// The HMAC context holds the padded key, so it is wiped with the key
+impl Drop for ResponseMac {
+    fn drop(&mut self) {
+        let bytes = unsafe {
+            core::slice::from_raw_parts_mut(
+                &mut self.0 as *mut cx_hmac_sha256_t as *mut u8,
+                core::mem::size_of::<cx_hmac_sha256_t>(),
+            )
+        };
+        wipe(bytes);
+    }
+}
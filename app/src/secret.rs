@@ -0,0 +1,52 @@
+use core::sync::atomic::{compiler_fence, Ordering};
+use ledger_secure_sdk_sys::cx_ecfp_private_key_t;
+
+// Wipe the bytes with volatile writes, so that the compiler does not elide the writes to a
+// buffer that is not read anymore
+pub fn wipe(bytes: &mut [u8]) {
+    for byte in bytes.iter_mut() {
+        unsafe { core::ptr::write_volatile(byte, 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+    #[cfg(feature = "debug")]
+    assert!(bytes.iter().all(|&byte| byte == 0));
+}
+
+// A buffer of key material, e.g. a raw private key or a SLIP-21 node, that is wiped when it
+// goes out of scope, including on early returns
+pub struct Secret<const N: usize>([u8; N]);
+
+impl<const N: usize> Secret<N> {
+    pub fn new() -> Self {
+        Self([0; N])
+    }
+
+    pub fn as_bytes(&self) -> &[u8; N] {
+        &self.0
+    }
+
+    pub fn as_mut_bytes(&mut self) -> &mut [u8; N] {
+        &mut self.0
+    }
+}
+
+impl<const N: usize> Drop for Secret<N> {
+    fn drop(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+// A private key that is initialized from a raw key, the scalar is wiped when it goes out of scope
+pub struct PrivateKey(pub cx_ecfp_private_key_t);
+
+impl PrivateKey {
+    pub fn new() -> Self {
+        Self(cx_ecfp_private_key_t::default())
+    }
+}
+
+impl Drop for PrivateKey {
+    fn drop(&mut self) {
+        wipe(&mut self.0.d);
+    }
+}
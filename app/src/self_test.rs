@@ -0,0 +1,110 @@
+use crate::blake2b_hasher::Blake2bHasher;
+use ledger_device_sdk::nvm::{AtomicStorage, SingleStorage};
+use ledger_device_sdk::NVMData;
+use ledger_secure_sdk_sys::*;
+use utils::{base58::base58_encode, types::U256};
+
+// The bits of the self-test result, a bit is set if the test passed
+pub const BLAKE2B_PASSED: u8 = 1 << 0;
+pub const BASE58_PASSED: u8 = 1 << 1;
+pub const U256_PASSED: u8 = 1 << 2;
+pub const SIGNATURE_PASSED: u8 = 1 << 3;
+pub const NVM_PASSED: u8 = 1 << 4;
+
+const NVM_TEST_SIZE: usize = 4;
+#[link_section = ".nvm_data"]
+static mut NVM_TEST_DATA: NVMData<AtomicStorage<[u8; NVM_TEST_SIZE]>> =
+    NVMData::new(AtomicStorage::new(&[0u8; NVM_TEST_SIZE]));
+
+// blake2b-256("abc")
+const BLAKE2B_ABC: [u8; 32] = [
+    0xbd, 0xdd, 0x81, 0x3c, 0x63, 0x42, 0x39, 0x72, 0x31, 0x71, 0xef, 0x3f, 0xee, 0x98, 0x57, 0x9b,
+    0x94, 0x96, 0x4e, 0x3b, 0xb1, 0xcb, 0x3e, 0x42, 0x72, 0x62, 0xc8, 0xc0, 0x68, 0xd5, 0x23, 0x19,
+];
+// The encoded 1.5 ALPH
+const ENCODED_AMOUNT: [u8; 9] = [0xc4, 0x14, 0xd1, 0x12, 0x0d, 0x7b, 0x16, 0x00, 0x00];
+// A test private key that is not derived from the seed
+const TEST_PRIVATE_KEY: [u8; 32] = [0x01; 32];
+
+// Run the known-answer tests and return the bitmap of the passed tests
+pub fn run_self_test() -> u8 {
+    let tests: [(u8, fn() -> bool); 5] = [
+        (BLAKE2B_PASSED, test_blake2b),
+        (BASE58_PASSED, test_base58),
+        (U256_PASSED, test_u256),
+        (SIGNATURE_PASSED, test_signature),
+        (NVM_PASSED, test_nvm),
+    ];
+    tests
+        .iter()
+        .filter(|(_, test)| test())
+        .fold(0, |result, (bit, _)| result | bit)
+}
+
+fn test_blake2b() -> bool {
+    matches!(Blake2bHasher::hash(b"abc"), Ok(hash) if hash == BLAKE2B_ABC)
+}
+
+fn test_base58() -> bool {
+    let mut output = [0u8; 8];
+    matches!(base58_encode(b"\0abc", &mut output), Some(result) if result == b"1ZiCa")
+}
+
+fn test_u256() -> bool {
+    let mut output = [0u8; 33];
+    let amount = U256::from_encoded_bytes(&ENCODED_AMOUNT);
+    matches!(amount.to_alph(&mut output, None), Some(result) if result == b"ALPH 1.5")
+}
+
+// Sign a hash with the test key and verify the signature with the public key of the test key
+fn test_signature() -> bool {
+    let mut private_key = cx_ecfp_private_key_t::default();
+    let mut public_key = cx_ecfp_public_key_t::default();
+    let mut signature = [0u8; 72];
+    let mut signature_length = signature.len();
+    let mut info = 0u32;
+    unsafe {
+        let mut rc = cx_ecfp_init_private_key_no_throw(
+            CX_CURVE_SECP256K1,
+            TEST_PRIVATE_KEY.as_ptr(),
+            TEST_PRIVATE_KEY.len(),
+            &mut private_key,
+        );
+        if rc == CX_OK {
+            rc = cx_ecfp_generate_pair_no_throw(
+                CX_CURVE_SECP256K1,
+                &mut public_key,
+                &mut private_key,
+                true,
+            );
+        }
+        if rc == CX_OK {
+            rc = cx_ecdsa_sign_no_throw(
+                &private_key,
+                CX_RND_RFC6979 | CX_LAST,
+                CX_SHA256,
+                BLAKE2B_ABC.as_ptr(),
+                BLAKE2B_ABC.len(),
+                signature.as_mut_ptr(),
+                &mut signature_length,
+                &mut info,
+            );
+        }
+        rc == CX_OK
+            && cx_ecdsa_verify_no_throw(
+                &public_key,
+                BLAKE2B_ABC.as_ptr(),
+                BLAKE2B_ABC.len(),
+                signature.as_ptr(),
+                signature_length,
+            )
+    }
+}
+
+// Write a value that differs from the stored one, so that the write is always checked
+fn test_nvm() -> bool {
+    let storage = unsafe { NVM_TEST_DATA.get_mut() };
+    let value = u32::from_be_bytes(*storage.get_ref()).wrapping_add(1);
+    storage.update(&value.to_be_bytes());
+    u32::from_be_bytes(*storage.get_ref()) == value
+}
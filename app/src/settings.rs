@@ -7,15 +7,116 @@ pub const SETTINGS_SIZE: usize = 10;
 pub static mut SETTINGS_DATA: NVMData<AtomicStorage<[u8; SETTINGS_SIZE]>> =
     NVMData::new(AtomicStorage::new(&[0u8; SETTINGS_SIZE]));
 
-pub fn is_blind_signing_enabled() -> bool {
+// The ALPH amount in atto ALPH, big-endian, above which signing a tx requires an additional
+// confirmation. The threshold is disabled if it is zero
+#[link_section = ".nvm_data"]
+static mut AMOUNT_THRESHOLD_DATA: NVMData<AtomicStorage<[u8; 16]>> =
+    NVMData::new(AtomicStorage::new(&[0u8; 16]));
+
+const BLIND_SIGNING_INDEX: usize = 0;
+const RESPONSE_MAC_INDEX: usize = 1;
+const MAINNET_ONLY_INDEX: usize = 2;
+const DISPLAY_TX_ID_INDEX: usize = 3;
+const EXPERT_MODE_INDEX: usize = 4;
+const UNKNOWN_TOKEN_WARNING_INDEX: usize = 5;
+const CROSS_GROUP_WARNING_DISABLED_INDEX: usize = 6;
+
+fn is_setting_enabled(index: usize) -> bool {
     let settings = unsafe { SETTINGS_DATA.get_mut() };
-    settings.get_ref()[0] != 0
+    settings.get_ref()[index] != 0
 }
 
 #[cfg(not(any(target_os = "stax", target_os = "flex")))]
-pub fn toggle_blind_signing_setting() {
+fn toggle_setting(index: usize) {
     let settings = unsafe { SETTINGS_DATA.get_mut() };
     let mut updated_data: [u8; SETTINGS_SIZE] = unsafe { *SETTINGS_DATA.get_mut().get_ref() };
-    updated_data[0] = if settings.get_ref()[0] != 0 { 0 } else { 1 };
+    updated_data[index] = if settings.get_ref()[index] != 0 { 0 } else { 1 };
     unsafe { SETTINGS_DATA.get_mut().update(&updated_data) }
 }
+
+pub fn is_blind_signing_enabled() -> bool {
+    is_setting_enabled(BLIND_SIGNING_INDEX)
+}
+
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+pub fn toggle_blind_signing_setting() {
+    toggle_setting(BLIND_SIGNING_INDEX)
+}
+
+// The responses of the sign commands are authenticated if enabled
+pub fn is_response_mac_enabled() -> bool {
+    is_setting_enabled(RESPONSE_MAC_INDEX)
+}
+
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+pub fn toggle_response_mac_setting() {
+    toggle_setting(RESPONSE_MAC_INDEX)
+}
+
+// Only mainnet transactions can be signed if enabled, and hash signing is refused
+pub fn is_mainnet_only_enabled() -> bool {
+    is_setting_enabled(MAINNET_ONLY_INDEX)
+}
+
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+pub fn toggle_mainnet_only_setting() {
+    toggle_setting(MAINNET_ONLY_INDEX)
+}
+
+// The tx id is displayed on the final confirmation page if enabled
+pub fn is_display_tx_id_enabled() -> bool {
+    is_setting_enabled(DISPLAY_TX_ID_INDEX)
+}
+
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+pub fn toggle_display_tx_id_setting() {
+    toggle_setting(DISPLAY_TX_ID_INDEX)
+}
+
+// The gas amount and the gas price are displayed along with the fees if enabled
+pub fn is_expert_mode_enabled() -> bool {
+    is_setting_enabled(EXPERT_MODE_INDEX)
+}
+
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+pub fn toggle_expert_mode_setting() {
+    toggle_setting(EXPERT_MODE_INDEX)
+}
+
+// A warning is displayed for any token without metadata if enabled, otherwise only for the
+// tokens without metadata whose raw amount is large
+pub fn is_unknown_token_warning_enabled() -> bool {
+    is_setting_enabled(UNKNOWN_TOKEN_WARNING_INDEX)
+}
+
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+pub fn toggle_unknown_token_warning_setting() {
+    toggle_setting(UNKNOWN_TOKEN_WARNING_INDEX)
+}
+
+// The settings are disabled by default, so the setting disables the cross-group warning
+pub fn is_cross_group_warning_disabled() -> bool {
+    is_setting_enabled(CROSS_GROUP_WARNING_DISABLED_INDEX)
+}
+
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+pub fn toggle_cross_group_warning_setting() {
+    toggle_setting(CROSS_GROUP_WARNING_DISABLED_INDEX)
+}
+
+pub fn get_amount_threshold() -> Option<u128> {
+    let threshold = u128::from_be_bytes(unsafe { *AMOUNT_THRESHOLD_DATA.get_mut().get_ref() });
+    if threshold == 0 {
+        None
+    } else {
+        Some(threshold)
+    }
+}
+
+pub fn set_amount_threshold(threshold: u128) {
+    unsafe {
+        AMOUNT_THRESHOLD_DATA
+            .get_mut()
+            .update(&threshold.to_be_bytes())
+    }
+}
@@ -0,0 +1,239 @@
+use utils::{
+    deserialize_path, is_testnet_path,
+    message::{displayable_message, MAX_DISPLAYED_MESSAGE_SIZE, MESSAGE_PREFIX},
+    to_hex, to_hex_fixed,
+    typed_data::{
+        TypedData, TypedValue, MAX_TYPED_BYTES_SIZE, MAX_TYPED_DATA_SIZE, MAX_TYPED_FIELDS,
+        TYPED_DATA_PREFIX,
+    },
+    types::I32,
+    PATH_LENGTH,
+};
+
+use crate::{
+    blake2b_hasher::{Blake2bHasher, BLAKE2B_HASH_SIZE},
+    error_code::ErrorCode,
+    public_key::{sign_hash, to_base58_address},
+    settings::is_mainnet_only_enabled,
+    ui::{bytes_to_string, review_message, review_path, review_typed_data, TESTNET_KEY_WARNING},
+};
+#[cfg(any(target_os = "stax", target_os = "flex"))]
+use ledger_device_sdk::nbgl::Field;
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+use ledger_device_sdk::ui::gadgets::Field;
+
+const MESSAGE_SIZE_LENGTH: usize = 2;
+const MAINNET_NETWORK_ID: u8 = 0;
+// The longest displayed value is a U256 of 78 digits
+const MAX_TYPED_VALUE_SIZE: usize = 78;
+// The domain name and the network id are displayed before the fields
+const TYPED_DATA_HEADER_FIELDS: usize = 2;
+
+// The context for signing a message
+// The message is split into frames, the first frame starts with the path and the message size
+// The prefixed message is hashed in chunks, and only the beginning of the message is kept for
+// the review since longer messages are reviewed by their hash
+// Typed data is uploaded like a message, but it is kept in full so that it can be decoded
+// and reviewed field by field when it is complete
+pub struct SignMessageContext {
+    path: [u32; PATH_LENGTH],
+    hasher: Blake2bHasher,
+    message: [u8; MAX_DISPLAYED_MESSAGE_SIZE],
+    message_size: usize,
+    received_size: usize,
+    is_typed_data: bool,
+}
+
+impl SignMessageContext {
+    pub fn new() -> Self {
+        Self {
+            path: [0; PATH_LENGTH],
+            hasher: Blake2bHasher::new(),
+            message: [0; MAX_DISPLAYED_MESSAGE_SIZE],
+            message_size: 0,
+            received_size: 0,
+            is_typed_data: false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.path = [0; PATH_LENGTH];
+        self.hasher.reset();
+        self.message = [0; MAX_DISPLAYED_MESSAGE_SIZE];
+        self.message_size = 0;
+        self.received_size = 0;
+        self.is_typed_data = false;
+    }
+
+    // The first frame is the encoded path, the 2-byte message size and the first message chunk
+    pub fn init(&mut self, data: &[u8], is_typed_data: bool) -> Result<(), ErrorCode> {
+        let header_length = PATH_LENGTH * 4 + MESSAGE_SIZE_LENGTH;
+        if data.len() < header_length {
+            return Err(ErrorCode::BadLen);
+        }
+        self.reset();
+        deserialize_path(
+            &data[..(PATH_LENGTH * 4)],
+            &mut self.path,
+            ErrorCode::HDPathDecodingFailed,
+        )?;
+        review_path(&self.path)?;
+        let size_bytes = &data[(PATH_LENGTH * 4)..header_length];
+        self.message_size = u16::from_be_bytes([size_bytes[0], size_bytes[1]]) as usize;
+        if self.message_size == 0 {
+            return Err(ErrorCode::BadLen);
+        }
+        self.is_typed_data = is_typed_data;
+        if is_typed_data {
+            if self.message_size > MAX_TYPED_DATA_SIZE {
+                return Err(ErrorCode::BadLen);
+            }
+        } else {
+            self.hasher.update(MESSAGE_PREFIX)?;
+        }
+        self.handle_data(&data[header_length..])
+    }
+
+    pub fn handle_data(&mut self, data: &[u8]) -> Result<(), ErrorCode> {
+        if self.received_size + data.len() > self.message_size {
+            return Err(ErrorCode::BadLen);
+        }
+        if self.received_size < MAX_DISPLAYED_MESSAGE_SIZE {
+            let size = core::cmp::min(data.len(), MAX_DISPLAYED_MESSAGE_SIZE - self.received_size);
+            self.message[self.received_size..(self.received_size + size)]
+                .copy_from_slice(&data[..size]);
+        }
+        self.received_size += data.len();
+        if self.is_typed_data {
+            Ok(())
+        } else {
+            self.hasher.update(data)
+        }
+    }
+
+    #[inline]
+    pub fn is_typed_data(&self) -> bool {
+        self.is_typed_data
+    }
+
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.message_size != 0 && self.received_size == self.message_size
+    }
+
+    // Review the message, or its hash if the message cannot be displayed, and sign the hash
+    pub fn review_and_sign(&mut self) -> Result<([u8; 72], u32, u32), ErrorCode> {
+        assert!(self.is_complete());
+        if self.is_typed_data {
+            return self.review_and_sign_typed_data();
+        }
+        let hash = self.hasher.finalize()?;
+        let hash_hex: [u8; BLAKE2B_HASH_SIZE * 2] = to_hex_fixed(&hash);
+        let hash_str = core::str::from_utf8(&hash_hex).map_err(|_| ErrorCode::InternalError)?;
+        let message = if self.message_size <= MAX_DISPLAYED_MESSAGE_SIZE {
+            displayable_message(&self.message[..self.message_size])
+        } else {
+            None
+        };
+        let testnet_key_field = Field {
+            name: "Warning",
+            value: TESTNET_KEY_WARNING,
+        };
+        let hash_field = Field {
+            name: "Message Hash",
+            value: hash_str,
+        };
+        let fields = match message {
+            Some(text) => [
+                testnet_key_field,
+                Field {
+                    name: "Message",
+                    value: text,
+                },
+                hash_field,
+            ],
+            None => [
+                testnet_key_field,
+                Field {
+                    name: "Warning",
+                    value: "Message not displayable",
+                },
+                hash_field,
+            ],
+        };
+        let from_index = if is_testnet_path(&self.path) { 0 } else { 1 };
+        review_message(&fields[from_index..])?;
+        sign_hash(&self.path, &hash)
+    }
+
+    // The typed data is reviewed field by field, the signed hash commits to the domain so that
+    // the same message cannot be replayed in another dApp or on another network
+    fn review_and_sign_typed_data(&mut self) -> Result<([u8; 72], u32, u32), ErrorCode> {
+        let typed_data = TypedData::decode(&self.message[..self.message_size])
+            .ok_or(ErrorCode::TypedDataDecodingFailed)?;
+        if is_mainnet_only_enabled() && typed_data.network_id != MAINNET_NETWORK_ID {
+            return Err(ErrorCode::MainnetOnly);
+        }
+        let domain_hash = Blake2bHasher::hash(typed_data.encoded_domain)?;
+        let message_hash = Blake2bHasher::hash(typed_data.encoded_message)?;
+        let hash = Blake2bHasher::hash_inputs(&[TYPED_DATA_PREFIX, &domain_hash, &message_hash])?;
+
+        let mut network_id_bytes = [0u8; 3];
+        let network_id = I32::unsafe_from(typed_data.network_id as usize)
+            .to_str(&mut network_id_bytes)
+            .ok_or(ErrorCode::Overflow)?;
+        let mut values = [[0u8; MAX_TYPED_VALUE_SIZE]; MAX_TYPED_FIELDS];
+        let mut fields = [const {
+            Field {
+                name: "",
+                value: "",
+            }
+        }; 1 + TYPED_DATA_HEADER_FIELDS + MAX_TYPED_FIELDS];
+        fields[0] = Field {
+            name: "Warning",
+            value: TESTNET_KEY_WARNING,
+        };
+        fields[1] = Field {
+            name: "Domain",
+            value: typed_data.domain_name,
+        };
+        fields[2] = Field {
+            name: "Network ID",
+            value: bytes_to_string(network_id)?,
+        };
+        let mut size = 1 + TYPED_DATA_HEADER_FIELDS;
+        for (field, output) in typed_data.fields().zip(values.iter_mut()) {
+            fields[size] = Field {
+                name: field.name,
+                value: write_typed_value(&field.value, output)?,
+            };
+            size += 1;
+        }
+        let from_index = if is_testnet_path(&self.path) { 0 } else { 1 };
+        review_typed_data(&fields[from_index..size])?;
+        sign_hash(&self.path, &hash)
+    }
+}
+
+fn write_typed_value<'a>(
+    value: &TypedValue<'a>,
+    output: &'a mut [u8; MAX_TYPED_VALUE_SIZE],
+) -> Result<&'a str, ErrorCode> {
+    match value {
+        TypedValue::Bool(true) => Ok("true"),
+        TypedValue::Bool(false) => Ok("false"),
+        TypedValue::U256(amount) => {
+            bytes_to_string(amount.to_str(output).ok_or(ErrorCode::Overflow)?)
+        }
+        TypedValue::Bytes(bytes) => {
+            let hex: [u8; MAX_TYPED_BYTES_SIZE * 2] = to_hex(bytes).ok_or(ErrorCode::Overflow)?;
+            let length = bytes.len() * 2;
+            output[..length].copy_from_slice(&hex[..length]);
+            bytes_to_string(&output[..length])
+        }
+        TypedValue::String(text) => Ok(*text),
+        TypedValue::Address(prefix, hash) => {
+            bytes_to_string(to_base58_address(*prefix, hash, output)?)
+        }
+    }
+}
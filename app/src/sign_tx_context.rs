@@ -1,14 +1,19 @@
 use ledger_device_sdk::io::ApduHeader;
 use ledger_device_sdk::NVMData;
 use utils::{
-    buffer::Buffer, decode::StreamingDecoder, deserialize_path, types::UnsignedTx, PATH_LENGTH,
+    buffer::Buffer,
+    decode::{DecodeError, StreamingDecoder},
+    deserialize_path, is_testnet_path,
+    types::{UnsignedTx, U256},
+    PATH_LENGTH,
 };
 
+use crate::error_log::DecodePosition;
 use crate::nvm::swapping_buffer::{SwappingBuffer, RAM_SIZE};
 use crate::nvm::{NVM, NVM_DATA_SIZE};
 use crate::public_key::sign_hash;
-use crate::public_key::Address;
-use crate::ui::tx_reviewer::TxReviewer;
+use crate::public_key::{DeviceAddresses, MAX_SIGNING_PATHS};
+use crate::ui::{review_path, tx_reviewer::TxReviewer};
 use crate::{
     blake2b_hasher::{Blake2bHasher, BLAKE2B_HASH_SIZE},
     error_code::ErrorCode,
@@ -22,67 +27,276 @@ static mut DATA: NVMData<NVM<NVM_DATA_SIZE>> = NVMData::new(NVM::zeroed());
 enum DecodeStep {
     Init,
     DecodingTx,
+    // The rest of a tx of an unrecognized format is only hashed
+    HashOnly,
     Complete,
 }
 
+// The maximum number of txs of a chained signing session, the signatures of all the txs are
+// returned in a single response
+pub const MAX_CHAINED_TXS: usize = 3;
+// Each signature of a chained session is prefixed with its length
+pub const MAX_CHAINED_SIGNATURES_SIZE: usize = MAX_CHAINED_TXS * (1 + 72);
+
+// The approved txs of a chained session, they are signed once the last tx is approved
+struct ChainedTxs {
+    paths: [[u32; PATH_LENGTH]; MAX_CHAINED_TXS],
+    tx_ids: [[u8; BLAKE2B_HASH_SIZE]; MAX_CHAINED_TXS],
+    // The number of approved txs
+    size: usize,
+    // The number of txs of the session, 0 if there is no chained session
+    count: usize,
+    // The total ALPH sent to other addresses by the approved txs
+    total_sent: U256,
+}
+
+impl ChainedTxs {
+    fn new() -> Self {
+        Self {
+            paths: [[0; PATH_LENGTH]; MAX_CHAINED_TXS],
+            tx_ids: [[0; BLAKE2B_HASH_SIZE]; MAX_CHAINED_TXS],
+            size: 0,
+            count: 0,
+            total_sent: U256::default(),
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
 // The context for signing a transaction
-// It keeps track of the current step, the transaction decoder, the path, and the device address
+// It keeps track of the current step, the transaction decoder, the paths, and the device addresses
 // A streaming decoder is used to decode the transaction in chunks so that it can handle large transactions
+// A sweep transaction spends from multiple paths, the signatures of the remaining paths are returned
+// one by one after the first signature
+// The txs of a chained session are reviewed one by one and signed together after the last review
 pub struct SignTxContext {
-    pub path: [u32; PATH_LENGTH],
+    paths: [[u32; PATH_LENGTH]; MAX_SIGNING_PATHS],
+    path_size: usize,
     pub tx_decoder: StreamingDecoder<UnsignedTx>,
     current_step: DecodeStep,
     hasher: Blake2bHasher,
     temp_data: SwappingBuffer<'static, RAM_SIZE, NVM_DATA_SIZE>,
-    device_address: Option<Address>,
+    device_addresses: DeviceAddresses,
+    tx_id: Option<[u8; BLAKE2B_HASH_SIZE]>,
+    next_signing_index: usize,
+    frame_count: u16,
+    chained_txs: ChainedTxs,
 }
 
 impl SignTxContext {
     pub fn new() -> Self {
         SignTxContext {
-            path: [0; PATH_LENGTH],
+            paths: [[0; PATH_LENGTH]; MAX_SIGNING_PATHS],
+            path_size: 0,
             tx_decoder: StreamingDecoder::default(),
             current_step: DecodeStep::Init,
             hasher: Blake2bHasher::new(),
             temp_data: unsafe { SwappingBuffer::new(&mut DATA) },
-            device_address: None,
+            device_addresses: DeviceAddresses::new(),
+            tx_id: None,
+            next_signing_index: 0,
+            frame_count: 0,
+            chained_txs: ChainedTxs::new(),
         }
     }
 
-    // Initialize the context
+    // Initialize the context, the data is the concatenation of the encoded paths
     pub fn init(&mut self, data: &[u8]) -> Result<(), ErrorCode> {
-        deserialize_path(data, &mut self.path, ErrorCode::HDPathDecodingFailed)?;
-        self.tx_decoder.reset();
-        self.current_step = DecodeStep::Init;
-        self.hasher.reset();
-        self.temp_data.reset(0);
-        self.device_address = Some(Address::from_path(&self.path)?);
+        let encoded_path_size = PATH_LENGTH * 4;
+        if data.is_empty() || data.len() % encoded_path_size != 0 {
+            return Err(ErrorCode::BadLen);
+        }
+        let path_size = data.len() / encoded_path_size;
+        if path_size > MAX_SIGNING_PATHS {
+            return Err(ErrorCode::BadLen);
+        }
+        // The token metadata frames are processed before the paths, so they are still counted
+        let frame_count = self.frame_count;
+        self.reset_tx();
+        self.frame_count = frame_count;
+        for (path, encoded_path) in self.paths.iter_mut().zip(data.chunks(encoded_path_size)) {
+            deserialize_path(encoded_path, path, ErrorCode::HDPathDecodingFailed)?;
+            review_path(path)?;
+        }
+        self.path_size = path_size;
+        self.device_addresses.init(&self.paths[..path_size])?;
         Ok(())
     }
 
+    // Whether any of the signing paths uses the testnet coin type
+    pub fn has_testnet_path(&self) -> bool {
+        self.paths[..self.path_size]
+            .iter()
+            .any(|path| is_testnet_path(path))
+    }
+
     pub fn reset(&mut self) {
-        self.path = [0; PATH_LENGTH];
+        self.reset_tx();
+        self.device_addresses.reset();
+        self.chained_txs.reset();
+    }
+
+    // Reset the current tx, the device addresses and the approved txs of a chained session
+    // are kept
+    pub fn reset_tx(&mut self) {
+        self.paths = [[0; PATH_LENGTH]; MAX_SIGNING_PATHS];
+        self.path_size = 0;
         self.tx_decoder.reset();
         self.current_step = DecodeStep::Init;
         self.hasher.reset();
-        self.temp_data.reset(0);
-        self.device_address = None;
+        self.temp_data.clear();
+        self.tx_id = None;
+        self.next_signing_index = 0;
+        self.frame_count = 0;
+    }
+
+    // The number of data frames of the current tx that were processed, the host resumes
+    // from the next frame if the response of a frame is lost
+    pub fn frame_count(&self) -> u16 {
+        self.frame_count
+    }
+
+    pub fn on_frame_processed(&mut self, is_first_frame: bool) {
+        self.frame_count = if is_first_frame {
+            1
+        } else {
+            self.frame_count.saturating_add(1)
+        };
+    }
+
+    // The maximum size of a single decoded field, e.g. a script or the keys of a multisig lockup
+    pub fn buffer_capacity(&self) -> usize {
+        self.temp_data.capacity()
+    }
+
+    pub fn decode_position(&self) -> DecodePosition {
+        DecodePosition {
+            tx_step: self.tx_decoder.inner.step_index(),
+            stage_step: self.tx_decoder.stage.step,
+            stage_index: self.tx_decoder.stage.index,
+        }
     }
 
     pub fn is_complete(&self) -> bool {
         self.current_step == DecodeStep::Complete
     }
 
-    // Get the transaction ID by finalizing the hash
+    // Get the transaction ID by finalizing the hash, the ID is kept for the following signatures
     pub fn get_tx_id(&mut self) -> Result<[u8; BLAKE2B_HASH_SIZE], ErrorCode> {
         assert!(self.is_complete());
-        self.hasher.finalize()
+        if let Some(tx_id) = self.tx_id {
+            return Ok(tx_id);
+        }
+        let tx_id = self.hasher.finalize()?;
+        self.tx_id = Some(tx_id);
+        Ok(tx_id)
     }
 
-    // Sign the transaction by signing the transaction ID
+    // Sign the transaction by signing the transaction ID with the first path
     pub fn sign_tx(&mut self) -> Result<([u8; 72], u32, u32), ErrorCode> {
         let tx_id = self.get_tx_id()?;
-        sign_hash(&self.path, &tx_id)
+        self.next_signing_index = 1;
+        sign_hash(&self.paths[0], &tx_id)
+    }
+
+    // Start or continue a chained session with the tx at `index` of `count` txs, the txs are sent
+    // in order, and a tx that is not chained discards the approved txs of a session
+    pub fn set_chained_tx(&mut self, chained_tx: Option<(u8, u8)>) -> Result<(), ErrorCode> {
+        let (index, count) = match chained_tx {
+            Some((index, count)) => (index as usize, count as usize),
+            None => {
+                self.chained_txs.reset();
+                return Ok(());
+            }
+        };
+        if !(2..=MAX_CHAINED_TXS).contains(&count) || index >= count {
+            return Err(ErrorCode::BadLen);
+        }
+        if index == 0 {
+            self.chained_txs.reset();
+            self.chained_txs.count = count;
+            Ok(())
+        } else if index == self.chained_txs.size && count == self.chained_txs.count {
+            Ok(())
+        } else {
+            Err(ErrorCode::InvalidState)
+        }
+    }
+
+    #[inline]
+    pub fn is_chained_tx(&self) -> bool {
+        self.chained_txs.count != 0
+    }
+
+    #[inline]
+    pub fn is_chain_complete(&self) -> bool {
+        self.is_chained_tx() && self.chained_txs.size == self.chained_txs.count
+    }
+
+    // Keep the path and the id of an approved chained tx until the last tx is approved
+    pub fn approve_chained_tx(&mut self, total_sent: &U256) -> Result<(), ErrorCode> {
+        assert!(self.is_chained_tx() && !self.is_chain_complete());
+        let tx_id = self.get_tx_id()?;
+        let index = self.chained_txs.size;
+        self.chained_txs.paths[index] = self.paths[0];
+        self.chained_txs.tx_ids[index] = tx_id;
+        self.chained_txs.total_sent = self
+            .chained_txs
+            .total_sent
+            .add(total_sent)
+            .ok_or(ErrorCode::Overflow)?;
+        self.chained_txs.size += 1;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn chained_tx_count(&self) -> usize {
+        self.chained_txs.count
+    }
+
+    #[inline]
+    pub fn chained_total_sent(&self) -> &U256 {
+        &self.chained_txs.total_sent
+    }
+
+    // Sign all the txs of a complete chained session, each signature is prefixed with its length
+    pub fn sign_chained_txs(
+        &self,
+        output: &mut [u8; MAX_CHAINED_SIGNATURES_SIZE],
+    ) -> Result<usize, ErrorCode> {
+        assert!(self.is_chain_complete());
+        let chained_txs = &self.chained_txs;
+        let mut size = 0;
+        for (path, tx_id) in chained_txs
+            .paths
+            .iter()
+            .zip(chained_txs.tx_ids.iter())
+            .take(chained_txs.count)
+        {
+            let (signature, length, _) = sign_hash(path, tx_id)?;
+            let length = length as usize;
+            output[size] = length as u8;
+            output[(size + 1)..(size + 1 + length)].copy_from_slice(&signature[..length]);
+            size += 1 + length;
+        }
+        Ok(size)
+    }
+
+    // Whether there are sweep signatures that have not been returned yet
+    pub fn has_pending_signatures(&self) -> bool {
+        self.next_signing_index > 0 && self.next_signing_index < self.path_size
+    }
+
+    // Sign the approved transaction with the next path of a sweep transaction
+    pub fn sign_next(&mut self, path_index: usize) -> Result<([u8; 72], u32, u32), ErrorCode> {
+        if !self.has_pending_signatures() || path_index != self.next_signing_index {
+            return Err(ErrorCode::BadP1P2);
+        }
+        self.next_signing_index += 1;
+        sign_hash(&self.paths[path_index], self.tx_id.as_ref().unwrap())
     }
 
     fn _decode_tx(
@@ -94,11 +308,17 @@ impl SignTxContext {
             match self.tx_decoder.step(buffer) {
                 // New transaction details are available
                 Ok(true) => {
-                    tx_reviewer.review_tx_details(
+                    match tx_reviewer.review_tx_details(
                         &self.tx_decoder.inner,
-                        self.device_address.as_ref().unwrap(),
+                        &self.device_addresses,
                         &self.temp_data,
-                    )?;
+                    ) {
+                        // A tx of a newer version may have a different layout
+                        Err(ErrorCode::UnsupportedTxVersion) => {
+                            return self.fall_back_to_hash_only(tx_reviewer)
+                        }
+                        result => result?,
+                    }
                     self.temp_data.reset(0);
                     if self.tx_decoder.inner.is_complete() {
                         self.current_step = DecodeStep::Complete;
@@ -110,12 +330,25 @@ impl SignTxContext {
                 }
                 // No new transaction details are available
                 Ok(false) => return Ok(()),
+                // An unknown variant, e.g. a lockup script type added by a chain upgrade
+                Err(DecodeError::InvalidData) => return self.fall_back_to_hash_only(tx_reviewer),
+                // A field of a known variant cannot be decoded, e.g. a compact integer of more than 4 bytes
+                Err(DecodeError::InvalidSize) => return Err(ErrorCode::MalformedField),
                 Err(_) => return Err(ErrorCode::TxDecodingFailed),
             }
         }
         Ok(())
     }
 
+    // Stop decoding a tx of an unrecognized format, the rest of the tx is only hashed and
+    // the tx id is reviewed with blind signing
+    fn fall_back_to_hash_only(&mut self, tx_reviewer: &mut TxReviewer) -> Result<(), ErrorCode> {
+        let is_network_checked = self.tx_decoder.inner.step_index() > 1;
+        tx_reviewer.set_unrecognized_format(is_network_checked)?;
+        self.current_step = DecodeStep::HashOnly;
+        Ok(())
+    }
+
     // Decode a transaction chunk
     fn decode_tx(
         &mut self,
@@ -142,7 +375,7 @@ impl SignTxContext {
             DecodeStep::Complete => Err(ErrorCode::InternalError),
             DecodeStep::Init => {
                 // The first chunk of the transaction
-                if apdu_header.p1 == 1 && apdu_header.p2 == 0 {
+                if apdu_header.p1 == 1 && matches!(apdu_header.p2, 0 | 2 | 3 | 5) {
                     self.current_step = DecodeStep::DecodingTx;
                     self.decode_tx(tx_data_chunk, tx_reviewer)
                 } else {
@@ -157,6 +390,18 @@ impl SignTxContext {
                     Err(ErrorCode::BadP1P2)
                 }
             }
+            DecodeStep::HashOnly => {
+                // The end of the tx cannot be decoded, so the host sends it with `P2` = 4
+                match (apdu_header.p1, apdu_header.p2) {
+                    (1, 1) => self.hasher.update(tx_data_chunk),
+                    (1, 4) => {
+                        self.hasher.update(tx_data_chunk)?;
+                        self.current_step = DecodeStep::Complete;
+                        Ok(())
+                    }
+                    _ => Err(ErrorCode::BadP1P2),
+                }
+            }
         }
     }
 }
@@ -9,9 +9,18 @@ use ledger_device_sdk::ui::{
 };
 use ledger_secure_sdk_sys::buttons::ButtonEvent;
 
-use crate::settings::{is_blind_signing_enabled, toggle_blind_signing_setting};
+use crate::settings::{
+    is_blind_signing_enabled, is_cross_group_warning_disabled, is_display_tx_id_enabled,
+    is_expert_mode_enabled, is_mainnet_only_enabled, is_response_mac_enabled,
+    is_unknown_token_warning_enabled, toggle_blind_signing_setting,
+    toggle_cross_group_warning_setting, toggle_display_tx_id_setting, toggle_expert_mode_setting,
+    toggle_mainnet_only_setting, toggle_response_mac_setting, toggle_unknown_token_warning_setting,
+};
+
+use crate::public_key::{derive_wallet_fingerprint, WALLET_FINGERPRINT_SIZE};
+use utils::to_hex_fixed;
 
-const UI_PAGE_NUM: u8 = 4;
+const UI_PAGE_NUM: u8 = 11;
 
 fn show_ui_welcome() {
     const APP_ICON: Glyph = Glyph::from_include(include_gif!("alph_14x14.gif"));
@@ -27,11 +36,76 @@ fn show_ui_blind_signing() {
     gadgets::Page::from((["Blind Signing", label], false)).place();
 }
 
+fn show_ui_response_mac() {
+    let label = if is_response_mac_enabled() {
+        "enabled"
+    } else {
+        "disabled"
+    };
+    gadgets::Page::from((["Response MAC", label], false)).place();
+}
+
+fn show_ui_mainnet_only() {
+    let label = if is_mainnet_only_enabled() {
+        "enabled"
+    } else {
+        "disabled"
+    };
+    gadgets::Page::from((["Mainnet Only", label], false)).place();
+}
+
+fn show_ui_display_tx_id() {
+    let label = if is_display_tx_id_enabled() {
+        "enabled"
+    } else {
+        "disabled"
+    };
+    gadgets::Page::from((["Display Tx ID", label], false)).place();
+}
+
+fn show_ui_expert_mode() {
+    let label = if is_expert_mode_enabled() {
+        "enabled"
+    } else {
+        "disabled"
+    };
+    gadgets::Page::from((["Expert Mode", label], false)).place();
+}
+
+fn show_ui_unknown_token_warning() {
+    let label = if is_unknown_token_warning_enabled() {
+        "enabled"
+    } else {
+        "disabled"
+    };
+    gadgets::Page::from((["Unknown Tokens", label], false)).place();
+}
+
+fn show_ui_cross_group_warning() {
+    let label = if is_cross_group_warning_disabled() {
+        "disabled"
+    } else {
+        "enabled"
+    };
+    gadgets::Page::from((["Cross-Group Warn", label], false)).place();
+}
+
 fn show_ui_version() {
     const VERSION: &str = env!("CARGO_PKG_VERSION");
     gadgets::Page::from((["Version", VERSION], false)).place();
 }
 
+fn show_ui_wallet_id() {
+    let hex: Option<[u8; WALLET_FINGERPRINT_SIZE * 2]> = derive_wallet_fingerprint()
+        .ok()
+        .map(|fingerprint| to_hex_fixed(&fingerprint));
+    let label = hex
+        .as_ref()
+        .and_then(|hex| core::str::from_utf8(hex).ok())
+        .unwrap_or("unavailable");
+    gadgets::Page::from((["Wallet ID", label], false)).place();
+}
+
 fn show_ui_quit() {
     gadgets::Page::from(("Quit", &DASHBOARD_X)).place();
 }
@@ -45,7 +119,14 @@ fn show_ui(index: u8) {
         0 => show_ui_welcome(),
         1 => show_ui_version(),
         2 => show_ui_blind_signing(),
-        3 => show_ui_quit(),
+        3 => show_ui_response_mac(),
+        4 => show_ui_mainnet_only(),
+        5 => show_ui_display_tx_id(),
+        6 => show_ui_expert_mode(),
+        7 => show_ui_unknown_token_warning(),
+        8 => show_ui_cross_group_warning(),
+        9 => show_ui_wallet_id(),
+        10 => show_ui_quit(),
         _ => panic!("Invalid ui index"),
     }
 
@@ -102,6 +183,24 @@ impl MainPages {
                         toggle_blind_signing_setting();
                         show_ui_blind_signing();
                     } else if self.ui_index == 3 {
+                        toggle_response_mac_setting();
+                        show_ui_response_mac();
+                    } else if self.ui_index == 4 {
+                        toggle_mainnet_only_setting();
+                        show_ui_mainnet_only();
+                    } else if self.ui_index == 5 {
+                        toggle_display_tx_id_setting();
+                        show_ui_display_tx_id();
+                    } else if self.ui_index == 6 {
+                        toggle_expert_mode_setting();
+                        show_ui_expert_mode();
+                    } else if self.ui_index == 7 {
+                        toggle_unknown_token_warning_setting();
+                        show_ui_unknown_token_warning();
+                    } else if self.ui_index == 8 {
+                        toggle_cross_group_warning_setting();
+                        show_ui_cross_group_warning();
+                    } else if self.ui_index == 10 {
                         ledger_device_sdk::exit_app(0);
                     }
                 }
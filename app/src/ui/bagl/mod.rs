@@ -1,38 +1,195 @@
 pub mod home;
 pub mod tx_reviewer_inner;
 
-use crate::{error_code::ErrorCode, public_key::sign_hash};
+use crate::{
+    error_code::ErrorCode,
+    public_key::sign_hash,
+    ui::{bytes_to_string, BLIND_HASH_WARNING, EXTENDED_KEY_WARNING, TESTNET_KEY_WARNING},
+};
 use core::str::from_utf8;
-use ledger_device_sdk::ui::bitmaps::{CHECKMARK, CROSS, EYE};
+use ledger_device_sdk::ui::bitmaps::{CHECKMARK, CROSS, EYE, WARNING};
 use ledger_device_sdk::ui::gadgets::{Field, MultiFieldReview};
+use utils::{format_path, is_testnet_path, KeyType, MAX_PATH_STR_LENGTH};
 
 pub fn sign_hash_ui(path: &[u32], message: &[u8]) -> Result<([u8; 72], u32, u32), ErrorCode> {
     let hex: [u8; 64] = utils::to_hex(message).ok_or(ErrorCode::BadLen)?;
     let hex_str = from_utf8(&hex).map_err(|_| ErrorCode::InternalError)?;
 
-    let review_messages = ["Review", "Hash"];
+    let review_messages = ["Blind Signing", "Hash"];
+    let fields = [
+        Field {
+            name: "Warning",
+            value: TESTNET_KEY_WARNING,
+        },
+        Field {
+            name: "Risk",
+            value: BLIND_HASH_WARNING,
+        },
+        Field {
+            name: "Hash",
+            value: hex_str,
+        },
+    ];
+    let from_index = if is_testnet_path(path) { 0 } else { 1 };
+    let review = MultiFieldReview::new(
+        &fields[from_index..],
+        &review_messages,
+        Some(&WARNING),
+        "Accept risk and sign",
+        Some(&CHECKMARK),
+        "Reject",
+        Some(&CROSS),
+    );
+    if review.show() {
+        sign_hash(path, message)
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
+
+pub fn review_message(fields: &[Field]) -> Result<(), ErrorCode> {
+    let review_messages = ["Review", "Message"];
+    let review = MultiFieldReview::new(
+        fields,
+        &review_messages,
+        Some(&EYE),
+        "Sign message",
+        Some(&CHECKMARK),
+        "Reject",
+        Some(&CROSS),
+    );
+    if review.show() {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
+
+pub fn review_typed_data(fields: &[Field]) -> Result<(), ErrorCode> {
+    let review_messages = ["Review", "Typed Data"];
+    let review = MultiFieldReview::new(
+        fields,
+        &review_messages,
+        Some(&EYE),
+        "Sign typed data",
+        Some(&CHECKMARK),
+        "Reject",
+        Some(&CROSS),
+    );
+    if review.show() {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
+
+pub fn review_address(address: &str, key_type: KeyType, path: &[u32]) -> Result<(), ErrorCode> {
+    show_address_review(address, key_type, path, &["Review", "Address"])
+}
+
+pub fn review_change_address(
+    address: &str,
+    key_type: KeyType,
+    path: &[u32],
+) -> Result<(), ErrorCode> {
+    show_address_review(address, key_type, path, &["Review", "Change Address"])
+}
+
+fn show_address_review(
+    address: &str,
+    key_type: KeyType,
+    path: &[u32],
+    review_messages: &[&str],
+) -> Result<(), ErrorCode> {
+    let mut path_bytes = [0u8; MAX_PATH_STR_LENGTH];
+    let path_length = format_path(path, &mut path_bytes).ok_or(ErrorCode::InternalError)?;
+    let fields = [
+        Field {
+            name: "Warning",
+            value: TESTNET_KEY_WARNING,
+        },
+        Field {
+            name: "Address",
+            value: address,
+        },
+        Field {
+            name: "Path",
+            value: bytes_to_string(&path_bytes[..path_length])?,
+        },
+        Field {
+            name: "Key Type",
+            value: key_type.name(),
+        },
+    ];
+    let from_index = if is_testnet_path(path) { 0 } else { 1 };
+    let review = MultiFieldReview::new(
+        &fields[from_index..],
+        review_messages,
+        Some(&EYE),
+        "Confirm address",
+        Some(&CHECKMARK),
+        "Reject",
+        Some(&CROSS),
+    );
+    if review.show() {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
+
+pub fn review_multi_sig_address(address: &str, policy: &str) -> Result<(), ErrorCode> {
+    let review_messages = ["Review", "Multi-sig Address"];
+    let fields = [
+        Field {
+            name: "Address",
+            value: address,
+        },
+        Field {
+            name: "Signatures",
+            value: policy,
+        },
+    ];
+    let review = MultiFieldReview::new(
+        &fields,
+        &review_messages,
+        Some(&EYE),
+        "Confirm address",
+        Some(&CHECKMARK),
+        "Reject",
+        Some(&CROSS),
+    );
+    if review.show() {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
+
+pub fn review_wallet_fingerprint(fingerprint: &str) -> Result<(), ErrorCode> {
+    let review_messages = ["Verify", "Wallet ID"];
     let fields = [Field {
-        name: "Hash",
-        value: hex_str,
+        name: "Wallet ID",
+        value: fingerprint,
     }];
     let review = MultiFieldReview::new(
         &fields,
         &review_messages,
         Some(&EYE),
-        "Approve",
+        "Confirm",
         Some(&CHECKMARK),
         "Reject",
         Some(&CROSS),
     );
     if review.show() {
-        sign_hash(path, message)
+        Ok(())
     } else {
         Err(ErrorCode::UserCancelled)
     }
 }
 
-pub fn review_address(address: &str) -> Result<(), ErrorCode> {
-    let review_messages = ["Review", "Address"];
+pub fn review_pairing(address: &str) -> Result<(), ErrorCode> {
+    let review_messages = ["Pair", "Wallet"];
     let fields = [Field {
         name: "Address",
         value: address,
@@ -41,7 +198,101 @@ pub fn review_address(address: &str) -> Result<(), ErrorCode> {
         &fields,
         &review_messages,
         Some(&EYE),
-        "Confirm address",
+        "Pair wallet",
+        Some(&CHECKMARK),
+        "Reject",
+        Some(&CROSS),
+    );
+    if review.show() {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
+
+pub fn review_response_mac_key() -> Result<(), ErrorCode> {
+    let review_messages = ["Share response", "MAC key"];
+    let review = MultiFieldReview::new(
+        &[],
+        &review_messages,
+        Some(&EYE),
+        "Share key",
+        Some(&CHECKMARK),
+        "Reject",
+        Some(&CROSS),
+    );
+    if review.show() {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
+
+pub fn review_extended_key(path: &[u32]) -> Result<(), ErrorCode> {
+    let mut path_bytes = [0u8; MAX_PATH_STR_LENGTH];
+    let path_length = format_path(path, &mut path_bytes).ok_or(ErrorCode::InternalError)?;
+    let review_messages = ["Export", "Extended Key"];
+    let fields = [
+        Field {
+            name: "Path",
+            value: bytes_to_string(&path_bytes[..path_length])?,
+        },
+        Field {
+            name: "Warning",
+            value: EXTENDED_KEY_WARNING,
+        },
+    ];
+    let review = MultiFieldReview::new(
+        &fields,
+        &review_messages,
+        Some(&WARNING),
+        "Export key",
+        Some(&CHECKMARK),
+        "Reject",
+        Some(&CROSS),
+    );
+    if review.show() {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
+
+pub fn review_unusual_path(path: &[u32]) -> Result<(), ErrorCode> {
+    let mut path_bytes = [0u8; MAX_PATH_STR_LENGTH];
+    let path_length = format_path(path, &mut path_bytes).ok_or(ErrorCode::InternalError)?;
+    let review_messages = ["Unusual", "Derivation Path"];
+    let fields = [Field {
+        name: "Path",
+        value: bytes_to_string(&path_bytes[..path_length])?,
+    }];
+    let review = MultiFieldReview::new(
+        &fields,
+        &review_messages,
+        Some(&WARNING),
+        "Continue",
+        Some(&CHECKMARK),
+        "Reject",
+        Some(&CROSS),
+    );
+    if review.show() {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
+
+pub fn review_amount_threshold(amount: &str) -> Result<(), ErrorCode> {
+    let review_messages = ["Set amount", "threshold"];
+    let fields = [Field {
+        name: "Threshold",
+        value: amount,
+    }];
+    let review = MultiFieldReview::new(
+        &fields,
+        &review_messages,
+        Some(&EYE),
+        "Set threshold",
         Some(&CHECKMARK),
         "Reject",
         Some(&CROSS),
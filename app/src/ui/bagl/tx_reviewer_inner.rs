@@ -12,12 +12,17 @@ use ledger_device_sdk::{
 // The `TxReviewInner` here is for Ledger Nanosp/Nanox.
 pub struct TxReviewerInner {
     is_tx_execute_script: bool,
+    is_preview: bool,
+    // The index of the tx and the number of txs of a chained session
+    chained_tx: Option<(u8, u8)>,
 }
 
 impl TxReviewerInner {
     pub fn new() -> TxReviewerInner {
         TxReviewerInner {
             is_tx_execute_script: false,
+            is_preview: false,
+            chained_tx: None,
         }
     }
 
@@ -27,6 +32,12 @@ impl TxReviewerInner {
         Ok(())
     }
 
+    // The amount and the recipient are displayed in the output review on Nano devices
+    #[inline]
+    pub fn start_transfer_review(&self, _title: &str, _recipient: &str) -> Result<(), ErrorCode> {
+        Ok(())
+    }
+
     pub fn review_fields<'a>(
         &self,
         fields: &'a [Field<'a>],
@@ -50,8 +61,7 @@ impl TxReviewerInner {
     }
 
     // Review transfer that sends to self
-    pub fn review_self_transfer(&self, fee_field: Field) -> Result<(), ErrorCode> {
-        let fields = &[fee_field];
+    pub fn review_self_transfer<'a>(&self, fields: &'a [Field<'a>]) -> Result<(), ErrorCode> {
         if self.is_tx_execute_script {
             self.finish_review_inner(fields, &["Blind Signing"], Some(&WARNING))
         } else {
@@ -59,6 +69,25 @@ impl TxReviewerInner {
         }
     }
 
+    // The section header page, the review messages of the following fields name the details
+    pub fn review_section(&self, title: &str, _description: &str) -> Result<(), ErrorCode> {
+        let review_messages = ["Section", title];
+        let review = MultiFieldReview::new(
+            &[],
+            &review_messages,
+            Some(&EYE),
+            "Continue",
+            Some(&CHECKMARK),
+            "Reject",
+            Some(&CROSS),
+        );
+        if review.show() {
+            Ok(())
+        } else {
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
     // Review the warning for external inputs, i.e. inputs that are not from the device address
     pub fn warning_external_inputs(&self) -> Result<(), ErrorCode> {
         let review_messages = ["There are", "external inputs"];
@@ -78,6 +107,124 @@ impl TxReviewerInner {
         }
     }
 
+    // Review the warning for a transaction that is not for the mainnet
+    pub fn warning_network(&self, message: &str) -> Result<(), ErrorCode> {
+        let fields = [Field {
+            name: "Warning",
+            value: message,
+        }];
+        let review_messages = ["Non-mainnet", "network"];
+        let review = MultiFieldReview::new(
+            &fields,
+            &review_messages,
+            Some(&WARNING),
+            "Continue",
+            Some(&CHECKMARK),
+            "Reject",
+            Some(&CROSS),
+        );
+        if review.show() {
+            Ok(())
+        } else {
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
+    // Review the warning for a tx whose format the device does not recognize
+    pub fn warning_unrecognized_format(&self) -> Result<(), ErrorCode> {
+        let review_messages = ["Unrecognized", "tx format"];
+        let review = MultiFieldReview::new(
+            &[],
+            &review_messages,
+            Some(&WARNING),
+            "Continue",
+            Some(&CHECKMARK),
+            "Reject",
+            Some(&CROSS),
+        );
+        if review.show() {
+            Ok(())
+        } else {
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
+    // Review the warning for fees that are high compared to the sent amount
+    // The total sent is above the threshold, so the user confirms it on a distinct screen
+    pub fn confirm_amount_above_threshold(&self) -> Result<(), ErrorCode> {
+        let review_messages = ["Amount above", "your threshold"];
+        let review = MultiFieldReview::new(
+            &[],
+            &review_messages,
+            Some(&WARNING),
+            "Confirm amount",
+            Some(&CHECKMARK),
+            "Reject",
+            Some(&CROSS),
+        );
+        if review.show() {
+            Ok(())
+        } else {
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
+    // Review the warning for a transfer to another group than the inputs
+    pub fn warning_cross_group(&self) -> Result<(), ErrorCode> {
+        let review_messages = ["Cross-group", "transfer"];
+        let review = MultiFieldReview::new(
+            &[],
+            &review_messages,
+            Some(&WARNING),
+            "Continue",
+            Some(&CHECKMARK),
+            "Reject",
+            Some(&CROSS),
+        );
+        if review.show() {
+            Ok(())
+        } else {
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
+    // Review the warning for a token without metadata, whose amount is displayed in raw units
+    pub fn warning_unknown_token(&self) -> Result<(), ErrorCode> {
+        let review_messages = ["Unknown token", "raw amount"];
+        let review = MultiFieldReview::new(
+            &[],
+            &review_messages,
+            Some(&WARNING),
+            "Continue",
+            Some(&CHECKMARK),
+            "Reject",
+            Some(&CROSS),
+        );
+        if review.show() {
+            Ok(())
+        } else {
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
+    pub fn warning_high_fees(&self) -> Result<(), ErrorCode> {
+        let review_messages = ["The fees", "are high"];
+        let review = MultiFieldReview::new(
+            &[],
+            &review_messages,
+            Some(&WARNING),
+            "Continue",
+            Some(&CHECKMARK),
+            "Reject",
+            Some(&CROSS),
+        );
+        if review.show() {
+            Ok(())
+        } else {
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
     pub fn finish_review<'a>(&self, fields: &'a [Field<'a>]) -> Result<(), ErrorCode> {
         self.finish_review_inner(fields, &[], None)
     }
@@ -88,10 +235,12 @@ impl TxReviewerInner {
         review_message: &'a [&'a str],
         review_glyph: Option<&'a Glyph<'a>>,
     ) -> Result<(), ErrorCode> {
-        let validation_messages = if !self.is_tx_execute_script {
-            ["Accept", "and sign"]
-        } else {
-            ["Accept risk", "and sign"]
+        let validation_messages = match self.chained_tx {
+            _ if self.is_preview => ["Confirm", "preview"],
+            // The txs of a chained session are signed together after the final review
+            Some(_) => ["Accept", "and continue"],
+            None if !self.is_tx_execute_script => ["Accept", "and sign"],
+            None => ["Accept risk", "and sign"],
         };
 
         let review = MultiFieldReview::new_with_validation_messages(
@@ -110,14 +259,59 @@ impl TxReviewerInner {
         }
     }
 
+    // Review the number of txs and the total sent of a chained session before signing them
+    pub fn review_chained_txs<'a>(&self, fields: &'a [Field<'a>]) -> Result<(), ErrorCode> {
+        let review = MultiFieldReview::new_with_validation_messages(
+            fields,
+            &["Review", "chained txs"],
+            Some(&EYE),
+            ["Accept all", "and sign"],
+            Some(&CHECKMARK),
+            "Reject",
+            Some(&CROSS),
+        );
+        if review.show() {
+            Ok(())
+        } else {
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
     #[inline]
     pub fn set_tx_execute_script(&mut self, is_tx_execute_script: bool) {
         self.is_tx_execute_script = is_tx_execute_script;
     }
 
+    #[inline]
+    pub fn set_preview(&mut self, is_preview: bool) {
+        self.is_preview = is_preview;
+    }
+
+    #[inline]
+    pub fn set_chained_tx(&mut self, chained_tx: Option<(u8, u8)>) {
+        self.chained_tx = chained_tx;
+    }
+
     #[inline]
     pub fn reset(&mut self) {
         self.is_tx_execute_script = false;
+        self.is_preview = false;
+        self.chained_tx = None;
+    }
+
+    #[inline]
+    pub fn chained_tx(&self) -> Option<(u8, u8)> {
+        self.chained_tx
+    }
+
+    #[inline]
+    pub fn is_preview(&self) -> bool {
+        self.is_preview
+    }
+
+    #[inline]
+    pub fn is_tx_execute_script(&self) -> bool {
+        self.is_tx_execute_script
     }
 
     #[inline]
@@ -4,14 +4,43 @@ pub mod bagl;
 pub mod nbgl;
 
 #[cfg(not(any(target_os = "stax", target_os = "flex")))]
-pub use bagl::{review_address, sign_hash_ui, tx_reviewer_inner::TxReviewerInner};
+pub use bagl::{
+    review_address, review_amount_threshold, review_change_address, review_extended_key,
+    review_message, review_multi_sig_address, review_pairing, review_response_mac_key,
+    review_typed_data, review_unusual_path, review_wallet_fingerprint, sign_hash_ui,
+    tx_reviewer_inner::TxReviewerInner,
+};
 #[cfg(any(target_os = "stax", target_os = "flex"))]
-pub use nbgl::{review_address, sign_hash_ui, tx_reviewer_inner::TxReviewerInner};
+pub use nbgl::{
+    review_address, review_amount_threshold, review_change_address, review_extended_key,
+    review_message, review_multi_sig_address, review_pairing, review_response_mac_key,
+    review_typed_data, review_unusual_path, review_wallet_fingerprint, sign_hash_ui,
+    tx_reviewer_inner::TxReviewerInner,
+};
 
 use crate::error_code::ErrorCode;
 use core::str::from_utf8;
+use utils::is_standard_path;
 pub mod tx_reviewer;
 
+// Shown on the address and review screens of keys derived with the testnet coin type
+pub const TESTNET_KEY_WARNING: &str = "TESTNET KEY";
+// Shown before signing a raw hash, whose content cannot be verified on the device
+pub const BLIND_HASH_WARNING: &str =
+    "The hash content cannot be verified. Sign only if you trust the requester.";
+// Shown before exporting an extended public key above the address level
+pub const EXTENDED_KEY_WARNING: &str =
+    "The wallet will be able to see all the addresses and transactions derived from this key.";
+
+// Keys are only derived from a non-standard path after the user has seen the path
+pub fn review_path(path: &[u32]) -> Result<(), ErrorCode> {
+    if is_standard_path(path) {
+        Ok(())
+    } else {
+        review_unusual_path(path)
+    }
+}
+
 #[inline]
 pub fn bytes_to_string(bytes: &[u8]) -> Result<&str, ErrorCode> {
     #[cfg(not(target_os = "stax"))]
@@ -1,12 +1,17 @@
 pub mod tx_reviewer_inner;
 
-use crate::{error_code::ErrorCode, public_key::sign_hash};
+use crate::{
+    error_code::ErrorCode,
+    public_key::sign_hash,
+    ui::{bytes_to_string, BLIND_HASH_WARNING, EXTENDED_KEY_WARNING, TESTNET_KEY_WARNING},
+};
 use core::str::from_utf8;
 use include_gif::include_gif;
 use ledger_device_sdk::nbgl::{
     Field, NbglAddressReview, NbglChoice, NbglGlyph, NbglReviewStatus, NbglStreamingReview,
     TransactionType,
 };
+use utils::{format_path, is_testnet_path, KeyType, MAX_PATH_STR_LENGTH};
 
 pub static APP_ICON: NbglGlyph = NbglGlyph::from_include(include_gif!("alph_64x64.gif", NBGL));
 
@@ -19,19 +24,53 @@ fn new_nbgl_review(tx_type: TransactionType, blind: bool) -> NbglStreamingReview
     }
 }
 
-fn nbgl_review_hash(hash: &str) -> bool {
-    let reviewer = new_nbgl_review(TransactionType::Operation, false);
-    if !reviewer.start("Review Hash", "") {
+fn nbgl_review_hash(hash: &str, is_testnet_key: bool) -> bool {
+    // The blind review starts with the blind signing risk page
+    let reviewer = new_nbgl_review(TransactionType::Operation, true);
+    if !reviewer.start("Review Hash", BLIND_HASH_WARNING) {
         return false;
     }
-    let fields = [Field {
-        name: "Hash",
-        value: hash,
-    }];
-    if !reviewer.continue_review(&fields) {
+    let fields = [
+        Field {
+            name: "Warning",
+            value: TESTNET_KEY_WARNING,
+        },
+        Field {
+            name: "Hash",
+            value: hash,
+        },
+    ];
+    let from_index = if is_testnet_key { 0 } else { 1 };
+    if !reviewer.continue_review(&fields[from_index..]) {
         return false;
     }
-    reviewer.finish("Sign Hash")
+    reviewer.finish("Accept risk and sign hash?")
+}
+
+pub fn review_message(fields: &[Field]) -> Result<(), ErrorCode> {
+    let reviewer = new_nbgl_review(TransactionType::Message, false);
+    let approved = reviewer.start("Review Message", "")
+        && reviewer.continue_review(fields)
+        && reviewer.finish("Sign Message");
+    NbglReviewStatus::new().show(approved);
+    if approved {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
+
+pub fn review_typed_data(fields: &[Field]) -> Result<(), ErrorCode> {
+    let reviewer = new_nbgl_review(TransactionType::Message, false);
+    let approved = reviewer.start("Review Typed Data", "")
+        && reviewer.continue_review(fields)
+        && reviewer.finish("Sign Typed Data");
+    NbglReviewStatus::new().show(approved);
+    if approved {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
 }
 
 pub fn nbgl_review_warning(
@@ -46,11 +85,17 @@ pub fn nbgl_review_warning(
         .show(message, sub_message, confirm_text, cancel_text)
 }
 
+pub fn nbgl_review_section(title: &str, description: &str) -> bool {
+    NbglChoice::new()
+        .glyph(&APP_ICON)
+        .show(title, description, "Continue", "Reject")
+}
+
 pub fn sign_hash_ui(path: &[u32], message: &[u8]) -> Result<([u8; 72], u32, u32), ErrorCode> {
     let hex: [u8; 64] = utils::to_hex(message).ok_or(ErrorCode::BadLen)?;
     match from_utf8(&hex) {
         Ok(hex_str) => {
-            if nbgl_review_hash(hex_str) {
+            if nbgl_review_hash(hex_str, is_testnet_path(path)) {
                 NbglReviewStatus::new().show(true);
                 sign_hash(path, message)
             } else {
@@ -62,10 +107,65 @@ pub fn sign_hash_ui(path: &[u32], message: &[u8]) -> Result<([u8; 72], u32, u32)
     }
 }
 
-pub fn review_address(address: &str) -> Result<(), ErrorCode> {
+pub fn review_address(address: &str, key_type: KeyType, path: &[u32]) -> Result<(), ErrorCode> {
+    let verify_str = match key_type {
+        KeyType::Default => "Verify Alephium address",
+        KeyType::Schnorr => "Verify Alephium Schnorr address",
+    };
+    show_address_review(address, verify_str, path)
+}
+
+pub fn review_change_address(
+    address: &str,
+    key_type: KeyType,
+    path: &[u32],
+) -> Result<(), ErrorCode> {
+    let verify_str = match key_type {
+        KeyType::Default => "Verify Alephium change address",
+        KeyType::Schnorr => "Verify Alephium Schnorr change address",
+    };
+    show_address_review(address, verify_str, path)
+}
+
+fn show_address_review(address: &str, verify_str: &str, path: &[u32]) -> Result<(), ErrorCode> {
+    // The address review has no extra fields, so testnet keys are flagged on a warning page
+    // and the derivation path is shown on a page before the address
+    if is_testnet_path(path)
+        && !nbgl_review_warning(
+            TESTNET_KEY_WARNING,
+            "This address uses the testnet coin type",
+            "Continue",
+            "Reject",
+        )
+    {
+        return Err(ErrorCode::UserCancelled);
+    }
+    let mut path_bytes = [0u8; MAX_PATH_STR_LENGTH];
+    let path_length = format_path(path, &mut path_bytes).ok_or(ErrorCode::InternalError)?;
+    if !nbgl_review_section(
+        "Derivation path",
+        bytes_to_string(&path_bytes[..path_length])?,
+    ) {
+        return Err(ErrorCode::UserCancelled);
+    }
+    let result = NbglAddressReview::new()
+        .glyph(&APP_ICON)
+        .verify_str(verify_str)
+        .show(address);
+    if result {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
+
+pub fn review_multi_sig_address(address: &str, policy: &str) -> Result<(), ErrorCode> {
+    if !nbgl_review_section("Required signatures", policy) {
+        return Err(ErrorCode::UserCancelled);
+    }
     let result = NbglAddressReview::new()
         .glyph(&APP_ICON)
-        .verify_str("Verify Alephium address")
+        .verify_str("Verify Alephium multi-sig address")
         .show(address);
     if result {
         Ok(())
@@ -73,3 +173,96 @@ pub fn review_address(address: &str) -> Result<(), ErrorCode> {
         Err(ErrorCode::UserCancelled)
     }
 }
+
+pub fn review_wallet_fingerprint(fingerprint: &str) -> Result<(), ErrorCode> {
+    let result = NbglChoice::new().glyph(&APP_ICON).show(
+        "Verify wallet ID",
+        fingerprint,
+        "Confirm",
+        "Reject",
+    );
+    if result {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
+
+pub fn review_pairing(address: &str) -> Result<(), ErrorCode> {
+    let result = NbglChoice::new().glyph(&APP_ICON).show(
+        "Pair with this wallet?",
+        address,
+        "Pair wallet",
+        "Reject",
+    );
+    if result {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
+
+pub fn review_response_mac_key() -> Result<(), ErrorCode> {
+    let result = nbgl_review_warning(
+        "Share response MAC key",
+        "The wallet uses this key to authenticate public keys and signatures",
+        "Share key",
+        "Reject",
+    );
+    if result {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
+
+pub fn review_extended_key(path: &[u32]) -> Result<(), ErrorCode> {
+    let mut path_bytes = [0u8; MAX_PATH_STR_LENGTH];
+    let path_length = format_path(path, &mut path_bytes).ok_or(ErrorCode::InternalError)?;
+    if !nbgl_review_section(
+        "Export extended key",
+        bytes_to_string(&path_bytes[..path_length])?,
+    ) {
+        return Err(ErrorCode::UserCancelled);
+    }
+    if nbgl_review_warning(
+        "Export extended key",
+        EXTENDED_KEY_WARNING,
+        "Export key",
+        "Reject",
+    ) {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
+
+pub fn review_unusual_path(path: &[u32]) -> Result<(), ErrorCode> {
+    let mut path_bytes = [0u8; MAX_PATH_STR_LENGTH];
+    let path_length = format_path(path, &mut path_bytes).ok_or(ErrorCode::InternalError)?;
+    let result = nbgl_review_warning(
+        "Unusual derivation path",
+        bytes_to_string(&path_bytes[..path_length])?,
+        "Continue",
+        "Reject",
+    );
+    if result {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
+
+pub fn review_amount_threshold(amount: &str) -> Result<(), ErrorCode> {
+    let result = NbglChoice::new().glyph(&APP_ICON).show(
+        "Set amount threshold?",
+        amount,
+        "Set threshold",
+        "Reject",
+    );
+    if result {
+        Ok(())
+    } else {
+        Err(ErrorCode::UserCancelled)
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     error_code::ErrorCode,
     settings::is_blind_signing_enabled,
-    ui::nbgl::{nbgl_review_warning, new_nbgl_review},
+    ui::nbgl::{nbgl_review_section, nbgl_review_warning, new_nbgl_review},
 };
 use ledger_device_sdk::nbgl::{Field, NbglReviewStatus, NbglStreamingReview, TransactionType};
 
@@ -11,6 +11,9 @@ use ledger_device_sdk::nbgl::{Field, NbglReviewStatus, NbglStreamingReview, Tran
 pub struct TxReviewerInner {
     pub display_settings: bool,
     is_tx_execute_script: bool,
+    is_preview: bool,
+    // The index of the tx and the number of txs of a chained session
+    chained_tx: Option<(u8, u8)>,
     reviewer: Option<NbglStreamingReview>,
 }
 
@@ -19,6 +22,8 @@ impl TxReviewerInner {
         TxReviewerInner {
             display_settings: false,
             is_tx_execute_script: false,
+            is_preview: false,
+            chained_tx: None,
             reviewer: None,
         }
     }
@@ -39,7 +44,7 @@ impl TxReviewerInner {
     }
 
     // Start review tx details
-    pub fn start_review(&mut self) -> Result<(), ErrorCode> {
+    pub fn start_review(&self) -> Result<(), ErrorCode> {
         let message = if self.is_tx_execute_script {
             "Review transaction"
         } else {
@@ -53,6 +58,19 @@ impl TxReviewerInner {
         }
     }
 
+    // Start the review of a simple transfer with a large-format page of the amount and the recipient
+    pub fn start_transfer_review(&self, title: &str, recipient: &str) -> Result<(), ErrorCode> {
+        if self.is_tx_execute_script {
+            return self.start_review();
+        }
+        if self.get_reviewer().start(title, recipient) {
+            Ok(())
+        } else {
+            NbglReviewStatus::new().show(false);
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
     pub fn review_fields<'a>(
         &self,
         fields: &'a [Field<'a>],
@@ -67,18 +85,27 @@ impl TxReviewerInner {
     }
 
     // Review transfer that sends to self
-    pub fn review_self_transfer(&mut self, fee_field: Field) -> Result<(), ErrorCode> {
-        if self.is_tx_execute_script {
-            self.finish_review(&[fee_field])
+    pub fn review_self_transfer<'a>(
+        &mut self,
+        fee_fields: &'a [Field<'a>],
+    ) -> Result<(), ErrorCode> {
+        if !self.is_tx_execute_script {
+            let fields = &[Field {
+                name: "Amount",
+                value: "Self-transfer",
+            }];
+            self.review_fields(fields, "Amount")?;
+        }
+        self.finish_review(fee_fields)
+    }
+
+    // The section header page between the pages of the streaming review
+    pub fn review_section(&self, title: &str, description: &str) -> Result<(), ErrorCode> {
+        if nbgl_review_section(title, description) {
+            Ok(())
         } else {
-            let fields = &[
-                Field {
-                    name: "Amount",
-                    value: "Self-transfer",
-                },
-                fee_field,
-            ];
-            self.finish_review(fields)
+            NbglReviewStatus::new().show(false);
+            Err(ErrorCode::UserCancelled)
         }
     }
 
@@ -97,17 +124,106 @@ impl TxReviewerInner {
         }
     }
 
+    // Review the warning for a transaction that is not for the mainnet
+    pub fn warning_network(&self, message: &str) -> Result<(), ErrorCode> {
+        let approved = nbgl_review_warning("Non-mainnet network", message, "Continue", "Reject");
+        if approved {
+            Ok(())
+        } else {
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
+    // Review the warning for a tx whose format the device does not recognize
+    pub fn warning_unrecognized_format(&self) -> Result<(), ErrorCode> {
+        let approved = nbgl_review_warning(
+            "Unrecognized transaction format",
+            "The device cannot decode this transaction, only its ID can be reviewed. Sign only if you trust the requester.",
+            "Continue",
+            "Reject",
+        );
+        if approved {
+            Ok(())
+        } else {
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
+    // Review the warning for fees that are high compared to the sent amount
+    // The total sent is above the threshold, so the user confirms it on a distinct screen
+    pub fn confirm_amount_above_threshold(&self) -> Result<(), ErrorCode> {
+        let approved = nbgl_review_warning(
+            "Amount above your threshold",
+            "The total sent is above the amount threshold set on this device.",
+            "Confirm amount",
+            "Reject",
+        );
+        if approved {
+            Ok(())
+        } else {
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
+    // Review the warning for a transfer to another group than the inputs
+    pub fn warning_cross_group(&self) -> Result<(), ErrorCode> {
+        let approved = nbgl_review_warning(
+            "Cross-group transfer",
+            "This transaction sends to another group, it takes longer to confirm.",
+            "Continue",
+            "Reject",
+        );
+        if approved {
+            Ok(())
+        } else {
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
+    // Review the warning for a token without metadata, whose amount is displayed in raw units
+    pub fn warning_unknown_token(&self) -> Result<(), ErrorCode> {
+        let approved = nbgl_review_warning(
+            "Unknown token",
+            "A token of this transaction is unknown to the device, its amount is shown in raw units without decimals.",
+            "Continue",
+            "Reject",
+        );
+        if approved {
+            Ok(())
+        } else {
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
+    pub fn warning_high_fees(&self) -> Result<(), ErrorCode> {
+        let approved = nbgl_review_warning(
+            "High fees",
+            "The fees of this transaction are high compared to the amount sent.",
+            "Continue",
+            "Reject",
+        );
+        if approved {
+            Ok(())
+        } else {
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
     pub fn finish_review<'a>(&mut self, fee_fields: &'a [Field<'a>]) -> Result<(), ErrorCode> {
         assert!(!fee_fields.is_empty());
         self.reset_display_settings();
         self.review_fields(fee_fields, "Fees")?;
-        let message = if self.is_tx_execute_script {
-            "Accept risk and sign transaction?"
-        } else {
-            "Sign transaction to send assets?"
+        let message = match self.chained_tx {
+            _ if self.is_preview => "Confirm transaction preview? Nothing will be signed.",
+            // The txs of a chained session are signed together after the final review
+            Some(_) => "Approve transaction and continue?",
+            None if self.is_tx_execute_script => "Accept risk and sign transaction?",
+            None => "Sign transaction to send assets?",
         };
         if self.get_reviewer().finish(message) {
-            NbglReviewStatus::new().show(true);
+            if self.chained_tx.is_none() {
+                NbglReviewStatus::new().show(true);
+            }
             Ok(())
         } else {
             NbglReviewStatus::new().show(false);
@@ -115,6 +231,20 @@ impl TxReviewerInner {
         }
     }
 
+    // Review the number of txs and the total sent of a chained session before signing them
+    pub fn review_chained_txs(&self, fields: &[Field]) -> Result<(), ErrorCode> {
+        let reviewer = new_nbgl_review(TransactionType::Transaction, false);
+        let approved = reviewer.start("Review chained transactions", "")
+            && reviewer.continue_review(fields)
+            && reviewer.finish("Sign all the chained transactions?");
+        NbglReviewStatus::new().show(approved);
+        if approved {
+            Ok(())
+        } else {
+            Err(ErrorCode::UserCancelled)
+        }
+    }
+
     #[inline]
     pub fn reset(&mut self) {
         // Since `reset` is called when blind signing checks fails,
@@ -122,6 +252,33 @@ impl TxReviewerInner {
         // Instead, we will reset the `display_settings` in the `finish_review` function.
         self.reviewer = None;
         self.is_tx_execute_script = false;
+        self.is_preview = false;
+        self.chained_tx = None;
+    }
+
+    #[inline]
+    pub fn set_chained_tx(&mut self, chained_tx: Option<(u8, u8)>) {
+        self.chained_tx = chained_tx;
+    }
+
+    #[inline]
+    pub fn chained_tx(&self) -> Option<(u8, u8)> {
+        self.chained_tx
+    }
+
+    #[inline]
+    pub fn set_preview(&mut self, is_preview: bool) {
+        self.is_preview = is_preview;
+    }
+
+    #[inline]
+    pub fn is_preview(&self) -> bool {
+        self.is_preview
+    }
+
+    #[inline]
+    pub fn is_tx_execute_script(&self) -> bool {
+        self.is_tx_execute_script
     }
 
     #[inline]
@@ -1,13 +1,20 @@
 use super::TxReviewerInner;
 use crate::{
-    blake2b_hasher::Blake2bHasher,
+    blake2b_hasher::{Blake2bHasher, BLAKE2B_HASH_SIZE},
     error_code::ErrorCode,
     handler::TOKEN_METADATA_SIZE,
     nvm::swapping_buffer::{SwappingBuffer, RAM_SIZE},
     nvm::{NVM, NVM_DATA_SIZE},
-    public_key::{to_base58_address, Address},
+    public_key::{
+        is_schnorr_script, to_base58_address, DeviceAddresses, InputAddresses,
+        COMPRESSED_PUBKEY_SIZE,
+    },
+    settings::{
+        get_amount_threshold, is_cross_group_warning_disabled, is_display_tx_id_enabled,
+        is_expert_mode_enabled, is_mainnet_only_enabled, is_unknown_token_warning_enabled,
+    },
     token_verifier::TokenVerifier,
-    ui::bytes_to_string,
+    ui::{bytes_to_string, review_multi_sig_address, TESTNET_KEY_WARNING},
 };
 #[cfg(any(target_os = "stax", target_os = "flex"))]
 use ledger_device_sdk::nbgl::Field;
@@ -15,20 +22,102 @@ use ledger_device_sdk::nbgl::Field;
 use ledger_device_sdk::ui::gadgets::Field;
 use ledger_device_sdk::NVMData;
 use utils::{
-    base58::ALPHABET,
+    base58::{base58_encode_inputs, ALPHABET},
+    checksum_words::{write_checksum_words, MAX_CHECKSUM_WORDS_LENGTH},
+    deserialize_path,
+    displayed_addresses::DisplayedAddresses,
+    estimated_outputs::EstimatedOutputs,
+    get_script_group,
+    public_key_hash_cache::PublicKeyHashCache,
+    sub_contract::{NftCollection, NftCollections},
+    to_hex, to_hex_fixed,
+    token_totals::{TokenTotals, MAX_TOKEN_TOTALS},
     types::{
-        AssetOutput, Byte32, Hash, LockupScript, Token, TxInput, UnlockScript, UnsignedTx, I32,
-        U256,
+        approved_assets::{CallMethod, Const, ContractCall, ALPH_TOKEN_ID, MAX_CALL_ARGS},
+        asset_output::{MAX_ADDITIONAL_DATA_PREFIX_SIZE, MAX_OUTPUT_TOKENS},
+        u256::{
+            MAX_ALPH_AMOUNT_LENGTH, MAX_TOKEN_AMOUNT_LENGTH, MAX_TOKEN_SYMBOL_LENGTH,
+            THOUSANDS_SEPARATOR,
+        },
+        unlock_script::{PublicKeyWithIndex, SigningKeyIndex},
+        unsigned_tx::is_supported_tx_version,
+        ApprovedAssets, AssetOutput, Byte32, ContractCalls, Hash, LockupScript, TimeStamp, Token,
+        TxInput, UnlockScript, UnsignedTx, I32, U256,
     },
+    PATH_LENGTH,
 };
 
 #[link_section = ".nvm_data"]
 static mut DATA: NVMData<NVM<NVM_DATA_SIZE>> = NVMData::new(NVM::zeroed());
 
 const FIRST_OUTPUT_INDEX: u16 = 1;
-const MAX_TOKEN_SYMBOL_LENGTH: usize = 12;
+// The number of groups of the mainnet and the testnet
+const GROUP_NUM: u8 = 4;
+const P2MPKH_PREFIX: u8 = 1;
+const P2SH_PREFIX: u8 = 2;
+const P2C_PREFIX: u8 = 3;
+const P2PK_PREFIX: u8 = 4;
+const P2HMPK_PREFIX: u8 = 5;
+const MAINNET_NETWORK_ID: u8 = 0;
+const TESTNET_NETWORK_ID: u8 = 1;
+// The base58 address of a multi-sig lockup script of 5 keys fits in a response
+pub const MAX_MULTI_SIG_ADDRESS_KEYS: usize = 5;
+const TESTNET_WARNING: &str = "You are signing a TESTNET transaction";
+// Any other network id is a local or private network
+const DEVNET_WARNING: &str = "You are signing a DEVNET transaction";
 const TOKEN_METADATA_VERSION: u8 = 0;
+// The number of hex chars displayed at both ends of a token id
+const TOKEN_ID_AFFIX_LENGTH: usize = 8;
+// The title of a simple transfer is the prefix followed by the sent amount
+const TRANSFER_TITLE_PREFIX: &[u8] = b"Send ";
+// The maximum number of fields displayed for an output
+const MAX_OUTPUT_FIELDS: usize = 13 + 3 * MAX_OUTPUT_TOKENS;
+// The maximum number of fields displayed before signing
+const MAX_FINAL_FIELDS: usize = 8;
+// A self transfer that spends more inputs into at most 2 outputs is a UTXO consolidation
+const MIN_CONSOLIDATION_INPUTS: usize = 3;
+const MAX_CONSOLIDATION_OUTPUTS: usize = 2;
+// The fees are high if they exceed 1 ALPH or 10% of the total ALPH sent
+const HIGH_FEE_THRESHOLD: u128 = 1_000_000_000_000_000_000;
+const HIGH_FEE_PERCENTAGE: u128 = 10;
+// The full node rejects outputs with less than 0.001 ALPH
+const DUST_AMOUNT: u128 = 1_000_000_000_000_000;
+// A raw amount with more digits is easy to misread without the token decimals, so the unknown
+// token warning is displayed even if the setting is disabled
+const LARGE_RAW_AMOUNT: u128 = 1_000_000_000_000;
+const DUST_WARNING: &str = "Below the dust amount of 0.001 ALPH, the tx may be rejected";
+const CALL_ARG_NAMES: [&str; MAX_CALL_ARGS] = ["Argument #1", "Argument #2", "Argument #3"];
 type TokenSymbol = [u8; MAX_TOKEN_SYMBOL_LENGTH];
+// The ranges of the script type, the raw amount and the raw token amounts of an output in expert mode
+type ExpertOutputIndexes = (
+    (usize, usize),
+    (usize, usize),
+    [Option<(usize, usize)>; MAX_OUTPUT_TOKENS],
+);
+
+// The sections of the review of a script tx, so that the contract call is not
+// conflated with the plain transfers
+#[derive(PartialEq, Clone, Copy)]
+enum ReviewSection {
+    ContractInteraction,
+    AssetTransfers,
+}
+
+impl ReviewSection {
+    fn title(&self) -> &'static str {
+        match self {
+            ReviewSection::ContractInteraction => "Contract interaction",
+            ReviewSection::AssetTransfers => "Asset transfers",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            ReviewSection::ContractInteraction => "Assets the contract is allowed to spend",
+            ReviewSection::AssetTransfers => "Outputs sent by this transaction",
+        }
+    }
+}
 
 // The TxReviewer is used to review the transaction details
 // It keeps track of the transaction details and the current state
@@ -36,10 +125,46 @@ type TokenSymbol = [u8; MAX_TOKEN_SYMBOL_LENGTH];
 pub struct TxReviewer {
     buffer: SwappingBuffer<'static, RAM_SIZE, NVM_DATA_SIZE>,
     has_external_inputs: bool,
+    // The bit of each device address that the inputs spend from
+    input_address_bits: u8,
+    // Whether an input is spent from the address of an input path
+    has_input_path_inputs: bool,
+    // All the inputs of a tx are from the same group
+    input_group: Option<u8>,
+    // The script hash of the first Schnorr script revealed by the inputs
+    schnorr_script_hash: Option<[u8; BLAKE2B_HASH_SIZE]>,
+    // The fingerprint and the index of the last displayed output, and the number and the
+    // total amount of the identical outputs that follow it
+    last_output: Option<([u8; BLAKE2B_HASH_SIZE], u16)>,
+    repeated_output_count: u16,
+    repeated_output_amount: U256,
+    is_review_started: bool,
+    is_testnet_key: bool,
+    input_count: usize,
+    output_count: usize,
+    // The output being reviewed is the last output of the tx
+    is_last_output: bool,
     next_output_index: u16,
     tx_fee: Option<U256>,
+    gas_amount: I32,
+    gas_price: U256,
+    total_sent: U256,
+    has_token_transfers: bool,
+    token_totals: TokenTotals,
     token_metadata_length: usize,
     token_verifier: Option<TokenVerifier>,
+    public_key_hash_cache: PublicKeyHashCache,
+    displayed_addresses: DisplayedAddresses,
+    current_section: Option<ReviewSection>,
+    estimated_outputs: EstimatedOutputs,
+    nft_collections: NftCollections,
+    // The addresses of the other device paths that the inputs are spent from
+    input_addresses: InputAddresses,
+    is_unrecognized_format: bool,
+    // The unknown token warning is only displayed once per tx
+    is_unknown_token_approved: bool,
+    // The cross-group warning is only displayed once per tx
+    is_cross_group_approved: bool,
     inner: TxReviewerInner,
 }
 
@@ -48,38 +173,121 @@ impl TxReviewer {
         Self {
             buffer: unsafe { SwappingBuffer::new(&mut DATA) },
             has_external_inputs: false,
+            input_address_bits: 0,
+            has_input_path_inputs: false,
+            input_group: None,
+            schnorr_script_hash: None,
+            last_output: None,
+            repeated_output_count: 0,
+            repeated_output_amount: U256::default(),
+            is_review_started: false,
+            is_testnet_key: false,
+            input_count: 0,
+            output_count: 0,
+            is_last_output: false,
             next_output_index: FIRST_OUTPUT_INDEX, // display output from index 1, similar to BTC
             tx_fee: None,
+            gas_amount: I32::default(),
+            gas_price: U256::default(),
+            total_sent: U256::default(),
+            has_token_transfers: false,
+            token_totals: TokenTotals::default(),
             token_metadata_length: 0,
             token_verifier: None,
+            public_key_hash_cache: PublicKeyHashCache::new(),
+            displayed_addresses: DisplayedAddresses::new(),
+            current_section: None,
+            estimated_outputs: EstimatedOutputs::default(),
+            nft_collections: NftCollections::default(),
+            input_addresses: InputAddresses::new(),
+            is_unrecognized_format: false,
+            is_unknown_token_approved: false,
+            is_cross_group_approved: false,
             inner: TxReviewerInner::new(),
         }
     }
 
     #[inline]
+    // The maximum size of the displayed details of a single tx field
+    pub fn buffer_capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
     fn reset_buffer(&mut self, from_index: usize) {
         self.buffer.reset(from_index);
     }
 
     #[inline]
     pub fn init(&mut self, token_size: u8) -> Result<(), ErrorCode> {
-        self.reset_buffer(0);
+        self.buffer.clear();
         self.has_external_inputs = false;
+        self.input_address_bits = 0;
+        self.has_input_path_inputs = false;
+        self.input_group = None;
+        self.schnorr_script_hash = None;
+        self.last_output = None;
+        self.repeated_output_count = 0;
+        self.repeated_output_amount = U256::default();
+        self.is_review_started = false;
+        self.is_testnet_key = false;
+        self.input_count = 0;
+        self.output_count = 0;
+        self.is_last_output = false;
         self.next_output_index = FIRST_OUTPUT_INDEX;
         self.tx_fee = None;
+        self.gas_amount = I32::default();
+        self.gas_price = U256::default();
+        self.total_sent = U256::default();
+        self.has_token_transfers = false;
+        self.token_totals.reset();
         self.token_metadata_length = (token_size as usize) * TOKEN_METADATA_SIZE;
         self.token_verifier = None;
+        self.public_key_hash_cache.reset();
+        self.displayed_addresses.reset();
+        self.current_section = None;
+        self.estimated_outputs = EstimatedOutputs::default();
+        self.nft_collections.reset();
+        self.input_addresses.reset();
+        self.is_unrecognized_format = false;
+        self.is_unknown_token_approved = false;
+        self.is_cross_group_approved = false;
         self.inner = TxReviewerInner::new();
         Ok(())
     }
 
     pub fn reset(&mut self) {
-        self.reset_buffer(0);
+        self.buffer.clear();
         self.has_external_inputs = false;
+        self.input_address_bits = 0;
+        self.has_input_path_inputs = false;
+        self.input_group = None;
+        self.schnorr_script_hash = None;
+        self.last_output = None;
+        self.repeated_output_count = 0;
+        self.repeated_output_amount = U256::default();
+        self.is_review_started = false;
+        self.is_testnet_key = false;
+        self.input_count = 0;
+        self.output_count = 0;
+        self.is_last_output = false;
         self.next_output_index = FIRST_OUTPUT_INDEX;
         self.tx_fee = None;
+        self.gas_amount = I32::default();
+        self.gas_price = U256::default();
+        self.total_sent = U256::default();
+        self.has_token_transfers = false;
+        self.token_totals.reset();
         self.token_metadata_length = 0;
         self.token_verifier = None;
+        self.public_key_hash_cache.reset();
+        self.displayed_addresses.reset();
+        self.current_section = None;
+        self.estimated_outputs = EstimatedOutputs::default();
+        self.nft_collections.reset();
+        self.input_addresses.reset();
+        self.is_unrecognized_format = false;
+        self.is_unknown_token_approved = false;
+        self.is_cross_group_approved = false;
         self.inner.reset();
     }
 
@@ -120,6 +328,44 @@ impl TxReviewer {
         result
     }
 
+    // The host can attach the simulated outputs of a script tx before the tx data
+    pub fn handle_estimated_outputs(&mut self, data: &[u8]) -> Result<(), ErrorCode> {
+        match EstimatedOutputs::decode(data) {
+            Some(estimated_outputs) => {
+                self.estimated_outputs = estimated_outputs;
+                Ok(())
+            }
+            None => Err(ErrorCode::BadLen),
+        }
+    }
+
+    #[inline]
+    pub fn has_estimated_outputs(&self) -> bool {
+        !self.estimated_outputs.is_empty()
+    }
+
+    // The host can attach the collection contract id and the sub-contract path of an NFT,
+    // the token id is derived from them so the NFT can be displayed by its collection
+    pub fn handle_nft_metadata(&mut self, data: &[u8]) -> Result<(), ErrorCode> {
+        self.nft_collections
+            .add(data, Blake2bHasher::hash_inputs, ErrorCode::BadLen)
+    }
+
+    // The host can attach the paths of the other device addresses that the inputs are spent from,
+    // so that these inputs are not displayed as external inputs
+    pub fn handle_input_paths(&mut self, data: &[u8]) -> Result<(), ErrorCode> {
+        let encoded_path_size = PATH_LENGTH * 4;
+        if data.is_empty() || data.len() % encoded_path_size != 0 {
+            return Err(ErrorCode::BadLen);
+        }
+        for encoded_path in data.chunks(encoded_path_size) {
+            let mut path = [0u32; PATH_LENGTH];
+            deserialize_path(encoded_path, &mut path, ErrorCode::HDPathDecodingFailed)?;
+            self.input_addresses.add(&path)?;
+        }
+        Ok(())
+    }
+
     fn write_token_metadata(&mut self, token_metadata: &[u8]) -> Result<(), ErrorCode> {
         let size = self.buffer.write(token_metadata)?;
         if size > self.token_metadata_length {
@@ -129,22 +375,97 @@ impl TxReviewer {
         }
     }
 
+    #[inline]
+    pub fn set_testnet_key(&mut self, is_testnet_key: bool) {
+        self.is_testnet_key = is_testnet_key;
+    }
+
     #[inline]
     pub fn set_tx_execute_script(&mut self, is_tx_execute_script: bool) {
         self.inner.set_tx_execute_script(is_tx_execute_script);
     }
 
+    // Only the tx id of a tx of an unrecognized format is reviewed, which requires blind signing.
+    // The network id of a tx of an unknown version is not checked, so it is refused in mainnet only mode.
+    pub fn set_unrecognized_format(&mut self, is_network_checked: bool) -> Result<(), ErrorCode> {
+        if !is_network_checked && is_mainnet_only_enabled() {
+            return Err(ErrorCode::MainnetOnly);
+        }
+        self.check_blind_signing()?;
+        self.is_unrecognized_format = true;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_preview(&mut self, is_preview: bool) {
+        self.inner.set_preview(is_preview);
+    }
+
+    #[inline]
+    pub fn is_preview(&self) -> bool {
+        self.inner.is_preview()
+    }
+
+    #[inline]
+    pub fn set_chained_tx(&mut self, chained_tx: Option<(u8, u8)>) {
+        self.inner.set_chained_tx(chained_tx);
+    }
+
+    // Whether all the inputs are spent from the address of the first signing path
+    #[inline]
+    pub fn is_spent_by_first_path(&self) -> bool {
+        self.input_address_bits == 1 && !self.has_external_inputs && !self.has_input_path_inputs
+    }
+
+    #[inline]
+    pub fn total_sent(&self) -> &U256 {
+        &self.total_sent
+    }
+
+    // The approved txs of a chained session are reviewed together before they are signed
+    pub fn review_chained_txs(&self, count: usize, total_sent: &U256) -> Result<(), ErrorCode> {
+        let count_output = [b'0' + count as u8];
+        let mut total_sent_output = [0u8; MAX_ALPH_AMOUNT_LENGTH];
+        let total_sent = total_sent
+            .to_alph(&mut total_sent_output, Some(THOUSANDS_SEPARATOR))
+            .ok_or(ErrorCode::Overflow)?;
+        let fields = [
+            Field {
+                name: "Transactions",
+                value: bytes_to_string(&count_output)?,
+            },
+            Field {
+                name: "Total Sent",
+                value: bytes_to_string(total_sent)?,
+            },
+        ];
+        self.inner.review_chained_txs(&fields)
+    }
+
     // Write the amount in alph format
     fn write_alph_amount(&mut self, u256: &U256) -> Result<usize, ErrorCode> {
-        let mut amount_output = [0u8; 33];
-        let amount_str = u256.to_alph(&mut amount_output).unwrap();
+        let mut amount_output = [0u8; MAX_ALPH_AMOUNT_LENGTH];
+        let amount_str = u256
+            .to_alph(&mut amount_output, Some(THOUSANDS_SEPARATOR))
+            .ok_or(ErrorCode::Overflow)?;
         self.buffer.write(amount_str)
     }
 
+    // Write the checksum words of an address, the words are derived from the hash of the base58 address
+    fn write_checksum_words(&mut self, address_hash: &[u8]) -> Result<usize, ErrorCode> {
+        let mut output = [0u8; MAX_CHECKSUM_WORDS_LENGTH];
+        match write_checksum_words(address_hash, &mut output) {
+            Some(words) => self.buffer.write(words),
+            None => Err(ErrorCode::Overflow),
+        }
+    }
+
     // Write the amount in raw format
     fn write_token_raw_amount(&mut self, u256: &U256) -> Result<usize, ErrorCode> {
-        let mut amount_output = [0u8; 78]; // u256 max
-        let amount_str = u256.to_str(&mut amount_output).unwrap();
+        let mut amount_output = [0u8; 103]; // u256 max with separators
+        let amount_str = u256
+            .to_str_with_decimals(&mut amount_output, 0, Some(THOUSANDS_SEPARATOR))
+            .ok_or(ErrorCode::Overflow)?;
         self.buffer.write(amount_str)
     }
 
@@ -155,23 +476,35 @@ impl TxReviewer {
         symbol: TokenSymbol,
         decimals: usize,
     ) -> Result<usize, ErrorCode> {
-        let mut amount_output = [0u8; 86]; // u256 max
+        let mut amount_output = [0u8; MAX_TOKEN_AMOUNT_LENGTH];
         let symbol_bytes = get_token_symbol_bytes(&symbol[..]);
-        amount_output[..symbol_bytes.len()].copy_from_slice(symbol_bytes);
-        amount_output[symbol_bytes.len()] = b' ';
-        let prefix_length = symbol_bytes.len() + 1;
-        let amount_str = u256.to_str_with_decimals(&mut amount_output[prefix_length..], decimals);
-        if amount_str.is_none() {
-            return Err(ErrorCode::Overflow);
-        }
-        let total_length = prefix_length + amount_str.unwrap().len();
-        self.buffer.write(&amount_output[..total_length])
+        let amount_str = u256
+            .to_token_str(
+                &mut amount_output,
+                decimals,
+                symbol_bytes,
+                Some(THOUSANDS_SEPARATOR),
+            )
+            .ok_or(ErrorCode::Overflow)?;
+        self.buffer.write(amount_str)
     }
 
     // Write the token id in hex format
     fn write_token_id(&mut self, token_id: &Byte32) -> Result<usize, ErrorCode> {
         let hex_str: [u8; 64] = utils::to_hex(&token_id.0).unwrap();
-        self.buffer.write(&hex_str)
+        if is_expert_mode_enabled() {
+            return self.buffer.write(&hex_str);
+        }
+        // The first and last 8 hex chars are followed by the checksum words of the hex id,
+        // which the host wallet derives with the same wordlist as the address checksum words
+        let hash = Blake2bHasher::hash(&hex_str)?;
+        self.buffer.write(&hex_str[..TOKEN_ID_AFFIX_LENGTH])?;
+        self.buffer.write(b"...")?;
+        self.buffer
+            .write(&hex_str[(hex_str.len() - TOKEN_ID_AFFIX_LENGTH)..])?;
+        self.buffer.write(b" (")?;
+        self.write_checksum_words(&hash)?;
+        self.buffer.write(b")")
     }
 
     // Update the buffer with the carry
@@ -211,7 +544,7 @@ impl TxReviewer {
                 for i in 0..length {
                     temp0[length - i - 1] = ALPHABET[stored[i] as usize];
                 }
-                self.buffer.update(begin, &temp0[..length]);
+                self.buffer.update(begin, &temp0[..length])?;
                 return Ok(());
             }
 
@@ -222,8 +555,8 @@ impl TxReviewer {
                 temp0[index] = ALPHABET[left[i] as usize];
                 temp1[index] = ALPHABET[right[i] as usize];
             }
-            self.buffer.update(begin, &temp1);
-            self.buffer.update(end - 64, &temp0);
+            self.buffer.update(begin, &temp1)?;
+            self.buffer.update(end - 64, &temp0)?;
             end -= 64;
             begin += 64;
         }
@@ -268,6 +601,39 @@ impl TxReviewer {
         Ok(to_index)
     }
 
+    // Compute the address of a multi-sig lockup script from the public keys of the co-signers,
+    // so that each co-signer can verify the shared address independently of the coordinator.
+    // The lockup script is the P2MPKH prefix, the number of keys, the key hashes and the threshold
+    pub fn review_multi_sig_address(
+        &mut self,
+        public_keys: &[u8],
+        threshold: usize,
+    ) -> Result<&[u8], ErrorCode> {
+        let key_size = public_keys.len() / COMPRESSED_PUBKEY_SIZE;
+        assert!(key_size <= MAX_MULTI_SIG_ADDRESS_KEYS && threshold <= key_size);
+        let mut lockup_script = [0u8; 3 + MAX_MULTI_SIG_ADDRESS_KEYS * BLAKE2B_HASH_SIZE];
+        lockup_script[0] = P2MPKH_PREFIX;
+        lockup_script[1] = key_size as u8;
+        let mut length = 2;
+        for public_key in public_keys.chunks(COMPRESSED_PUBKEY_SIZE) {
+            let public_key_hash = Blake2bHasher::hash(public_key)?;
+            lockup_script[length..(length + BLAKE2B_HASH_SIZE)].copy_from_slice(&public_key_hash);
+            length += BLAKE2B_HASH_SIZE;
+        }
+        lockup_script[length] = threshold as u8;
+        length += 1;
+
+        self.reset();
+        let address_from_index = self.buffer.get_index();
+        let address_to_index = self.write_multi_sig(&lockup_script[..length])?;
+        let policy = self.write_multi_sig_policy(threshold, key_size)?;
+        review_multi_sig_address(
+            self.get_str_from_range((address_from_index, address_to_index))?,
+            self.get_str_from_range(policy)?,
+        )?;
+        Ok(self.buffer.read(address_from_index, address_to_index))
+    }
+
     // Write the output index with a prefix
     fn write_index_with_prefix(&mut self, index: usize, prefix: &[u8]) -> Result<usize, ErrorCode> {
         let mut output = [0u8; 13];
@@ -281,7 +647,70 @@ impl TxReviewer {
         self.buffer.write(&output[..total_size])
     }
 
+    // Write the group of an output, an output to another group than the inputs is flagged
+    // because cross-group transfers take longer to confirm
+    fn write_group(&mut self, group: u8) -> Result<(usize, usize), ErrorCode> {
+        let from_index = self.buffer.get_index();
+        let mut to_index = self.write_index_with_prefix(group as usize, b"")?;
+        if matches!(self.input_group, Some(input_group) if input_group != group) {
+            to_index = self.buffer.write(b" (cross-group)")?;
+        }
+        Ok((from_index, to_index))
+    }
+
+    // Write the time until which the output is locked
+    fn write_lock_time(&mut self, lock_time: &TimeStamp) -> Result<(usize, usize), ErrorCode> {
+        let mut output = [0u8; TimeStamp::UTC_STR_LENGTH];
+        let str = lock_time
+            .to_utc_str(&mut output)
+            .ok_or(ErrorCode::Overflow)?;
+        let from_index = self.buffer.get_index();
+        let to_index = self.buffer.write(str)?;
+        Ok((from_index, to_index))
+    }
+
+    // Write the additional data as text if all the bytes are printable, otherwise in hex
+    fn write_additional_data(&mut self, output: &AssetOutput) -> Result<(usize, usize), ErrorCode> {
+        let data = output.additional_data();
+        let from_index = self.buffer.get_index();
+        let mut to_index = if data.iter().all(|byte| (0x20..0x7f).contains(byte)) {
+            self.buffer.write(data)?
+        } else {
+            let hex: [u8; MAX_ADDITIONAL_DATA_PREFIX_SIZE * 2] =
+                to_hex(data).ok_or(ErrorCode::Overflow)?;
+            self.buffer.write(&hex[..(data.len() * 2)])?
+        };
+        if output.is_additional_data_truncated() {
+            to_index = self.buffer.write(b"...")?;
+        }
+        Ok((from_index, to_index))
+    }
+
+    // Write the threshold and the number of keys of a multi-sig address, e.g. `2 of 3`
+    fn write_multi_sig_policy(&mut self, m: usize, n: usize) -> Result<(usize, usize), ErrorCode> {
+        let from_index = self.buffer.get_index();
+        self.write_index_with_prefix(m, b"")?;
+        let to_index = self.write_index_with_prefix(n, b" of ")?;
+        Ok((from_index, to_index))
+    }
+
     // Write the address
+    // A groupless address is the base58 encoding of the type and the public key, or the hash of
+    // a multi-sig wallet, followed by the group of the lockup script
+    fn write_groupless_address(
+        &mut self,
+        prefix: u8,
+        payload: &[u8],
+        group: u8,
+    ) -> Result<usize, ErrorCode> {
+        let mut output = [0u8; 64];
+        let str_bytes =
+            base58_encode_inputs(&[&[prefix], payload], &mut output).ok_or(ErrorCode::Overflow)?;
+        self.buffer.write(str_bytes)?;
+        self.buffer.write(b":")?;
+        self.write_index_with_prefix(group as usize, b"")
+    }
+
     pub fn write_address(&mut self, prefix: u8, hash: &[u8; 32]) -> Result<usize, ErrorCode> {
         let mut output = [0u8; 46];
         let str_bytes = to_base58_address(prefix, hash, &mut output)?;
@@ -311,7 +740,7 @@ impl TxReviewer {
     fn prepare_output(
         &mut self,
         output: &AssetOutput,
-        device_address: &Address,
+        device_addresses: &DeviceAddresses,
         temp_data: &[u8],
     ) -> Result<Option<OutputIndexes>, ErrorCode> {
         let address_from_index = self.buffer.get_index();
@@ -320,37 +749,127 @@ impl TxReviewer {
                 self.write_address(output.lockup_script.get_type(), &hash.0)?
             }
             LockupScript::P2MPKH(_) => self.write_multi_sig(temp_data)?,
-            _ => panic!(), // dead branch
+            LockupScript::P2PK(p2pk) => self.write_groupless_address(
+                P2PK_PREFIX,
+                p2pk.public_key(),
+                p2pk.get_group(GROUP_NUM),
+            )?,
+            LockupScript::P2HMPK(p2hmpk) => self.write_groupless_address(
+                P2HMPK_PREFIX,
+                &p2hmpk.inner.hash.0,
+                p2hmpk.inner.get_group(GROUP_NUM),
+            )?,
+            // A contract address only receives assets through a script
+            LockupScript::P2C(_) | LockupScript::Unknown => {
+                return Err(ErrorCode::UnsupportedLockupScript)
+            }
+        };
+        let multi_sig_policy = match &output.lockup_script {
+            LockupScript::P2MPKH(multi_sig) => Some(self.write_multi_sig_policy(
+                multi_sig.inner.m.inner as usize,
+                multi_sig.inner.size.inner as usize,
+            )?),
+            _ => None,
         };
 
         let address = self.buffer.read(address_from_index, address_to_index);
-        if device_address.eq(address) {
+        if device_addresses.contains(address) {
             return Ok(None);
         }
+        let address_hash = Blake2bHasher::hash(address)?;
+        // Only the prefix of the additional data is kept, so outputs with truncated
+        // additional data are never considered identical
+        let fingerprint = if output.is_additional_data_truncated() {
+            None
+        } else {
+            Some(output_fingerprint(output, &address_hash)?)
+        };
+        let checksum_words_from_index = self.buffer.get_index();
+        let checksum_words_to_index = self.write_checksum_words(&address_hash)?;
 
+        let output_index = self.next_output_index;
         let review_message_from_index = self.buffer.get_index();
         let review_message_to_index =
-            self.write_index_with_prefix(self.next_output_index as usize, b"Output #")?;
+            self.write_index_with_prefix(output_index as usize, b"Output #")?;
         self.next_output_index += 1;
 
+        // The later outputs that send to a displayed address refer to the first output that sends
+        // to it, the device has checked that the address is the same as the referred output.
+        // The full address is still displayed in the next field
+        let address_reference = match self.displayed_addresses.get(&address_hash) {
+            Some(displayed_output_index) => {
+                let from_index = self.buffer.get_index();
+                let to_index = self.write_index_with_prefix(
+                    displayed_output_index as usize,
+                    b"Same as output #",
+                )?;
+                Some((from_index, to_index))
+            }
+            None => {
+                self.displayed_addresses.add(&address_hash, output_index);
+                None
+            }
+        };
+
+        // A P2SH output only has the script hash, so it is only recognized as a Schnorr
+        // address if an input has revealed the same script
+        let is_schnorr_address = matches!(
+            &output.lockup_script,
+            LockupScript::P2SH(hash) if self.schnorr_script_hash == Some(hash.0)
+        );
+        let group = match &output.lockup_script {
+            LockupScript::P2PKH(hash) | LockupScript::P2SH(hash) => {
+                Some(get_script_group(&hash.0, GROUP_NUM))
+            }
+            LockupScript::P2MPKH(_) => {
+                first_multi_sig_key_hash(temp_data).map(|hash| get_script_group(hash, GROUP_NUM))
+            }
+            LockupScript::P2PK(p2pk) => Some(p2pk.get_group(GROUP_NUM)),
+            LockupScript::P2HMPK(p2hmpk) => Some(p2hmpk.inner.get_group(GROUP_NUM)),
+            _ => None,
+        };
+        let is_cross_group = matches!(
+            (group, self.input_group),
+            (Some(group), Some(input_group)) if group != input_group
+        );
+        let group = match group {
+            Some(group) => Some(self.write_group(group)?),
+            None => None,
+        };
         let alph_amount_from_index = self.buffer.get_index();
         let alph_amount_to_index = self.write_alph_amount(&output.amount)?;
+        let lock_time = if output.lock_time.0 == 0 {
+            None
+        } else {
+            Some(self.write_lock_time(&output.lock_time)?)
+        };
+        let additional_data = if output.additional_data().is_empty() {
+            None
+        } else {
+            Some(self.write_additional_data(output)?)
+        };
 
         let output_indexes = OutputIndexes {
             review_message: (review_message_from_index, review_message_to_index),
             alph_amount: (alph_amount_from_index, alph_amount_to_index),
             address: (address_from_index, address_to_index),
-            token: None,
+            address_reference,
+            checksum_words: (checksum_words_from_index, checksum_words_to_index),
+            fingerprint,
+            is_schnorr_address,
+            group,
+            is_cross_group,
+            multi_sig_policy,
+            lock_time,
+            additional_data,
+            tokens: [None; MAX_OUTPUT_TOKENS],
         };
-        if output.tokens.is_empty() {
-            return Ok(Some(output_indexes));
+        let mut tokens = [None; MAX_OUTPUT_TOKENS];
+        for (token_indexes, token) in tokens.iter_mut().zip(output.get_tokens()) {
+            *token_indexes = Some(self.prepare_token(token)?);
         }
-
-        // Asset output has at most one token
-        let token = output.tokens.get_current_item().unwrap();
-        let token_indexes = self.prepare_token(token)?;
         Ok(Some(OutputIndexes {
-            token: Some(token_indexes),
+            tokens,
             ..output_indexes
         }))
     }
@@ -358,14 +877,31 @@ impl TxReviewer {
     // Prepare the token for review
     fn prepare_token(&mut self, token: &Token) -> Result<TokenIndexes, ErrorCode> {
         let token_id_from_index = self.buffer.get_index();
-        let token_id_to_index = self.write_token_id(&token.id)?;
-        match self.get_token_metadata(&token.id) {
+        let nft = self.nft_collections.get(&token.id.0);
+        let is_nft = nft.is_some();
+        let is_single_item = is_nft && token.amount.to_u128() == Some(1);
+        let token_metadata = self.get_token_metadata(&token.id);
+        // Tokens of the verified token list are displayed by their symbol instead of their id
+        let token_id_to_index = match (nft, token_metadata) {
+            (Some(nft), _) => {
+                let mut output = [0u8; 160];
+                let bytes = write_nft(nft, &mut output)?;
+                self.buffer.write(bytes)?
+            }
+            (None, Some((token_symbol, _))) if token_symbol[0] != 0 => self
+                .buffer
+                .write(get_token_symbol_bytes(&token_symbol[..]))?,
+            (None, _) => self.write_token_id(&token.id)?,
+        };
+        match token_metadata {
             Some((token_symbol, token_decimals)) => {
                 let token_amount_from_index = self.buffer.get_index();
                 let token_amount_to_index =
                     self.write_token_amount(&token.amount, token_symbol, token_decimals as usize)?;
                 Ok(TokenIndexes {
                     has_token_metadata: true,
+                    is_nft,
+                    is_single_item,
                     token_id: (token_id_from_index, token_id_to_index),
                     token_amount: (token_amount_from_index, token_amount_to_index),
                 })
@@ -375,6 +911,8 @@ impl TxReviewer {
                 let token_amount_to_index = self.write_token_raw_amount(&token.amount)?;
                 Ok(TokenIndexes {
                     has_token_metadata: false,
+                    is_nft,
+                    is_single_item,
                     token_id: (token_id_from_index, token_id_to_index),
                     token_amount: (token_amount_from_index, token_amount_to_index),
                 })
@@ -387,32 +925,504 @@ impl TxReviewer {
         bytes_to_string(bytes)
     }
 
+    // Start the review once, on the first displayed input or script detail, or before approving
+    fn start_review(&mut self) -> Result<(), ErrorCode> {
+        if !self.is_review_started {
+            self.inner.start_review()?;
+            self.is_review_started = true;
+            self.review_testnet_key()?;
+            self.review_chained_tx()?;
+        }
+        Ok(())
+    }
+
+    // The txs of a chained session are reviewed one by one, e.g. `Transaction 1 of 3`
+    fn review_chained_tx(&self) -> Result<(), ErrorCode> {
+        match self.inner.chained_tx() {
+            Some((index, count)) => {
+                let position = chained_tx_position(index, count);
+                let fields = [Field {
+                    name: "Transaction",
+                    value: bytes_to_string(&position)?,
+                }];
+                self.inner.review_fields(&fields, "Chained tx")
+            }
+            None => Ok(()),
+        }
+    }
+
+    // Keys with the testnet coin type are flagged at the start and at the end of the review
+    fn review_testnet_key(&self) -> Result<(), ErrorCode> {
+        if self.is_testnet_key {
+            self.inner
+                .review_fields(&[testnet_key_field()], TESTNET_KEY_WARNING)
+        } else {
+            Ok(())
+        }
+    }
+
+    // Show the section header page if the review of a script tx enters a new section
+    fn enter_section(&mut self, section: ReviewSection) -> Result<(), ErrorCode> {
+        if !self.inner.is_tx_execute_script() || self.current_section == Some(section) {
+            return Ok(());
+        }
+        self.current_section = Some(section);
+        self.inner
+            .review_section(section.title(), section.description())
+    }
+
+    // Simple transfers start the review with the amount and the recipient of the first reviewed output.
+    // The later outputs are not decoded yet, so a transfer is only simple if the first reviewed output
+    // is the last output of the tx, i.e. all the other outputs are change outputs
+    fn start_transfer_review(&mut self, output_indexes: &OutputIndexes) -> Result<(), ErrorCode> {
+        if self.is_review_started {
+            return Ok(());
+        }
+        if self.inner.is_tx_execute_script() || !self.is_last_output {
+            return self.start_review();
+        }
+        // The title only shows the token amount if the output sends a single token
+        let amount_range = match &output_indexes.tokens {
+            [Some(token), None, ..] if token.has_token_metadata => token.token_amount,
+            _ => output_indexes.alph_amount,
+        };
+        let mut title = [0u8; TRANSFER_TITLE_PREFIX.len() + MAX_TOKEN_AMOUNT_LENGTH];
+        let amount = self.buffer.read(amount_range.0, amount_range.1);
+        let title_length = TRANSFER_TITLE_PREFIX.len() + amount.len();
+        if title_length > title.len() {
+            return Err(ErrorCode::Overflow);
+        }
+        title[..TRANSFER_TITLE_PREFIX.len()].copy_from_slice(TRANSFER_TITLE_PREFIX);
+        title[TRANSFER_TITLE_PREFIX.len()..title_length].copy_from_slice(amount);
+        let title_from_index = self.buffer.get_index();
+        let title_to_index = self.buffer.write(&title[..title_length])?;
+
+        let title = self.get_str_from_range((title_from_index, title_to_index))?;
+        let recipient = self.get_str_from_range(output_indexes.address)?;
+        self.inner.start_transfer_review(title, recipient)?;
+        self.is_review_started = true;
+        self.review_testnet_key()?;
+        self.review_chained_tx()
+    }
+
+    // Write the indexes of the revealed public keys, the device key is marked if present
+    fn write_multi_sig_key_indexes(
+        &mut self,
+        temp_data: &[u8],
+        device_addresses: &DeviceAddresses,
+    ) -> Result<usize, ErrorCode> {
+        let mut to_index = self.buffer.get_index();
+        for (i, key) in temp_data
+            .chunks(PublicKeyWithIndex::TEMP_DATA_SIZE)
+            .enumerate()
+        {
+            let public_key = &key[..(PublicKeyWithIndex::TEMP_DATA_SIZE - 2)];
+            let key_index = u16::from_be_bytes([key[key.len() - 2], key[key.len() - 1]]);
+            let prefix: &[u8] = if i == 0 { b"#" } else { b", #" };
+            to_index = self.write_index_with_prefix(key_index as usize, prefix)?;
+
+            let mut address_bytes = [0u8; 46];
+            let public_key_hash = Blake2bHasher::hash(public_key)?;
+            let address = to_base58_address(0u8, &public_key_hash, &mut address_bytes)?;
+            if device_addresses.contains(address) {
+                to_index = self.buffer.write(b" (this device)")?;
+            }
+        }
+        Ok(to_index)
+    }
+
+    // Review the public keys revealed by a multi-sig input
+    fn review_multi_sig_input(
+        &mut self,
+        temp_data: &[u8],
+        device_addresses: &DeviceAddresses,
+    ) -> Result<(), ErrorCode> {
+        if temp_data.is_empty() || temp_data.len() % PublicKeyWithIndex::TEMP_DATA_SIZE != 0 {
+            return Err(ErrorCode::InternalError);
+        }
+        let key_size = temp_data.len() / PublicKeyWithIndex::TEMP_DATA_SIZE;
+        let key_size_from_index = self.buffer.get_index();
+        let key_size_to_index = self.write_index_with_prefix(key_size, b"")?;
+        let key_indexes_from_index = self.buffer.get_index();
+        let key_indexes_to_index = self.write_multi_sig_key_indexes(temp_data, device_addresses)?;
+
+        self.start_review()?;
+        let key_size = self.get_str_from_range((key_size_from_index, key_size_to_index))?;
+        let key_indexes =
+            self.get_str_from_range((key_indexes_from_index, key_indexes_to_index))?;
+        let fields = &[
+            Field {
+                name: "Signing Keys",
+                value: key_size,
+            },
+            Field {
+                name: "Key Indexes",
+                value: key_indexes,
+            },
+        ];
+        self.inner.review_fields(fields, "Multi-sig Input")
+    }
+
+    // Review the signing keys of a groupless multi-sig input, the input only reveals the indexes
+    // of the signing keys among the public keys of the wallet
+    fn review_groupless_multi_sig_input(
+        &mut self,
+        key_size: usize,
+        temp_data: &[u8],
+    ) -> Result<(), ErrorCode> {
+        if temp_data.is_empty() || temp_data.len() % SigningKeyIndex::TEMP_DATA_SIZE != 0 {
+            return Err(ErrorCode::InternalError);
+        }
+        let signing_key_size = temp_data.len() / SigningKeyIndex::TEMP_DATA_SIZE;
+        let policy = self.write_multi_sig_policy(signing_key_size, key_size)?;
+        let key_indexes_from_index = self.buffer.get_index();
+        let mut key_indexes_to_index = key_indexes_from_index;
+        for (i, index) in temp_data
+            .chunks(SigningKeyIndex::TEMP_DATA_SIZE)
+            .enumerate()
+        {
+            let prefix: &[u8] = if i == 0 { b"#" } else { b", #" };
+            let key_index = u16::from_be_bytes([index[0], index[1]]);
+            key_indexes_to_index = self.write_index_with_prefix(key_index as usize, prefix)?;
+        }
+
+        self.start_review()?;
+        let policy = self.get_str_from_range(policy)?;
+        let key_indexes =
+            self.get_str_from_range((key_indexes_from_index, key_indexes_to_index))?;
+        let fields = &[
+            Field {
+                name: "Signing Keys",
+                value: policy,
+            },
+            Field {
+                name: "Key Indexes",
+                value: key_indexes,
+            },
+        ];
+        self.inner.review_fields(fields, "Multi-sig Input")
+    }
+
+    // Review the output ref spent by an input in expert mode, auditors can match it with the coordinator
+    fn review_input_ref(&mut self, input: &TxInput, current_index: usize) -> Result<(), ErrorCode> {
+        let review_message_from_index = self.buffer.get_index();
+        let review_message_to_index =
+            self.write_index_with_prefix(current_index + 1, b"Input #")?;
+        let output_ref_from_index = self.buffer.get_index();
+        let output_ref_hex: [u8; 64] = to_hex_fixed(&input.key.0);
+        let output_ref_to_index = self.buffer.write(&output_ref_hex)?;
+
+        self.start_review()?;
+        let review_message =
+            self.get_str_from_range((review_message_from_index, review_message_to_index))?;
+        let output_ref = self.get_str_from_range((output_ref_from_index, output_ref_to_index))?;
+        let fields = [
+            Field {
+                name: "Transaction Input",
+                value: review_message,
+            },
+            Field {
+                name: "Output Ref",
+                value: output_ref,
+            },
+        ];
+        let fields = if self.inner.output_index_as_field() {
+            &fields[..]
+        } else {
+            &fields[1..]
+        };
+        self.inner.review_fields(fields, review_message)
+    }
+
+    // The inputs of a sweep that spend from several device addresses are summarized in a single page
+    fn review_device_inputs(&mut self) -> Result<(), ErrorCode> {
+        let address_count = self.input_address_bits.count_ones() as usize;
+        if address_count < 2 {
+            return Ok(());
+        }
+        let from_index = self.buffer.get_index();
+        self.buffer.write(b"From your device (")?;
+        self.write_index_with_prefix(address_count, b"")?;
+        let to_index = self.buffer.write(b" addresses)")?;
+        self.start_review()?;
+        let fields = &[Field {
+            name: "Inputs",
+            value: self.get_str_from_range((from_index, to_index))?,
+        }];
+        let result = self.inner.review_fields(fields, "Inputs");
+        self.reset_buffer(self.token_metadata_length);
+        result
+    }
+
+    // Review the contracts and the methods called by the script.
+    // The calls are not displayed if any of the called contracts is not a constant.
+    fn review_contract_calls(&mut self, contract_calls: &ContractCalls) -> Result<(), ErrorCode> {
+        if contract_calls.is_empty() || !contract_calls.is_determinable() {
+            return Ok(());
+        }
+        self.start_review()?;
+        self.enter_section(ReviewSection::ContractInteraction)?;
+        for call in contract_calls.get_calls() {
+            let result = self.review_contract_call(call);
+            self.reset_buffer(self.token_metadata_length);
+            result?;
+        }
+        Ok(())
+    }
+
+    fn review_contract_call(&mut self, call: &ContractCall) -> Result<(), ErrorCode> {
+        let contract_from_index = self.buffer.get_index();
+        let contract_to_index = self.write_address(P2C_PREFIX, &call.contract_id)?;
+        let method_from_index = self.buffer.get_index();
+        let method_to_index = match call.method {
+            CallMethod::Index(index) => self.write_index_with_prefix(index as usize, b"#")?,
+            CallMethod::Selector(selector) => {
+                let selector_hex: [u8; 8] = to_hex_fixed(&selector.to_be_bytes());
+                self.buffer.write(b"Selector 0x")?;
+                self.buffer.write(&selector_hex)?
+            }
+        };
+        let mut arg_ranges = [(0, 0); MAX_CALL_ARGS];
+        let args = call.args().unwrap_or(&[]);
+        for (arg, range) in args.iter().zip(arg_ranges.iter_mut()) {
+            let from_index = self.buffer.get_index();
+            let to_index = self.write_call_arg(arg)?;
+            *range = (from_index, to_index);
+        }
+        let args_length_from_index = self.buffer.get_index();
+        let args_length_to_index = self.write_index_with_prefix(call.args_length(), b"")?;
+
+        let mut fields: [Field; MAX_CALL_ARGS + 3] = core::array::from_fn(|_| Field {
+            name: "",
+            value: "",
+        });
+        fields[0] = Field {
+            name: "Contract",
+            value: self.get_str_from_range((contract_from_index, contract_to_index))?,
+        };
+        fields[1] = Field {
+            name: "Method",
+            value: self.get_str_from_range((method_from_index, method_to_index))?,
+        };
+        let mut size = 2;
+        if call.args().is_none() {
+            // Only the number of args is displayed if there are too many args
+            fields[size] = Field {
+                name: "Arguments",
+                value: self.get_str_from_range((args_length_from_index, args_length_to_index))?,
+            };
+            size += 1;
+        }
+        for (name, range) in CALL_ARG_NAMES
+            .iter()
+            .zip(arg_ranges.iter())
+            .take(args.len())
+        {
+            fields[size] = Field {
+                name,
+                value: self.get_str_from_range(*range)?,
+            };
+            size += 1;
+        }
+        self.inner.review_fields(&fields[..size], "Contract Call")
+    }
+
+    // Write an argument of a contract call, the args that are not constants are displayed as unknown
+    fn write_call_arg(&mut self, arg: &Const) -> Result<usize, ErrorCode> {
+        match arg {
+            Const::Unknown => self.buffer.write(b"Unknown"),
+            Const::Bool(true) => self.buffer.write(b"true"),
+            Const::Bool(false) => self.buffer.write(b"false"),
+            Const::U256(value) => {
+                let mut output = [0u8; 78]; // u256 max
+                let value_str = value.to_str(&mut output).ok_or(ErrorCode::Overflow)?;
+                self.buffer.write(value_str)
+            }
+            Const::Bytes32(bytes) => {
+                let hex: [u8; 64] = to_hex_fixed(bytes);
+                self.buffer.write(&hex)
+            }
+        }
+    }
+
+    // Review the assets that the script is allowed to spend from the user.
+    // We fall back to the blind signing warning if the approved assets cannot be determined.
+    fn review_approved_assets(
+        &mut self,
+        approved_assets: &ApprovedAssets,
+        contract_calls: &ContractCalls,
+    ) -> Result<(), ErrorCode> {
+        if approved_assets.is_empty() || !approved_assets.is_determinable() {
+            return Ok(());
+        }
+        self.start_review()?;
+        self.enter_section(ReviewSection::ContractInteraction)?;
+        for (index, asset) in approved_assets.get_assets().iter().enumerate() {
+            // The contract is only known if all the contract calls are determinable
+            let contract_id = if contract_calls.is_determinable() {
+                contract_calls
+                    .get_calls()
+                    .get(approved_assets.get_call_index(index))
+                    .map(|call| &call.contract_id)
+            } else {
+                None
+            };
+            let result = self.review_approved_asset(asset, contract_id);
+            self.reset_buffer(self.token_metadata_length);
+            result?;
+        }
+        Ok(())
+    }
+
+    // The approved assets can be spent by the contract, so they are displayed along with it
+    fn review_approved_asset(
+        &mut self,
+        asset: &Token,
+        contract_id: Option<&[u8; 32]>,
+    ) -> Result<(), ErrorCode> {
+        let contract = match contract_id {
+            Some(contract_id) => {
+                let from_index = self.buffer.get_index();
+                let to_index = self.write_address(P2C_PREFIX, contract_id)?;
+                Some((from_index, to_index))
+            }
+            None => None,
+        };
+        let mut fields: [Field; 3] = core::array::from_fn(|_| Field {
+            name: "",
+            value: "",
+        });
+        let mut size = 0;
+        let review_message = if asset.id.0 == ALPH_TOKEN_ID {
+            let amount_from_index = self.buffer.get_index();
+            let amount_to_index = self.write_alph_amount(&asset.amount)?;
+            fields[size] = Field {
+                name: "Approved Amount",
+                value: self.get_str_from_range((amount_from_index, amount_to_index))?,
+            };
+            size += 1;
+            "Approved ALPH"
+        } else {
+            let TokenIndexes {
+                has_token_metadata,
+                is_nft,
+                token_id,
+                token_amount,
+                ..
+            } = self.prepare_token(asset)?;
+            if !has_token_metadata && !is_nft {
+                self.review_unknown_token(&asset.amount)?;
+            }
+            fields[size] = Field {
+                name: token_id_name(is_nft, has_token_metadata),
+                value: self.get_str_from_range(token_id)?,
+            };
+            fields[size + 1] = Field {
+                name: if has_token_metadata {
+                    "Approved Amount"
+                } else {
+                    "Approved Raw Amount"
+                },
+                value: self.get_str_from_range(token_amount)?,
+            };
+            size += 2;
+            "Approved Token"
+        };
+        if let Some(range) = contract {
+            fields[size] = Field {
+                name: "To Contract",
+                value: self.get_str_from_range(range)?,
+            };
+            size += 1;
+        }
+        self.inner.review_fields(&fields[..size], review_message)
+    }
+
     // Review the input for the transaction
     pub fn review_input(
         &mut self,
         input: &TxInput,
         current_index: usize,
         input_size: usize,
-        device_address: &Address,
+        device_addresses: &DeviceAddresses,
+        temp_data: &[u8],
     ) -> Result<(), ErrorCode> {
         assert!(current_index < input_size);
+        if is_expert_mode_enabled() {
+            let result = self.review_input_ref(input, current_index);
+            self.reset_buffer(self.token_metadata_length);
+            result?
+        }
         match &input.unlock_script {
             UnlockScript::P2PKH(public_key) => {
                 let mut address_bytes = [0u8; 46];
-                let public_key_hash = Blake2bHasher::hash(&public_key.0)?;
+                let public_key_hash = self
+                    .public_key_hash_cache
+                    .get_or_insert_with(&public_key.0, Blake2bHasher::hash)?;
+                if self.input_group.is_none() {
+                    self.input_group = Some(get_script_group(&public_key_hash, GROUP_NUM));
+                }
                 let address = to_base58_address(0u8, &public_key_hash, &mut address_bytes)?;
-                if !self.has_external_inputs {
-                    self.has_external_inputs = !device_address.eq(address)
+                match device_addresses.index_of(address) {
+                    Some(index) => self.input_address_bits |= 1 << index,
+                    None if self.input_addresses.contains(address) => {
+                        self.has_input_path_inputs = true
+                    }
+                    None => self.has_external_inputs = true,
                 }
             }
-            UnlockScript::P2MPKH(_) => self.has_external_inputs = true,
-            UnlockScript::P2SH(_) => self.has_external_inputs = true,
+            UnlockScript::P2MPKH(_) => {
+                self.has_external_inputs = true;
+                let result = self.review_multi_sig_input(temp_data, device_addresses);
+                self.reset_buffer(self.token_metadata_length);
+                result?
+            }
+            UnlockScript::P2SH(_) => self.review_p2sh_input(device_addresses, temp_data)?,
+            // The public key is not in the input, so the device cannot tell if it is its key
+            UnlockScript::P2PK => self.has_external_inputs = true,
+            UnlockScript::P2HMPK(p2hmpk) => {
+                self.has_external_inputs = true;
+                let result =
+                    self.review_groupless_multi_sig_input(p2hmpk.inner.key_size(), temp_data);
+                self.reset_buffer(self.token_metadata_length);
+                result?
+            }
             UnlockScript::SameAsPrevious => (),
-            _ => panic!(),
+            UnlockScript::Unknown => return Err(ErrorCode::UnsupportedUnlockScript),
         };
 
-        if (current_index == input_size - 1) && self.has_external_inputs {
-            self.inner.warning_external_inputs()?;
+        if current_index == input_size - 1 {
+            self.input_count = input_size;
+            self.review_device_inputs()?;
+            if self.has_external_inputs {
+                self.inner.warning_external_inputs()?;
+            }
+        }
+        Ok(())
+    }
+
+    // The script of a P2SH input is kept in temp data, a Schnorr input is spent from a device
+    // address if the address of its script is one of the device addresses
+    fn review_p2sh_input(
+        &mut self,
+        device_addresses: &DeviceAddresses,
+        temp_data: &[u8],
+    ) -> Result<(), ErrorCode> {
+        if !is_schnorr_script(temp_data) {
+            self.has_external_inputs = true;
+            return Ok(());
+        }
+        let script_hash = Blake2bHasher::hash(temp_data)?;
+        if self.input_group.is_none() {
+            self.input_group = Some(get_script_group(&script_hash, GROUP_NUM));
+        }
+        self.schnorr_script_hash.get_or_insert(script_hash);
+        let mut address_bytes = [0u8; 46];
+        let address = to_base58_address(P2SH_PREFIX, &script_hash, &mut address_bytes)?;
+        match device_addresses.index_of(address) {
+            Some(index) => self.input_address_bits |= 1 << index,
+            None if self.input_addresses.contains(address) => self.has_input_path_inputs = true,
+            None => self.has_external_inputs = true,
         }
         Ok(())
     }
@@ -421,94 +1431,347 @@ impl TxReviewer {
     pub fn review_output(
         &mut self,
         output: &AssetOutput,
-        device_address: &Address,
+        device_addresses: &DeviceAddresses,
         temp_data: &[u8],
     ) -> Result<(), ErrorCode> {
-        let output_indexes_opt = self.prepare_output(output, device_address, temp_data)?;
-        if output_indexes_opt.is_none() {
-            return Ok(());
+        let output_indexes = match self.prepare_output(output, device_addresses, temp_data)? {
+            Some(output_indexes) => output_indexes,
+            None => return Ok(()),
+        };
+        // The raw amounts and the lockup script type are displayed in expert mode
+        let expert_indexes = if is_expert_mode_enabled() {
+            Some(self.write_expert_output_details(output, &output_indexes.tokens)?)
+        } else {
+            None
+        };
+        // Change outputs to the device addresses are not included in the total
+        self.total_sent = self
+            .total_sent
+            .add(&output.amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.has_token_transfers |= !output.tokens.is_empty();
+        for token in output.get_tokens() {
+            self.token_totals.add(token).ok_or(ErrorCode::Overflow)?;
+        }
+        // The outputs identical to the last displayed output are displayed together once
+        // the run of identical outputs ends
+        match (self.last_output, output_indexes.fingerprint) {
+            (Some((last_fingerprint, _)), Some(fingerprint)) if last_fingerprint == fingerprint => {
+                self.repeated_output_count += 1;
+                self.repeated_output_amount = self
+                    .repeated_output_amount
+                    .add(&output.amount)
+                    .ok_or(ErrorCode::Overflow)?;
+                return Ok(());
+            }
+            _ => self.review_repeated_outputs()?,
         }
+        self.last_output = output_indexes
+            .fingerprint
+            .map(|fingerprint| (fingerprint, self.next_output_index - 1));
+        if output_indexes.is_cross_group {
+            self.review_cross_group()?;
+        }
+        for (token_indexes, token) in output_indexes.tokens.iter().zip(output.get_tokens()) {
+            let is_unknown_token = token_indexes.map_or(false, |indexes| {
+                !indexes.has_token_metadata && !indexes.is_nft
+            });
+            if is_unknown_token {
+                self.review_unknown_token(&token.amount)?;
+            }
+        }
+        self.start_transfer_review(&output_indexes)?;
+        self.enter_section(ReviewSection::AssetTransfers)?;
         let OutputIndexes {
             review_message,
             alph_amount,
             address,
-            token,
-        } = output_indexes_opt.unwrap();
+            address_reference,
+            checksum_words,
+            fingerprint: _,
+            is_schnorr_address,
+            group,
+            is_cross_group: _,
+            multi_sig_policy,
+            lock_time,
+            additional_data,
+            tokens,
+        } = output_indexes;
         let review_message = self.get_str_from_range(review_message)?;
-        let alph_amount = self.get_str_from_range(alph_amount)?;
-        let address = self.get_str_from_range(address)?;
-        let address_field = Field {
-            name: "To",
-            value: address,
-        };
-        let alph_amount_field = Field {
+        let mut fields: [Field; MAX_OUTPUT_FIELDS] = core::array::from_fn(|_| Field {
+            name: "",
+            value: "",
+        });
+        let mut size = 0;
+        if self.inner.output_index_as_field() {
+            fields[size] = Field {
+                name: "Transaction Output",
+                value: review_message,
+            };
+            size += 1;
+        }
+        if is_dust_amount(&output.amount) {
+            fields[size] = Field {
+                name: "Warning",
+                value: DUST_WARNING,
+            };
+            size += 1;
+        }
+        for &TokenIndexes {
+            has_token_metadata,
+            is_nft,
+            is_single_item,
+            token_id,
+            token_amount,
+        } in tokens.iter().flatten()
+        {
+            fields[size] = Field {
+                name: token_id_name(is_nft, has_token_metadata),
+                value: self.get_str_from_range(token_id)?,
+            };
+            size += 1;
+            // The amount of a single NFT item is always 1, so only the item is displayed
+            if !is_single_item {
+                fields[size] = Field {
+                    name: if has_token_metadata {
+                        "Token Amount"
+                    } else {
+                        "Raw Token Amount"
+                    },
+                    value: self.get_str_from_range(token_amount)?,
+                };
+                size += 1;
+            }
+        }
+        fields[size] = Field {
             name: "Amount",
-            value: alph_amount,
+            value: self.get_str_from_range(alph_amount)?,
         };
-        let output_index_field = Field {
-            name: "Transaction Output",
-            value: review_message,
-        };
-        if token.is_none() {
-            let all_fields = &[output_index_field, alph_amount_field, address_field];
-            let fields = if self.inner.output_index_as_field() {
-                all_fields
+        size += 1;
+        if let Some(range) = address_reference {
+            fields[size] = Field {
+                name: "To",
+                value: self.get_str_from_range(range)?,
+            };
+            size += 1;
+        }
+        fields[size] = Field {
+            name: if address_reference.is_some() {
+                "Full Address"
             } else {
-                &all_fields[1..]
+                "To"
+            },
+            value: self.get_str_from_range(address)?,
+        };
+        fields[size + 1] = Field {
+            name: "Checksum Words",
+            value: self.get_str_from_range(checksum_words)?,
+        };
+        size += 2;
+        if is_schnorr_address {
+            fields[size] = Field {
+                name: "Address Type",
+                value: "Schnorr address",
+            };
+            size += 1;
+        }
+        if let Some(range) = group {
+            fields[size] = Field {
+                name: "Group",
+                value: self.get_str_from_range(range)?,
+            };
+            size += 1;
+        }
+        // The number of signatures required by a multi-sig address
+        if let Some(range) = multi_sig_policy {
+            fields[size] = Field {
+                name: "Signatures Required",
+                value: self.get_str_from_range(range)?,
             };
-            return self.inner.review_fields(fields, review_message);
+            size += 1;
         }
+        // The device has no clock, so the lock time is displayed as is
+        if let Some(range) = lock_time {
+            fields[size] = Field {
+                name: "Locked Until",
+                value: self.get_str_from_range(range)?,
+            };
+            size += 1;
+        }
+        if let Some(range) = additional_data {
+            fields[size] = Field {
+                name: "Additional Data",
+                value: self.get_str_from_range(range)?,
+            };
+            size += 1;
+        }
+        if let Some((script_type, raw_amount, raw_token_amounts)) = expert_indexes {
+            fields[size] = Field {
+                name: "Script Type",
+                value: self.get_str_from_range(script_type)?,
+            };
+            fields[size + 1] = Field {
+                name: "Raw Amount",
+                value: self.get_str_from_range(raw_amount)?,
+            };
+            size += 2;
+            for range in raw_token_amounts.into_iter().flatten() {
+                fields[size] = Field {
+                    name: "Raw Token Amount",
+                    value: self.get_str_from_range(range)?,
+                };
+                size += 1;
+            }
+        }
+        self.inner.review_fields(&fields[..size], review_message)
+    }
 
-        let TokenIndexes {
-            has_token_metadata,
-            token_id,
-            token_amount,
-        } = token.unwrap();
-        let token_id = self.get_str_from_range(token_id)?;
-        let token_amount = self.get_str_from_range(token_amount)?;
-        let amount_name = if has_token_metadata {
-            "Token Amount"
-        } else {
-            "Raw Token Amount"
+    // A cross-group transfer takes longer to confirm, the user approves a warning before the
+    // first output to another group unless the warning is disabled in the settings
+    fn review_cross_group(&mut self) -> Result<(), ErrorCode> {
+        if self.is_cross_group_approved || is_cross_group_warning_disabled() {
+            return Ok(());
+        }
+        self.inner.warning_cross_group()?;
+        self.is_cross_group_approved = true;
+        Ok(())
+    }
+
+    // The raw amount of a token without metadata is displayed without decimals, so the user
+    // approves a warning before the first one
+    fn review_unknown_token(&mut self, amount: &U256) -> Result<(), ErrorCode> {
+        if self.is_unknown_token_approved {
+            return Ok(());
+        }
+        let is_large_amount = *amount >= U256::encode_u128(LARGE_RAW_AMOUNT);
+        if !is_unknown_token_warning_enabled() && !is_large_amount {
+            return Ok(());
+        }
+        self.inner.warning_unknown_token()?;
+        self.is_unknown_token_approved = true;
+        Ok(())
+    }
+
+    // Review the outputs that are identical to the last displayed output
+    fn review_repeated_outputs(&mut self) -> Result<(), ErrorCode> {
+        let count = self.repeated_output_count;
+        let output_index = match self.last_output {
+            Some((_, output_index)) if count != 0 => output_index,
+            _ => return Ok(()),
         };
-        let token_id_field = Field {
-            name: "Token ID",
-            value: token_id,
+        let review_message_from_index = self.buffer.get_index();
+        self.write_index_with_prefix(output_index as usize + 1, b"Outputs #")?;
+        let review_message_to_index =
+            self.write_index_with_prefix((output_index + count) as usize, b" to #")?;
+        let same_as_from_index = self.buffer.get_index();
+        let same_as_to_index = self.write_index_with_prefix(output_index as usize, b"Output #")?;
+        let count_from_index = self.buffer.get_index();
+        let count_to_index = self.write_index_with_prefix(count as usize, b"")?;
+        let amount_from_index = self.buffer.get_index();
+        let amount = core::mem::take(&mut self.repeated_output_amount);
+        let amount_to_index = self.write_alph_amount(&amount)?;
+        self.repeated_output_count = 0;
+
+        let review_message =
+            self.get_str_from_range((review_message_from_index, review_message_to_index))?;
+        let mut fields: [Field; 4] = core::array::from_fn(|_| Field {
+            name: "",
+            value: "",
+        });
+        let mut size = 0;
+        if self.inner.output_index_as_field() {
+            fields[size] = Field {
+                name: "Transaction Outputs",
+                value: review_message,
+            };
+            size += 1;
+        }
+        fields[size] = Field {
+            name: "Same As",
+            value: self.get_str_from_range((same_as_from_index, same_as_to_index))?,
         };
-        let token_amount_field = Field {
-            name: amount_name,
-            value: token_amount,
+        fields[size + 1] = Field {
+            name: "Count",
+            value: self.get_str_from_range((count_from_index, count_to_index))?,
         };
-        let all_fields = &[
-            output_index_field,
-            token_id_field,
-            token_amount_field,
-            alph_amount_field,
-            address_field,
-        ];
-        let fields: &[Field] = if self.inner.output_index_as_field() {
-            all_fields
-        } else {
-            &all_fields[1..]
+        fields[size + 2] = Field {
+            name: "Total Amount",
+            value: self.get_str_from_range((amount_from_index, amount_to_index))?,
         };
-        self.inner.review_fields(fields, review_message)
+        size += 3;
+        self.inner.review_fields(&fields[..size], review_message)
+    }
+
+    // Write the lockup script type, the raw ALPH amount and the raw amounts of the tokens
+    // whose amounts are displayed with their metadata
+    fn write_expert_output_details(
+        &mut self,
+        output: &AssetOutput,
+        tokens: &[Option<TokenIndexes>; MAX_OUTPUT_TOKENS],
+    ) -> Result<ExpertOutputIndexes, ErrorCode> {
+        let script_type_from_index = self.buffer.get_index();
+        let script_type_hex: [u8; 2] = to_hex_fixed(&[output.lockup_script.get_type()]);
+        self.buffer.write(b"0x")?;
+        let script_type_to_index = self.buffer.write(&script_type_hex)?;
+        let raw_amount_from_index = self.buffer.get_index();
+        let raw_amount_to_index = self.write_token_raw_amount(&output.amount)?;
+        let mut raw_token_amounts = [None; MAX_OUTPUT_TOKENS];
+        for ((range, token_indexes), token) in raw_token_amounts
+            .iter_mut()
+            .zip(tokens)
+            .zip(output.get_tokens())
+        {
+            match token_indexes {
+                Some(token_indexes)
+                    if token_indexes.has_token_metadata && !token_indexes.is_single_item =>
+                {
+                    let from_index = self.buffer.get_index();
+                    let to_index = self.write_token_raw_amount(&token.amount)?;
+                    *range = Some((from_index, to_index));
+                }
+                _ => (),
+            }
+        }
+        Ok((
+            (script_type_from_index, script_type_to_index),
+            (raw_amount_from_index, raw_amount_to_index),
+            raw_token_amounts,
+        ))
     }
 
     // Review the transaction details
     pub fn review_tx_details(
         &mut self,
         unsigned_tx: &UnsignedTx,
-        device_address: &Address,
+        device_addresses: &DeviceAddresses,
         temp_data: &SwappingBuffer<'static, RAM_SIZE, NVM_DATA_SIZE>,
     ) -> Result<(), ErrorCode> {
         match unsigned_tx {
-            UnsignedTx::NetworkId(_) => Ok(()),
-            UnsignedTx::TxFee(tx_fee) => {
-                let fee = tx_fee.inner.get();
-                if fee.is_none() {
-                    return Err(ErrorCode::Overflow);
+            UnsignedTx::Version(version) => {
+                if !is_supported_tx_version(version.0) {
+                    return Err(ErrorCode::UnsupportedTxVersion);
+                }
+                Ok(())
+            }
+            UnsignedTx::NetworkId(network_id) => match network_id.0 {
+                MAINNET_NETWORK_ID => Ok(()),
+                _ if is_mainnet_only_enabled() => Err(ErrorCode::MainnetOnly),
+                TESTNET_NETWORK_ID => self.inner.warning_network(TESTNET_WARNING),
+                _ => self.inner.warning_network(DEVNET_WARNING),
+            },
+            UnsignedTx::ScriptOpt(script_opt) => match &script_opt.inner {
+                Some(script) => {
+                    self.review_contract_calls(script.contract_calls())?;
+                    self.review_approved_assets(script.approved_assets(), script.contract_calls())
                 }
-                self.tx_fee = Some(fee.as_ref().unwrap().clone());
+                None => Ok(()),
+            },
+            UnsignedTx::TxFee(tx_fee) => {
+                // The fee is the product of the gas amount and the gas price
+                let fee = tx_fee.inner.get().ok_or(ErrorCode::MalformedField)?;
+                self.tx_fee = Some(fee);
+                self.gas_amount = I32::from(tx_fee.inner.gas_amount.inner);
+                self.gas_price = tx_fee.inner.gas_price.clone();
                 Ok(())
             }
             UnsignedTx::Inputs(inputs) => {
@@ -517,7 +1780,8 @@ impl TxReviewer {
                         current_input,
                         inputs.current_index as usize,
                         inputs.size(),
-                        device_address,
+                        device_addresses,
+                        temp_data.read_all(),
                     )
                 } else {
                     Ok(())
@@ -525,11 +1789,10 @@ impl TxReviewer {
             }
             UnsignedTx::FixedOutputs(outputs) => {
                 if let Some(current_output) = outputs.get_current_item() {
-                    if outputs.current_index == 0 {
-                        self.inner.start_review()?;
-                    }
+                    self.output_count = outputs.size();
+                    self.is_last_output = (outputs.current_index as usize) + 1 == outputs.size();
                     let result =
-                        self.review_output(current_output, device_address, temp_data.read_all());
+                        self.review_output(current_output, device_addresses, temp_data.read_all());
                     self.reset_buffer(self.token_metadata_length);
                     result
                 } else {
@@ -540,29 +1803,251 @@ impl TxReviewer {
         }
     }
 
+    // The estimated outputs are displayed in their own section after all the verified outputs
+    fn review_estimated_outputs(&mut self) -> Result<(), ErrorCode> {
+        if self.estimated_outputs.is_empty() {
+            return Ok(());
+        }
+        self.inner
+            .review_section("Estimated results", "Not verified by device")?;
+        for output in self.estimated_outputs.iter() {
+            let mut address_bytes = [0u8; 46];
+            let address = to_base58_address(
+                output.address_prefix,
+                &output.address_hash,
+                &mut address_bytes,
+            )?;
+            let mut amount_output = [0u8; MAX_ALPH_AMOUNT_LENGTH];
+            let amount = output
+                .amount
+                .to_alph(&mut amount_output, Some(THOUSANDS_SEPARATOR))
+                .ok_or(ErrorCode::Overflow)?;
+            let fields = &[
+                Field {
+                    name: "Unverified Amount",
+                    value: bytes_to_string(amount)?,
+                },
+                Field {
+                    name: "Unverified To",
+                    value: bytes_to_string(address)?,
+                },
+            ];
+            self.inner.review_fields(fields, "Estimated Output")?;
+        }
+        Ok(())
+    }
+
+    // The total of each token is displayed if tokens are sent by more than one output
+    fn review_token_totals(&mut self) -> Result<(), ErrorCode> {
+        if self.token_totals.output_count() < 2 || self.token_totals.is_incomplete() {
+            return Ok(());
+        }
+        let mut token_ranges = [((0, 0), (0, 0)); MAX_TOKEN_TOTALS];
+        let size = self.token_totals.get_totals().len();
+        for index in 0..size {
+            let total = &self.token_totals.get_totals()[index];
+            let token = Token::from(Hash::from_bytes(total.id.0), total.amount.clone());
+            let token_indexes = self.prepare_token(&token)?;
+            token_ranges[index] = (token_indexes.token_id, token_indexes.token_amount);
+        }
+        let mut fields: [Field; MAX_TOKEN_TOTALS * 2] = core::array::from_fn(|_| Field {
+            name: "",
+            value: "",
+        });
+        for (index, (token_id, token_amount)) in token_ranges.iter().take(size).enumerate() {
+            fields[index * 2] = Field {
+                name: "Token",
+                value: self.get_str_from_range(*token_id)?,
+            };
+            fields[index * 2 + 1] = Field {
+                name: "Total Amount",
+                value: self.get_str_from_range(*token_amount)?,
+            };
+        }
+        let result = self
+            .inner
+            .review_fields(&fields[..(size * 2)], "Token Totals");
+        self.reset_buffer(self.token_metadata_length);
+        result
+    }
+
     // Review the rest transaction details and approve it
-    pub fn approve_tx(&mut self) -> Result<(), ErrorCode> {
+    // The total sent is compared with the threshold set by the user on the device
+    fn is_above_amount_threshold(&self) -> bool {
+        match get_amount_threshold() {
+            Some(threshold) => self.total_sent > U256::encode_u128(threshold),
+            None => false,
+        }
+    }
+
+    // All the inputs and the outputs of a consolidation are from and to the device addresses
+    fn is_consolidation(&self) -> bool {
+        self.next_output_index == FIRST_OUTPUT_INDEX
+            && !self.has_external_inputs
+            && !self.inner.is_tx_execute_script()
+            && self.input_count >= MIN_CONSOLIDATION_INPUTS
+            && self.output_count <= MAX_CONSOLIDATION_OUTPUTS
+    }
+
+    pub fn approve_tx(&mut self, tx_id: &[u8; BLAKE2B_HASH_SIZE]) -> Result<(), ErrorCode> {
+        if self.is_unrecognized_format {
+            return self.approve_unrecognized_tx(tx_id);
+        }
         assert!(self.tx_fee.is_some());
-        let mut amount_output = [0u8; 33];
+        let result = self.review_repeated_outputs();
+        self.reset_buffer(self.token_metadata_length);
+        result?;
+        self.start_review()?;
+        self.review_estimated_outputs()?;
+        self.review_token_totals()?;
+        if self.has_high_fees() {
+            self.inner.warning_high_fees()?;
+        }
+        if !self.is_preview() && self.is_above_amount_threshold() {
+            self.inner.confirm_amount_above_threshold()?;
+        }
+        let mut total_sent_output = [0u8; MAX_ALPH_AMOUNT_LENGTH];
+        let mut amount_output = [0u8; MAX_ALPH_AMOUNT_LENGTH];
+        let mut gas_amount_output = [0u8; 11];
+        let mut gas_price_output = [0u8; MAX_ALPH_AMOUNT_LENGTH];
+        let tx_id_hex: [u8; BLAKE2B_HASH_SIZE * 2] = to_hex_fixed(tx_id);
+        let mut fields: [Field; MAX_FINAL_FIELDS] = core::array::from_fn(|_| Field {
+            name: "",
+            value: "",
+        });
+        let mut size = 0;
+        if self.is_testnet_key {
+            fields[size] = testnet_key_field();
+            size += 1;
+        }
+        let chained_tx_output = self
+            .inner
+            .chained_tx()
+            .map(|(index, count)| chained_tx_position(index, count));
+        if let Some(position) = chained_tx_output.as_ref() {
+            fields[size] = Field {
+                name: "Transaction",
+                value: bytes_to_string(position)?,
+            };
+            size += 1;
+        }
+        // A consolidation is reviewed with the number of inputs and the fees only
+        let mut input_count_output = [0u8; 11];
+        if self.is_consolidation() {
+            let input_count = I32::unsafe_from(self.input_count)
+                .to_str(&mut input_count_output)
+                .ok_or(ErrorCode::Overflow)?;
+            fields[size] = Field {
+                name: "Consolidated Inputs",
+                value: bytes_to_string(input_count)?,
+            };
+            size += 1;
+        }
+        // Nothing is sent to other addresses in a self transfer
+        if self.next_output_index != FIRST_OUTPUT_INDEX {
+            let total_sent = self
+                .total_sent
+                .to_alph(&mut total_sent_output, Some(THOUSANDS_SEPARATOR))
+                .ok_or(ErrorCode::Overflow)?;
+            fields[size] = Field {
+                name: "Total Sent",
+                value: bytes_to_string(total_sent)?,
+            };
+            size += 1;
+        }
+        let mut group_output = [0u8; 1];
+        if let Some(group) = self.input_group {
+            group_output[0] = b'0' + group;
+            fields[size] = Field {
+                name: "From Group",
+                value: bytes_to_string(&group_output)?,
+            };
+            size += 1;
+        }
         let amount_str = self
             .tx_fee
             .as_ref()
-            .unwrap()
-            .to_alph(&mut amount_output)
-            .unwrap();
-        let value = bytes_to_string(amount_str)?;
-        let fee_field = Field {
+            .ok_or(ErrorCode::InternalError)?
+            .to_alph(&mut amount_output, Some(THOUSANDS_SEPARATOR))
+            .ok_or(ErrorCode::Overflow)?;
+        fields[size] = Field {
             name: "Fees",
-            value,
+            value: bytes_to_string(amount_str)?,
         };
-        if self.next_output_index == FIRST_OUTPUT_INDEX {
-            return self.inner.review_self_transfer(fee_field);
+        size += 1;
+        // Users can tell an inflated gas price from a large execution in expert mode
+        if is_expert_mode_enabled() {
+            let gas_amount = self
+                .gas_amount
+                .to_str(&mut gas_amount_output)
+                .ok_or(ErrorCode::Overflow)?;
+            let gas_price = self
+                .gas_price
+                .to_alph(&mut gas_price_output, Some(THOUSANDS_SEPARATOR))
+                .ok_or(ErrorCode::Overflow)?;
+            fields[size] = Field {
+                name: "Gas Amount",
+                value: bytes_to_string(gas_amount)?,
+            };
+            fields[size + 1] = Field {
+                name: "Gas Price",
+                value: bytes_to_string(gas_price)?,
+            };
+            size += 2;
+        }
+        // Users can match the tx id with a coordinator or an explorer before approving
+        if is_display_tx_id_enabled() {
+            fields[size] = Field {
+                name: "Tx ID",
+                value: bytes_to_string(&tx_id_hex)?,
+            };
+            size += 1;
         }
+        self.finish_review(&fields[..size])
+    }
 
-        let fields = &[fee_field];
+    fn has_high_fees(&self) -> bool {
+        let fee = match self.tx_fee.as_ref().and_then(|fee| fee.to_u128()) {
+            Some(fee) => fee,
+            None => return true,
+        };
+        if fee > HIGH_FEE_THRESHOLD {
+            return true;
+        }
+        // The ALPH amount of token transfers and contract calls is not the value sent
+        if self.has_token_transfers || self.inner.is_tx_execute_script() {
+            return false;
+        }
+        match self.total_sent.to_u128() {
+            Some(total_sent) if !self.total_sent.is_zero() => {
+                fee.saturating_mul(100) > total_sent.saturating_mul(HIGH_FEE_PERCENTAGE)
+            }
+            _ => false,
+        }
+    }
+
+    fn finish_review<'a>(&mut self, fields: &'a [Field<'a>]) -> Result<(), ErrorCode> {
+        if self.next_output_index == FIRST_OUTPUT_INDEX {
+            return self.inner.review_self_transfer(fields);
+        }
         self.inner.finish_review(fields)
     }
 
+    // The details of a tx of an unrecognized format cannot be displayed, so only the tx id is reviewed
+    fn approve_unrecognized_tx(
+        &mut self,
+        tx_id: &[u8; BLAKE2B_HASH_SIZE],
+    ) -> Result<(), ErrorCode> {
+        self.start_review()?;
+        self.inner.warning_unrecognized_format()?;
+        let tx_id_hex: [u8; BLAKE2B_HASH_SIZE * 2] = to_hex_fixed(tx_id);
+        let fields = [Field {
+            name: "Tx ID",
+            value: bytes_to_string(&tx_id_hex)?,
+        }];
+        self.inner.finish_review(&fields)
+    }
+
     pub fn check_blind_signing(&mut self) -> Result<(), ErrorCode> {
         self.inner.check_blind_signing()
     }
@@ -588,19 +2073,119 @@ pub struct OutputIndexes {
     pub review_message: (usize, usize),
     pub alph_amount: (usize, usize),
     pub address: (usize, usize),
-    pub token: Option<TokenIndexes>,
+    // The reference to the first output that sends to the same address
+    pub address_reference: Option<(usize, usize)>,
+    pub checksum_words: (usize, usize),
+    pub fingerprint: Option<[u8; BLAKE2B_HASH_SIZE]>,
+    pub is_schnorr_address: bool,
+    pub group: Option<(usize, usize)>,
+    pub is_cross_group: bool,
+    pub multi_sig_policy: Option<(usize, usize)>,
+    pub lock_time: Option<(usize, usize)>,
+    pub additional_data: Option<(usize, usize)>,
+    pub tokens: [Option<TokenIndexes>; MAX_OUTPUT_TOKENS],
 }
 
 // Token indexes for review
 // The indexes are used to get the values from the buffer
 // The values are then used to display the token details
 // The token details are then reviewed by the user
+#[derive(Clone, Copy)]
 pub struct TokenIndexes {
     pub has_token_metadata: bool,
+    pub is_nft: bool,
+    pub is_single_item: bool,
     pub token_id: (usize, usize),
     pub token_amount: (usize, usize),
 }
 
+// The hash of the details of an output that are displayed, to find identical consecutive outputs
+fn output_fingerprint(
+    output: &AssetOutput,
+    address_hash: &[u8; BLAKE2B_HASH_SIZE],
+) -> Result<[u8; BLAKE2B_HASH_SIZE], ErrorCode> {
+    let mut hasher = Blake2bHasher::new();
+    hasher.update(address_hash)?;
+    if let LockupScript::P2MPKH(multi_sig) = &output.lockup_script {
+        hasher.update(&multi_sig.inner.m.inner.to_be_bytes())?;
+    }
+    hasher.update(&output.amount.0.bytes)?;
+    hasher.update(&output.lock_time.0.to_be_bytes())?;
+    let tokens = output.get_tokens();
+    hasher.update(&[tokens.len() as u8])?;
+    for token in tokens {
+        hasher.update(&token.id.0)?;
+        hasher.update(&token.amount.0.bytes)?;
+    }
+    hasher.update(output.additional_data())?;
+    hasher.finalize()
+}
+
+// The encoded multisig lockup script starts with its type and the compact encoded number of keys
+#[inline]
+fn first_multi_sig_key_hash(encoded_lockup_script: &[u8]) -> Option<&[u8]> {
+    let size_length = 1usize << (encoded_lockup_script.get(1)? >> 6);
+    encoded_lockup_script.get((1 + size_length)..(1 + size_length + Hash::ENCODED_LENGTH))
+}
+
+#[inline]
+fn is_dust_amount(amount: &U256) -> bool {
+    *amount < U256::encode_u128(DUST_AMOUNT)
+}
+
+// The position of a chained tx, the number of txs of a session is a single digit
+#[inline]
+fn chained_tx_position(index: u8, count: u8) -> [u8; 6] {
+    [b'1' + index, b' ', b'o', b'f', b' ', b'0' + count]
+}
+
+#[inline]
+fn testnet_key_field() -> Field<'static> {
+    Field {
+        name: "Warning",
+        value: TESTNET_KEY_WARNING,
+    }
+}
+
+#[inline]
+fn token_id_name(is_nft: bool, has_token_metadata: bool) -> &'static str {
+    if is_nft {
+        "NFT"
+    } else if has_token_metadata {
+        "Token"
+    } else {
+        "Token ID"
+    }
+}
+
+// Write the NFT as `Collection <address> item #<index>`, or with the hex path if the
+// sub-contract path is not an item index
+fn write_nft<'a>(nft: &NftCollection, output: &'a mut [u8; 160]) -> Result<&'a [u8], ErrorCode> {
+    let mut address = [0u8; 46];
+    let address = to_base58_address(P2C_PREFIX, &nft.parent_id, &mut address)?;
+    let mut suffix_output = [0u8; 78];
+    let (separator, suffix): (&[u8], &[u8]) = match nft.item_index() {
+        Some(index) => (
+            b" item #",
+            index
+                .to_str(&mut suffix_output)
+                .ok_or(ErrorCode::Overflow)?,
+        ),
+        None => {
+            let path = nft.path();
+            let hex: [u8; 64] = to_hex(path).ok_or(ErrorCode::Overflow)?;
+            suffix_output[..64].copy_from_slice(&hex);
+            (b" path ", &suffix_output[..(path.len() * 2)])
+        }
+    };
+    let mut length = 0;
+    for bytes in [b"Collection " as &[u8], address, separator, suffix] {
+        output[length..(length + bytes.len())].copy_from_slice(bytes);
+        length += bytes.len();
+    }
+    Ok(&output[..length])
+}
+
 #[inline]
 fn get_token_symbol_bytes(bytes: &[u8]) -> &[u8] {
     let mut index = 0;
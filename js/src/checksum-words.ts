@@ -0,0 +1,45 @@
+import { blake2b } from 'blakejs'
+
+// The same wordlist as the app, so that the checksum words shown by the host match the device
+export const WORDLIST: string[] = [
+  'acid', 'acorn', 'actor', 'agent', 'alarm', 'album', 'alien', 'alley', 'amber', 'angle', 'ankle', 'apple', 'apron',
+  'arena', 'armor', 'arrow', 'atlas', 'attic', 'award', 'axis', 'bacon', 'badge', 'bagel', 'baker', 'bamboo',
+  'banjo', 'barn', 'basil', 'beach', 'beard', 'bench', 'berry', 'bison', 'blade', 'bloom', 'board', 'boat', 'bonus',
+  'boots', 'brain', 'bread', 'brick', 'bridge', 'broom', 'bucket', 'cabin', 'cactus', 'camel', 'candle', 'canoe',
+  'canyon', 'cargo', 'carpet', 'castle', 'cedar', 'chain', 'chalk', 'cheese', 'cherry', 'chess', 'chief', 'cider',
+  'cloud', 'clover', 'coast', 'cobra', 'comet', 'coral', 'cotton', 'crane', 'crown', 'daisy', 'dance', 'delta',
+  'denim', 'desert', 'diary', 'dingo', 'donkey', 'dragon', 'drum', 'duck', 'eagle', 'earth', 'easel', 'echo',
+  'elbow', 'elder', 'ember', 'engine', 'fabric', 'falcon', 'fence', 'ferry', 'fiber', 'field', 'flame', 'flute',
+  'foam', 'forest', 'fossil', 'frost', 'fruit', 'galaxy', 'garden', 'garlic', 'gecko', 'giant', 'ginger', 'globe',
+  'goat', 'grape', 'gravel', 'guitar', 'hammer', 'harbor', 'hawk', 'hazel', 'heart', 'hedge', 'helmet', 'heron',
+  'honey', 'horse', 'hotel', 'igloo', 'island', 'ivory', 'jacket', 'jaguar', 'jelly', 'jewel', 'jigsaw', 'juice',
+  'jungle', 'kayak', 'kettle', 'kiwi', 'knight', 'koala', 'ladder', 'lagoon', 'lamp', 'laser', 'lava', 'lemon',
+  'lily', 'lion', 'lizard', 'locket', 'lotus', 'lunar', 'magnet', 'mango', 'maple', 'marble', 'meadow', 'melon',
+  'mint', 'mirror', 'moose', 'motor', 'muffin', 'museum', 'napkin', 'nectar', 'needle', 'nest', 'noodle', 'oasis',
+  'ocean', 'olive', 'onion', 'opera', 'orange', 'orbit', 'otter', 'oyster', 'paddle', 'palace', 'panda', 'paper',
+  'parrot', 'peach', 'pearl', 'pebble', 'pepper', 'piano', 'pillow', 'pilot', 'pine', 'planet', 'plum', 'pony',
+  'potato', 'puzzle', 'quail', 'quartz', 'quilt', 'rabbit', 'radar', 'radio', 'raven', 'reef', 'ribbon', 'river',
+  'robot', 'rocket', 'ruby', 'saddle', 'salmon', 'sand', 'satin', 'scarf', 'shark', 'shell', 'silver', 'skate',
+  'snow', 'socks', 'solar', 'spider', 'spoon', 'squid', 'stone', 'storm', 'sugar', 'summit', 'swan', 'table',
+  'tiger', 'timber', 'toast', 'tomato', 'torch', 'tower', 'tulip', 'tunnel', 'turtle', 'valley', 'velvet', 'violin',
+  'wagon', 'walnut', 'water', 'whale', 'wheat', 'willow', 'window', 'wolf', 'wool', 'yacht', 'yarn', 'yogurt',
+  'zebra', 'zinc',
+]
+
+export const CHECKSUM_WORD_SIZE = 3
+
+// Derive the checksum words of a base58 address, the device shows the same words under each destination address
+export function checksumWords(address: string): string[] {
+  const hash = blake2b(Buffer.from(address, 'ascii'), undefined, 32)
+  return Array.from(hash.slice(0, CHECKSUM_WORD_SIZE)).map((byte) => WORDLIST[byte])
+}
+
+// The device shows a token id without metadata as its first and last 8 hex chars followed by these words
+export function tokenIdChecksumWords(tokenId: string): string[] {
+  return checksumWords(tokenId.toLowerCase())
+}
+
+export function compactTokenId(tokenId: string): string {
+  const hex = tokenId.toLowerCase()
+  return `${hex.slice(0, 8)}...${hex.slice(-8)} (${tokenIdChecksumWords(hex).join(' ')})`
+}
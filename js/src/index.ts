@@ -1,2 +1,3 @@
 export * from './types'
 export * from './ledger-app'
+export * from './checksum-words'
@@ -1,10 +1,11 @@
 import { Account, KeyType, addressFromPublicKey, binToHex, codec, encodeHexSignature, groupOfAddress } from '@alephium/web3'
-import Transport, { StatusCodes } from '@ledgerhq/hw-transport'
+import Transport, { StatusCodes, TransportStatusError } from '@ledgerhq/hw-transport'
 import * as serde from './serde'
 import { ec as EC } from 'elliptic'
-import { MAX_TOKEN_SIZE, MAX_TOKEN_SYMBOL_LENGTH, TokenMetadata } from './types'
-import { encodeTokenMetadata, encodeUnsignedTx } from './tx-encoder'
+import { EstimatedOutput, MAX_TOKEN_SIZE, MAX_TOKEN_SYMBOL_LENGTH, NftMetadata, TokenMetadata } from './types'
+import { Frame, encodeChainedTx, encodeEstimatedOutputs, encodeInputPaths, encodeMessage, encodeNftMetadata, encodePreviewTx, encodeSweepTx, encodeTokenMetadata, encodeUnsignedTx } from './tx-encoder'
 import { merkleTokens } from './merkle'
+import { createHmac, timingSafeEqual } from 'crypto'
 
 const ec = new EC('secp256k1')
 
@@ -13,23 +14,255 @@ export enum INS {
   GET_VERSION = 0x00,
   GET_PUBLIC_KEY = 0x01,
   SIGN_HASH = 0x02,
-  SIGN_TX = 0x03
+  SIGN_TX = 0x03,
+  GET_RESPONSE_MAC_KEY = 0x04,
+  GET_LAST_ERROR = 0x05,
+  SELF_TEST = 0x06,
+  GET_CHANGE_ADDRESS = 0x07,
+  GET_APP_CONFIGURATION = 0x08,
+  SIGN_MESSAGE = 0x09,
+  GET_SIGN_TX_STATUS = 0x0a,
+  GET_EXTENDED_PUBLIC_KEY = 0x0b,
+  GET_BUFFER_CAPACITY = 0x0c,
+  ABORT = 0x0d,
+  SIGN_CHALLENGE = 0x0e,
+  SET_AMOUNT_THRESHOLD = 0x0f,
+  SIGN_TYPED_DATA = 0x10,
+  GET_MULTI_SIG_ADDRESS = 0x11,
+  GET_WALLET_FINGERPRINT = 0x12
+}
+
+// The bits of the self-test result, a bit is set if the test passed
+export enum SelfTestResult {
+  BLAKE2B = 0x01,
+  BASE58 = 0x02,
+  U256 = 0x04,
+  SIGNATURE = 0x08,
+  NVM = 0x10
+}
+export const SELF_TEST_ALL_PASSED = 0x1f
+
+// The bits of the features supported by the app
+export enum AppFeature {
+  TOKEN_METADATA = 0x01,
+  SCHNORR = 0x02,
+  SWEEP_TX = 0x04,
+  RESPONSE_MAC = 0x08,
+  ESTIMATED_OUTPUTS = 0x10,
+  NFT_METADATA = 0x20,
+  CHANGE_ADDRESS = 0x40,
+  TESTNET_PATHS = 0x80,
+  SIGN_MESSAGE = 0x100,
+  SIGN_TX_STATUS = 0x200,
+  EXTENDED_PUBLIC_KEY = 0x400,
+  BUFFER_CAPACITY = 0x800,
+  ABORT = 0x1000,
+  SIGN_CHALLENGE = 0x2000,
+  AMOUNT_THRESHOLD = 0x4000,
+  SIGN_TYPED_DATA = 0x8000,
+  MULTI_SIG_ADDRESS = 0x10000,
+  WALLET_FINGERPRINT = 0x20000,
+  INPUT_PATHS = 0x40000,
+  CHAINED_TXS = 0x80000
+}
+
+// The state of the app across instructions, returned by `getSignTxStatus`
+export enum InstructionState {
+  IDLE = 0,
+  UPLOADING = 1,
+  REVIEWING = 2,
+  SIGNING = 3,
+  UPLOADING_MESSAGE = 4
+}
+
+export interface BufferCapacity {
+  ramSize: number
+  nvmSize: number
+  // The maximum size of a single script or multisig lockup of a tx
+  available: number
+  maxFrameSize: number
+}
+
+export interface ExtendedPublicKey {
+  publicKey: string
+  chainCode: string
+  parentFingerprint: number
+}
+
+export interface SignTxStatus {
+  state: InstructionState
+  frameCount: number
+}
+
+// The number of times a tx is resumed after a transport failure
+const MAX_RESUME_ATTEMPTS = 3
+
+export interface AppConfiguration {
+  version: string
+  features: number
+  blindSigningEnabled: boolean
+  responseMacEnabled: boolean
+  mainnetOnlyEnabled: boolean
+  displayTxIdEnabled: boolean
+  expertModeEnabled: boolean
+  unknownTokenWarningEnabled: boolean
+  crossGroupWarningDisabled: boolean
+}
+
+export interface LastError {
+  statusCode: number
+  ins: number
+  txStep: number
+  stageStep: number
+  stageIndex: number
 }
 
 export const GROUP_NUM = 4
 export const HASH_LEN = 32
+export const SCHNORR_SIGNATURE_LEN = 64
+export const RESPONSE_MAC_LEN = 32
+export const MAX_BATCH_ACCOUNTS = 6
+const COMPRESSED_PUBLIC_KEY_LEN = 33
+// Set in P2 of `GET_PUBLIC_KEY` to return the compressed public key
+const COMPRESSED_PUBLIC_KEY_FLAG = 0x80
+const MAX_TYPED_DATA_SIZE = 256
+const MAX_MULTI_SIG_ADDRESS_KEYS = 5
 
 export class AlephiumApp {
   readonly transport: Transport
+  // The response MAC key learned during onboarding, the responses are verified if it is set
+  readonly responseMacKey: Buffer | undefined
 
-  constructor(transport: Transport) {
+  constructor(transport: Transport, responseMacKey?: Buffer) {
     this.transport = transport
+    this.responseMacKey = responseMacKey
+  }
+
+  // The response MAC needs to be enabled in the device settings, and the user needs to confirm sharing the key
+  async getResponseMacKey(): Promise<Buffer> {
+    const response = await this.transport.send(CLA, INS.GET_RESPONSE_MAC_KEY, 0x00, 0x00, Buffer.alloc(0), [StatusCodes.OK])
+    return response.slice(0, response.length - 2)
+  }
+
+  // Verify and remove the response MAC, the response ends with the 2 bytes status code
+  private verifyResponse(ins: INS, response: Buffer): Buffer {
+    const data = response.slice(0, response.length - 2)
+    if (this.responseMacKey === undefined) {
+      return data
+    }
+    if (data.length < RESPONSE_MAC_LEN) {
+      throw Error('Missing response MAC')
+    }
+    const payload = data.slice(0, data.length - RESPONSE_MAC_LEN)
+    const mac = data.slice(data.length - RESPONSE_MAC_LEN)
+    const expected = createHmac('sha256', this.responseMacKey).update(Buffer.from([ins])).update(payload).digest()
+    if (!timingSafeEqual(mac, expected)) {
+      throw Error('Invalid response MAC')
+    }
+    return payload
   }
 
   async close(): Promise<void> {
     await this.transport.close()
   }
 
+  // The last error reported by the device, all fields are 0 if there is no error
+  async getLastError(): Promise<LastError> {
+    const response = await this.transport.send(CLA, INS.GET_LAST_ERROR, 0x00, 0x00, Buffer.alloc(0), [StatusCodes.OK])
+    return {
+      statusCode: response.readUInt16BE(0),
+      ins: response[2],
+      txStep: response[3],
+      stageStep: response.readUInt16BE(4),
+      stageIndex: response.readUInt16BE(6)
+    }
+  }
+
+  // Run the known-answer tests on the device and return the bitmap of the passed tests
+  async selfTest(): Promise<number> {
+    const response = await this.transport.send(CLA, INS.SELF_TEST, 0x00, 0x00, Buffer.alloc(0), [StatusCodes.OK])
+    return response[0]
+  }
+
+  // Check `features` with the `AppFeature` bits before using a feature
+  async getAppConfiguration(): Promise<AppConfiguration> {
+    const response = await this.transport.send(CLA, INS.GET_APP_CONFIGURATION, 0x00, 0x00, Buffer.alloc(0), [StatusCodes.OK])
+    const settings = response[5]
+    return {
+      version: `${response[0]}.${response[1]}.${response[2]}`,
+      // The features from bit 16 follow the settings and precede the status word, older versions of
+      // the app do not return them
+      features: response.readUInt16BE(3) + (response.length >= 10 ? response.readUInt16BE(6) * 0x10000 : 0),
+      blindSigningEnabled: (settings & 0x01) !== 0,
+      responseMacEnabled: (settings & 0x02) !== 0,
+      mainnetOnlyEnabled: (settings & 0x04) !== 0,
+      displayTxIdEnabled: (settings & 0x08) !== 0,
+      expertModeEnabled: (settings & 0x10) !== 0,
+      unknownTokenWarningEnabled: (settings & 0x20) !== 0,
+      crossGroupWarningDisabled: (settings & 0x40) !== 0
+    }
+  }
+
+  // The first 4 bytes of the HASH160 of the compressed public key of `m/44'/1234'`, in hex. With `display`,
+  // the user confirms on the device that it matches the wallet id shown by the coordinator
+  async getWalletFingerprint(display = false): Promise<string> {
+    const response = await this.transport.send(CLA, INS.GET_WALLET_FINGERPRINT, display ? 0x01 : 0x00, 0x00, Buffer.alloc(0), [
+      StatusCodes.OK
+    ])
+    return response.slice(0, 4).toString('hex')
+  }
+
+  // Sign blake2b("Alephium Device Attestation: " || challenge || compressed public key) after the user confirms
+  // the pairing, so the wallet can check that the device holds the key of the account
+  async signChallenge(path: string, challenge: Buffer): Promise<string> {
+    if (challenge.length < 16 || challenge.length > 64) {
+      throw Error(`Invalid challenge size: ${challenge.length}`)
+    }
+    const data = Buffer.concat([serde.serializePath(path), challenge])
+    const response = await this.transport.send(CLA, INS.SIGN_CHALLENGE, 0x00, 0x00, data, [StatusCodes.OK])
+    return decodeSignature(this.verifyResponse(INS.SIGN_CHALLENGE, response))
+  }
+
+  // Set the ALPH amount above which the user confirms signing a tx on an additional screen, 0 disables it.
+  // The user confirms the new threshold on the device
+  async setAmountThreshold(attoAlphAmount: bigint): Promise<void> {
+    if (attoAlphAmount < 0n || attoAlphAmount >= (1n << 128n)) {
+      throw Error(`Invalid amount threshold: ${attoAlphAmount}`)
+    }
+    const data = Buffer.alloc(16)
+    data.writeBigUInt64BE(attoAlphAmount >> 64n, 0)
+    data.writeBigUInt64BE(attoAlphAmount & ((1n << 64n) - 1n), 8)
+    await this.transport.send(CLA, INS.SET_AMOUNT_THRESHOLD, 0x00, 0x00, data, [StatusCodes.OK])
+  }
+
+  // Discard the tx or the message in progress, e.g. after the host cancelled the flow
+  async abort(): Promise<void> {
+    await this.transport.send(CLA, INS.ABORT, 0x00, 0x00, Buffer.alloc(0), [StatusCodes.OK])
+  }
+
+  // The tx is decoded in a stream, each script or multisig lockup of the tx has to fit in the available capacity
+  async getBufferCapacity(): Promise<BufferCapacity> {
+    const response = await this.transport.send(CLA, INS.GET_BUFFER_CAPACITY, 0x00, 0x00, Buffer.alloc(0), [StatusCodes.OK])
+    return {
+      ramSize: response.readUInt16BE(0),
+      nvmSize: response.readUInt16BE(2),
+      available: response.readUInt16BE(4),
+      maxFrameSize: response[6]
+    }
+  }
+
+  // The number of processed frames of the tx in progress, used to resume a tx after a transport failure
+  async getSignTxStatus(): Promise<SignTxStatus> {
+    const response = await this.transport.send(CLA, INS.GET_SIGN_TX_STATUS, 0x00, 0x00, Buffer.alloc(0), [StatusCodes.OK])
+    return { state: response[0], frameCount: response.readUInt16BE(1) }
+  }
+
+  // Declare the tx encoding version to the device, which fails if the version is not supported
+  async checkTxVersion(txVersion: number): Promise<{ minTxVersion: number; maxTxVersion: number }> {
+    const response = await this.transport.send(CLA, INS.GET_VERSION, 0x00, 0x00, Buffer.from([txVersion]), [StatusCodes.OK])
+    return { minTxVersion: response[3], maxTxVersion: response[4] }
+  }
+
   async getVersion(): Promise<string> {
     const response = await this.transport.send(CLA, INS.GET_VERSION, 0x00, 0x00)
     console.log(`response ${response.length} - ${response.toString('hex')}`)
@@ -41,22 +274,95 @@ export class AlephiumApp {
       throw Error(`Invalid targetGroup: ${targetGroup}`)
     }
 
-    if (keyType === 'bip340-schnorr') {
-      throw Error('BIP340-Schnorr is not supported yet')
+    const pathKeyType = serde.getKeyType(startPath)
+    if (keyType !== undefined && keyType !== pathKeyType) {
+      throw Error(`The key type ${keyType} does not match the path ${startPath}`)
     }
 
     const p1 = targetGroup === undefined ? 0x00 : GROUP_NUM
     const p2 = targetGroup === undefined ? 0x00 : targetGroup
     const payload = Buffer.concat([serde.serializePath(startPath), Buffer.from([display ? 1 : 0])]);
-    const response = await this.transport.send(CLA, INS.GET_PUBLIC_KEY, p1, p2, payload)
-    const publicKey = ec.keyFromPublic(response.slice(0, 65)).getPublic(true, 'hex')
-    const address = addressFromPublicKey(publicKey)
+    const response = this.verifyResponse(INS.GET_PUBLIC_KEY, await this.transport.send(CLA, INS.GET_PUBLIC_KEY, p1, p2, payload))
+    // Schnorr keys use the x-only public key
+    const publicKey = pathKeyType === 'bip340-schnorr'
+      ? response.slice(1, 33).toString('hex')
+      : ec.keyFromPublic(response.slice(0, 65)).getPublic(true, 'hex')
+    const address = addressFromPublicKey(publicKey, pathKeyType)
     const group = groupOfAddress(address)
     const hdIndex = response.slice(65, 69).readUInt32BE(0)
 
-    return [{ publicKey: publicKey, address: address, group: group, keyType: keyType ?? 'default' }, hdIndex] as const
+    return [{ publicKey: publicKey, address: address, group: group, keyType: pathKeyType }, hdIndex] as const
+  }
+
+  // The raw public key of a path, uncompressed (65 bytes) by default or compressed (33 bytes)
+  async getPublicKey(path: string, compressed = false): Promise<Buffer> {
+    const payload = Buffer.concat([serde.serializePath(path), Buffer.from([0])])
+    const p2 = compressed ? COMPRESSED_PUBLIC_KEY_FLAG : 0x00
+    const response = this.verifyResponse(INS.GET_PUBLIC_KEY, await this.transport.send(CLA, INS.GET_PUBLIC_KEY, 0x00, p2, payload))
+    return response.slice(0, compressed ? COMPRESSED_PUBLIC_KEY_LEN : 65)
   }
 
+  // The change address is derived by the device, so the host can check the change outputs before building the tx
+  async getChangeAddress(path: string, targetGroup?: number, display = false): Promise<readonly [string, number]> {
+    if ((targetGroup ?? 0) >= GROUP_NUM) {
+      throw Error(`Invalid targetGroup: ${targetGroup}`)
+    }
+
+    const p1 = targetGroup === undefined ? 0x00 : GROUP_NUM
+    const p2 = targetGroup === undefined ? 0x00 : targetGroup
+    const payload = Buffer.concat([serde.serializePath(path), Buffer.from([display ? 1 : 0])])
+    const response = this.verifyResponse(
+      INS.GET_CHANGE_ADDRESS,
+      await this.transport.send(CLA, INS.GET_CHANGE_ADDRESS, p1, p2, payload, [StatusCodes.OK])
+    )
+    const hdIndex = response.readUInt32BE(0)
+    const address = response.slice(4).toString('ascii')
+    return [address, hdIndex] as const
+  }
+
+  // Derive the accounts of consecutive indexes in one exchange, for account discovery
+  async getAccounts(startPath: string, count: number, targetGroup?: number): Promise<(readonly [Account, number])[]> {
+    if (count <= 0 || count > MAX_BATCH_ACCOUNTS) {
+      throw Error(`Invalid count: ${count}`)
+    }
+    if ((targetGroup ?? 0) >= GROUP_NUM) {
+      throw Error(`Invalid targetGroup: ${targetGroup}`)
+    }
+
+    const keyType = serde.getKeyType(startPath)
+    const p1 = targetGroup === undefined ? 0x00 : GROUP_NUM
+    const p2 = targetGroup === undefined ? 0x00 : targetGroup
+    const payload = Buffer.concat([serde.serializePath(startPath), Buffer.from([0, count])])
+    const response = this.verifyResponse(INS.GET_PUBLIC_KEY, await this.transport.send(CLA, INS.GET_PUBLIC_KEY, p1, p2, payload))
+    const entrySize = COMPRESSED_PUBLIC_KEY_LEN + 4
+    return Array.from({ length: count }, (_, index) => {
+      const entry = response.slice(index * entrySize, (index + 1) * entrySize)
+      const compressed = entry.slice(0, COMPRESSED_PUBLIC_KEY_LEN)
+      // Schnorr keys use the x-only public key
+      const publicKey = keyType === 'bip340-schnorr' ? compressed.slice(1).toString('hex') : compressed.toString('hex')
+      const address = addressFromPublicKey(publicKey, keyType)
+      const hdIndex = entry.readUInt32BE(COMPRESSED_PUBLIC_KEY_LEN)
+      return [{ publicKey, address, group: groupOfAddress(address), keyType }, hdIndex] as const
+    })
+  }
+
+  // The compressed public key and the chain code of a path with at least 3 nodes, e.g. `m/44'/1234'/0'/0`,
+  // the non-hardened child keys can be derived without the device. The key of an account path
+  // `m/44'/1234'/account'` is only returned after the user confirms the export on the device
+  async getExtendedPublicKey(path: string): Promise<ExtendedPublicKey> {
+    const payload = serde.serializePath(path, 3)
+    const response = this.verifyResponse(
+      INS.GET_EXTENDED_PUBLIC_KEY,
+      await this.transport.send(CLA, INS.GET_EXTENDED_PUBLIC_KEY, 0x00, 0x00, payload, [StatusCodes.OK])
+    )
+    return {
+      publicKey: response.slice(0, COMPRESSED_PUBLIC_KEY_LEN).toString('hex'),
+      chainCode: response.slice(COMPRESSED_PUBLIC_KEY_LEN, COMPRESSED_PUBLIC_KEY_LEN + 32).toString('hex'),
+      parentFingerprint: response.readUInt32BE(COMPRESSED_PUBLIC_KEY_LEN + 32)
+    }
+  }
+
+  // Signing a raw hash requires the blind signing setting on the device
   async signHash(path: string, hash: Buffer): Promise<string> {
     if (hash.length !== HASH_LEN) {
       throw new Error('Invalid hash length')
@@ -64,25 +370,161 @@ export class AlephiumApp {
 
     const data = Buffer.concat([serde.serializePath(path), hash])
     console.log(`data ${data.length}`)
-    const response = await this.transport.send(CLA, INS.SIGN_HASH, 0x00, 0x00, data, [StatusCodes.OK])
+    const response = this.verifyResponse(INS.SIGN_HASH, await this.transport.send(CLA, INS.SIGN_HASH, 0x00, 0x00, data, [StatusCodes.OK]))
     console.log(`response ${response.length} - ${response.toString('hex')}`)
 
     return decodeSignature(response)
   }
 
-  async signUnsignedTx(path: string, unsignedTx: Buffer): Promise<string> {
+  // Sign the message with the `Alephium Signed Message: ` prefix, long messages are reviewed by their hash
+  async signMessage(path: string, message: string): Promise<string> {
+    const messageBytes = Buffer.from(message, 'utf8')
+    if (messageBytes.length === 0 || messageBytes.length > 0xffff) {
+      throw Error(`Invalid message size: ${messageBytes.length}`)
+    }
+    const frames = encodeMessage(path, messageBytes)
+    let response: Buffer | undefined = undefined
+    for (const frame of frames) {
+      response = await this.transport.send(CLA, INS.SIGN_MESSAGE, frame.p1, frame.p2, frame.data, [StatusCodes.OK])
+    }
+    return decodeSignature(this.verifyResponse(INS.SIGN_MESSAGE, response!))
+  }
+
+  // The device computes the multi-sig address from the compressed public keys of the co-signers and
+  // displays it, so that each co-signer can verify the shared address independently of the coordinator
+  async getMultiSigAddress(publicKeys: string[], threshold: number): Promise<string> {
+    if (publicKeys.length === 0 || publicKeys.length > MAX_MULTI_SIG_ADDRESS_KEYS) {
+      throw Error(`Invalid number of public keys: ${publicKeys.length}`)
+    }
+    if (threshold < 1 || threshold > publicKeys.length) {
+      throw Error(`Invalid threshold: ${threshold}`)
+    }
+    const data = Buffer.concat(publicKeys.map((publicKey) => Buffer.from(publicKey, 'hex')))
+    const response = this.verifyResponse(
+      INS.GET_MULTI_SIG_ADDRESS,
+      await this.transport.send(CLA, INS.GET_MULTI_SIG_ADDRESS, threshold, 0x00, data, [StatusCodes.OK])
+    )
+    return response.toString('ascii')
+  }
+
+  // The typed data is the encoded domain followed by the encoded message struct, see `SignTypedData`
+  // in docs/apdu.md. The signed hash commits to the domain, so the signature cannot be replayed as a tx
+  async signTypedData(path: string, typedData: Buffer): Promise<string> {
+    if (typedData.length === 0 || typedData.length > MAX_TYPED_DATA_SIZE) {
+      throw Error(`Invalid typed data size: ${typedData.length}`)
+    }
+    const frames = encodeMessage(path, typedData)
+    let response: Buffer | undefined = undefined
+    for (const frame of frames) {
+      response = await this.transport.send(CLA, INS.SIGN_TYPED_DATA, frame.p1, frame.p2, frame.data, [StatusCodes.OK])
+    }
+    return decodeSignature(this.verifyResponse(INS.SIGN_TYPED_DATA, response!))
+  }
+
+  // The estimated outputs of a script tx are displayed as unverified results after the tx details,
+  // and the NFTs are displayed by their collection and item index instead of the token id.
+  // The inputs spent from the `inputPaths` of the device are not displayed as external inputs
+  async signUnsignedTx(
+    path: string,
+    unsignedTx: Buffer,
+    estimatedOutputs: EstimatedOutput[] = [],
+    nfts: NftMetadata[] = [],
+    inputPaths: string[] = []
+  ): Promise<string> {
     console.log(`unsigned tx size: ${unsignedTx.length}`)
+    const txFrames = [
+      ...encodeEstimatedOutputs(estimatedOutputs),
+      ...encodeNftMetadata(nfts),
+      ...encodeInputPaths(inputPaths),
+      ...encodeUnsignedTx(path, unsignedTx)
+    ]
+    const response = await this.sendTxFrames(unsignedTx, txFrames)
+    return decodeSignature(response)
+  }
+
+  // Review a tx on the device without signing it, the tx id is returned once the preview is confirmed
+  async previewUnsignedTx(
+    path: string,
+    unsignedTx: Buffer,
+    estimatedOutputs: EstimatedOutput[] = [],
+    nfts: NftMetadata[] = []
+  ): Promise<string> {
+    const txFrames = [
+      ...encodeEstimatedOutputs(estimatedOutputs),
+      ...encodeNftMetadata(nfts),
+      ...encodePreviewTx(path, unsignedTx)
+    ]
+    const response = await this.sendTxFrames(unsignedTx, txFrames)
+    return response.toString('hex')
+  }
+
+  // Sign a tx that spends from all the paths after a single review, one signature is returned per path
+  async signSweepTx(paths: string[], unsignedTx: Buffer): Promise<string[]> {
+    console.log(`unsigned tx size: ${unsignedTx.length}, paths: ${paths.length}`)
+    const txFrames = encodeSweepTx(paths, unsignedTx)
+    const response = await this.sendTxFrames(unsignedTx, txFrames)
+    const signatures = [decodeSignature(response)]
+    for (let index = 1; index < paths.length; index++) {
+      const response = this.verifyResponse(INS.SIGN_TX, await this.transport.send(CLA, INS.SIGN_TX, 0x02, index, Buffer.alloc(0), [StatusCodes.OK]))
+      signatures.push(decodeSignature(response))
+    }
+    return signatures
+  }
+
+  // Sign dependent txs that are reviewed one by one, the signatures are returned once the last tx is approved
+  async signChainedTxs(txs: { path: string, unsignedTx: Buffer }[]): Promise<string[]> {
+    let response: Buffer | undefined = undefined
+    for (let index = 0; index < txs.length; index++) {
+      const { path, unsignedTx } = txs[index]
+      console.log(`unsigned tx size: ${unsignedTx.length}, chained tx: ${index + 1} of ${txs.length}`)
+      const txFrames = encodeChainedTx(index, txs.length, path, unsignedTx)
+      response = await this.sendTxFrames(unsignedTx, txFrames)
+    }
+    // Each signature is prefixed with its length
+    const signatures: string[] = []
+    let offset = 0
+    while (offset < response!.length) {
+      const length = response![offset]
+      signatures.push(decodeSignature(response!.slice(offset + 1, offset + 1 + length)))
+      offset += 1 + length
+    }
+    return signatures
+  }
+
+  private async sendTxFrames(unsignedTx: Buffer, txFrames: Frame[]): Promise<Buffer> {
     const tokenMetadata = getTokenMetadata(unsignedTx)
     serde.checkTokenMetadata(tokenMetadata)
     const tokenMetadataFrames = encodeTokenMetadata(tokenMetadata)
-    const txFrames = encodeUnsignedTx(path, unsignedTx)
     const allFrames = [...tokenMetadataFrames, ...txFrames]
 
     let response: Buffer | undefined = undefined
-    for (const frame of allFrames) {
-      response = await this.transport.send(CLA, INS.SIGN_TX, frame.p1, frame.p2, frame.data, [StatusCodes.OK])
+    let frameIndex = 0
+    let resumeAttempts = 0
+    while (frameIndex < allFrames.length) {
+      const frame = allFrames[frameIndex]
+      try {
+        response = await this.transport.send(CLA, INS.SIGN_TX, frame.p1, frame.p2, frame.data, [StatusCodes.OK])
+        frameIndex += 1
+      } catch (error) {
+        // The device may have processed the frame before the transport failed, so the tx is
+        // resumed from the frame count of the device. Rejected frames reset the tx on the device.
+        if (error instanceof TransportStatusError || resumeAttempts >= MAX_RESUME_ATTEMPTS) {
+          throw error
+        }
+        resumeAttempts += 1
+        const status = await this.getSignTxStatus()
+        if (status.state !== InstructionState.UPLOADING && status.state !== InstructionState.REVIEWING) {
+          throw error
+        }
+        frameIndex = status.frameCount
+      }
+    }
+    // The device only hashes the rest of a tx of an unrecognized format, it is approved with
+    // an empty end frame once all the frames are sent
+    if (response!.length === 2) {
+      response = await this.transport.send(CLA, INS.SIGN_TX, 0x01, 0x04, Buffer.alloc(0), [StatusCodes.OK])
     }
-    return decodeSignature(response!)
+    return this.verifyResponse(INS.SIGN_TX, response!)
   }
 }
 
@@ -110,6 +552,10 @@ function isTokenIdEqual(a: string, b: string): boolean {
 }
 
 function decodeSignature(response: Buffer): string {
+  // BIP340 Schnorr signatures are returned as is
+  if (response.length === SCHNORR_SIGNATURE_LEN) {
+    return response.toString('hex')
+  }
   // Decode signature: https://bitcoin.stackexchange.com/a/12556
   const rLen = response.slice(3, 4)[0]
   const r = response.slice(4, 4 + rLen)
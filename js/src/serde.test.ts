@@ -1,5 +1,5 @@
 import { binToHex } from '@alephium/web3'
-import { serializePath, serializeTokenMetadata, splitPath } from './serde'
+import { getKeyType, serializePath, serializeTokenMetadata, splitPath } from './serde'
 import { randomBytes } from 'crypto'
 import { MAX_TOKEN_SYMBOL_LENGTH, TokenMetadata } from './types'
 
@@ -18,6 +18,13 @@ describe('serde', () => {
     )
   })
 
+  it('should get key type from path', () => {
+    expect(getKeyType(`m/44'/1234'/0'/0/0`)).toEqual('default')
+    expect(getKeyType(`m/44'/1234'/1'/0/0`)).toEqual('bip340-schnorr')
+    expect(() => getKeyType(`m/44'/1234'/2'/0/0`)).toThrow()
+    expect(() => getKeyType(`m/44'/1234'/0/0/0`)).toThrow()
+  })
+
   it('should encode token metadata', () => {
     const token0: TokenMetadata = {
       version: 0,
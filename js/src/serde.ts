@@ -1,4 +1,4 @@
-import { isHexString } from '@alephium/web3'
+import { KeyType, isHexString } from '@alephium/web3'
 import { MAX_TOKEN_SIZE, MAX_TOKEN_SYMBOL_LENGTH, TOKEN_METADATA_SIZE, TokenMetadata } from './types'
 
 export const TRUE = 0x10
@@ -21,10 +21,10 @@ export function splitPath(path: string): number[] {
   return result
 }
 
-export function serializePath(path: string): Buffer {
+export function serializePath(path: string, minLength = 5): Buffer {
   const nodes = splitPath(path)
 
-  if (nodes.length != 5) {
+  if (nodes.length < minLength || nodes.length > 5) {
     throw Error('Invalid BIP32 path length')
   }
   const buffer = Buffer.alloc(nodes.length * 4)
@@ -32,6 +32,18 @@ export function serializePath(path: string): Buffer {
   return buffer
 }
 
+// The account node selects the key type: m/44'/1234'/0'/0/index for default keys and m/44'/1234'/1'/0/index for Schnorr keys
+export function getKeyType(path: string): KeyType {
+  const keyTypeNode = splitPath(path)[2]
+  if (keyTypeNode === 0x80000000) {
+    return 'default'
+  }
+  if (keyTypeNode === 0x80000001) {
+    return 'bip340-schnorr'
+  }
+  throw Error(`Invalid key type in bip32 path: ${path}`)
+}
+
 function symbolToBytes(symbol: string): Buffer {
   const buffer = Buffer.alloc(MAX_TOKEN_SYMBOL_LENGTH, 0)
   for (let i = 0; i < symbol.length; i++) {
@@ -1,6 +1,7 @@
+import { bs58, codec } from "@alephium/web3"
 import { tokenMerkleProofs } from "./merkle"
 import { checkTokenMetadata, serializePath, serializeSingleTokenMetadata } from "./serde"
-import { MAX_PAYLOAD_SIZE, TokenMetadata } from "./types"
+import { EstimatedOutput, MAX_CHAINED_TXS, MAX_ESTIMATED_OUTPUTS, MAX_INPUT_PATHS, MAX_NFTS, MAX_PAYLOAD_SIZE, MAX_SWEEP_PATHS, NftMetadata, TokenMetadata } from "./types"
 
 export interface Frame {
   p1: number
@@ -66,15 +67,82 @@ export function encodeProofLength(length: number): Uint8Array {
   return buffer;
 }
 
+// Each estimated output is encoded as the address bytes followed by the encoded ALPH amount
+export function encodeEstimatedOutputs(outputs: EstimatedOutput[]): Frame[] {
+  if (outputs.length === 0) return []
+  assert(outputs.length <= MAX_ESTIMATED_OUTPUTS, 'Too many estimated outputs')
+  const data = Buffer.concat(outputs.map((output) => {
+    const address = Buffer.from(bs58.decode(output.address))
+    assert(address.length === 33, `Invalid estimated output address ${output.address}`)
+    return Buffer.concat([address, Buffer.from(codec.compactUnsignedIntCodec.encodeU256(output.attoAlphAmount))])
+  }))
+  assert(data.length <= MAX_PAYLOAD_SIZE, 'Invalid estimated outputs frame size')
+  return [{ p1: 3, p2: 0, data }]
+}
+
+// Each NFT is encoded as the collection contract id followed by the sub-contract path
+export function encodeNftMetadata(nfts: NftMetadata[]): Frame[] {
+  assert(nfts.length <= MAX_NFTS, 'Too many NFTs')
+  return nfts.map((nft) => {
+    const collectionId = Buffer.from(nft.collectionId, 'hex')
+    const path = Buffer.from(nft.path, 'hex')
+    assert(collectionId.length === 32, `Invalid collection id ${nft.collectionId}`)
+    assert(path.length > 0 && path.length <= 32, `Invalid sub-contract path ${nft.path}`)
+    return { p1: 4, p2: 0, data: Buffer.concat([collectionId, path]) }
+  })
+}
+
+// The paths of the other device addresses that the inputs are spent from
+export function encodeInputPaths(paths: string[]): Frame[] {
+  if (paths.length === 0) return []
+  assert(paths.length <= MAX_INPUT_PATHS, 'Too many input paths')
+  return [{ p1: 5, p2: 0, data: Buffer.concat(paths.map((path) => serializePath(path))) }]
+}
+
+// The first message frame starts with the path and the 2-byte message size
+export function encodeMessage(path: string, message: Buffer): Frame[] {
+  const size = Buffer.alloc(2)
+  size.writeUint16BE(message.length)
+  const prefix = Buffer.concat([serializePath(path), size])
+  const firstFrameLength = Math.min(MAX_PAYLOAD_SIZE - prefix.length, message.length)
+  const frames: Frame[] = [{ p1: 0, p2: 0, data: Buffer.concat([prefix, message.slice(0, firstFrameLength)]) }]
+  for (let fromIndex = firstFrameLength; fromIndex < message.length; fromIndex += MAX_PAYLOAD_SIZE) {
+    frames.push({ p1: 1, p2: 0, data: message.slice(fromIndex, fromIndex + MAX_PAYLOAD_SIZE) })
+  }
+  return frames
+}
+
 export function encodeUnsignedTx(path: string, unsignedTx: Buffer): Frame[] {
-  const encodedPath = serializePath(path)
-  const firstFrameTxLength = MAX_PAYLOAD_SIZE - 20;
+  return encodeTxFrames(serializePath(path), 0, unsignedTx)
+}
+
+// A preview is encoded like a tx to sign, but its first frame is flagged so that the device returns the tx id instead of a signature
+export function encodePreviewTx(path: string, unsignedTx: Buffer): Frame[] {
+  return encodeTxFrames(serializePath(path), 3, unsignedTx)
+}
+
+// The first frame of a sweep tx starts with the number of paths and the paths that the tx spends from
+export function encodeSweepTx(paths: string[], unsignedTx: Buffer): Frame[] {
+  assert(paths.length > 0 && paths.length <= MAX_SWEEP_PATHS, 'Invalid number of sweep paths')
+  const encodedPaths = Buffer.concat([Buffer.from([paths.length]), ...paths.map((path) => serializePath(path))])
+  return encodeTxFrames(encodedPaths, 2, unsignedTx)
+}
+
+// The first frame of a chained tx starts with the index of the tx and the number of txs of the session
+export function encodeChainedTx(index: number, count: number, path: string, unsignedTx: Buffer): Frame[] {
+  assert(count >= 2 && count <= MAX_CHAINED_TXS && index < count, 'Invalid chained tx index')
+  const prefix = Buffer.concat([Buffer.from([index, count]), serializePath(path)])
+  return encodeTxFrames(prefix, 5, unsignedTx)
+}
+
+function encodeTxFrames(prefix: Buffer, firstFrameP2: number, unsignedTx: Buffer): Frame[] {
+  const firstFrameTxLength = MAX_PAYLOAD_SIZE - prefix.length;
   if (firstFrameTxLength >= unsignedTx.length) {
-    return [{ p1: 1, p2: 0, data: Buffer.concat([encodedPath, unsignedTx]) }]
+    return [{ p1: 1, p2: firstFrameP2, data: Buffer.concat([prefix, unsignedTx]) }]
   }
 
   const firstFrameTxData = unsignedTx.slice(0, firstFrameTxLength)
-  const frames: Frame[] = [{ p1: 1, p2: 0, data: Buffer.concat([encodedPath, firstFrameTxData]) }]
+  const frames: Frame[] = [{ p1: 1, p2: firstFrameP2, data: Buffer.concat([prefix, firstFrameTxData]) }]
   let fromIndex = firstFrameTxLength
   while (fromIndex < unsignedTx.length) {
     const remain = unsignedTx.length - fromIndex
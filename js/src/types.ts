@@ -1,8 +1,25 @@
 export const MAX_TOKEN_SIZE = 5
 export const MAX_TOKEN_SYMBOL_LENGTH = 12
 export const TOKEN_METADATA_SIZE = 46
+export const MAX_SWEEP_PATHS = 4
+export const MAX_CHAINED_TXS = 3
 // The maximum payload size is 255: https://github.com/LedgerHQ/ledger-live/blob/develop/libs/ledgerjs/packages/hw-transport/src/Transport.ts#L261
 export const MAX_PAYLOAD_SIZE = 255
+export const MAX_ESTIMATED_OUTPUTS = 4
+export const MAX_NFTS = 2
+export const MAX_INPUT_PATHS = 6
+
+// An output of a script tx simulated by the node, the device displays it as an unverified estimate
+export interface EstimatedOutput {
+  address: string,
+  attoAlphAmount: bigint
+}
+
+// An NFT that is a sub-contract of its collection contract, the path is usually the encoded item index
+export interface NftMetadata {
+  collectionId: string,
+  path: string
+}
 
 export interface TokenMetadata {
   version: number,
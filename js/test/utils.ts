@@ -28,56 +28,88 @@ export enum OutputType {
   Base,
   Multisig,
   Token,
-  BaseAndToken,
+  VerifiedToken,
   MultisigAndToken
 }
 
+// The warnings are reviewed before the output, a repeated address adds a reference to the
+// previous output and a Schnorr address adds its address type
+export interface ReviewedOutput {
+  type: OutputType
+  warnings?: number
+  isRepeatedAddress?: boolean
+  isSchnorrAddress?: boolean
+}
+
+function toReviewedOutput(output: OutputType | ReviewedOutput): ReviewedOutput {
+  return typeof output === 'number' ? { type: output } : output
+}
+
 const NanospClickTable = new Map([
-  [OutputType.Base, 3],
-  [OutputType.Multisig, 5],
-  [OutputType.Token, 6],
-  [OutputType.BaseAndToken, 6],
-  [OutputType.MultisigAndToken, 8],
+  [OutputType.Base, 5],
+  [OutputType.Multisig, 8],
+  [OutputType.Token, 8],
+  [OutputType.VerifiedToken, 7],
+  [OutputType.MultisigAndToken, 11],
 ])
 
 const StaxClickTable = new Map([
-  [OutputType.Base, 1],
-  [OutputType.Multisig, 2],
+  [OutputType.Base, 2],
+  [OutputType.Multisig, 3],
   [OutputType.Token, 2],
-  [OutputType.BaseAndToken, 2],
-  [OutputType.MultisigAndToken, 2],
+  [OutputType.VerifiedToken, 2],
+  [OutputType.MultisigAndToken, 3],
 ])
 
 const FlexClickTable = new Map([
-  [OutputType.Base, 1],
-  [OutputType.Multisig, 2],
-  [OutputType.Token, 2],
-  [OutputType.BaseAndToken, 2],
-  [OutputType.MultisigAndToken, 3],
+  [OutputType.Base, 2],
+  [OutputType.Multisig, 3],
+  [OutputType.Token, 3],
+  [OutputType.VerifiedToken, 3],
+  [OutputType.MultisigAndToken, 4],
 ])
 
-function getOutputClickSize(outputType: OutputType) {
+function getOutputClickSize(output: ReviewedOutput) {
   const model = getModel()
   switch (model) {
     case 'nanosp':
-    case 'nanox': return NanospClickTable.get(outputType)!
-    case 'stax': return StaxClickTable.get(outputType)!
-    case 'flex': return FlexClickTable.get(outputType)!
+    case 'nanox': {
+      // The extra fields have a page each on Nano devices
+      const extraFields = (output.isRepeatedAddress ? 1 : 0) + (output.isSchnorrAddress ? 1 : 0)
+      return NanospClickTable.get(output.type)! + extraFields
+    }
+    case 'stax': return StaxClickTable.get(output.type)!
+    case 'flex': return FlexClickTable.get(output.type)!
     default: throw new Error(`Unknown model ${model}`)
   }
 }
 
-async function click(outputs: OutputType[], hasExternalInputs: boolean) {
+async function click(
+  outputs: ReviewedOutput[],
+  hasExternalInputs: boolean,
+  hasHighFees: boolean,
+  isChainedTx: boolean = false
+) {
   await sleep(1000);
   if (hasExternalInputs) {
     await clickAndApprove(1)
   }
 
   for (let index = 0; index < outputs.length; index += 1) {
+    for (let warning = 0; warning < (outputs[index].warnings ?? 0); warning += 1) {
+      await clickAndApprove(1)
+    }
+    if (index === 0 && isChainedTx) {
+      await clickAndApprove(2) // the position of the chained tx
+    }
     await clickAndApprove(getOutputClickSize(outputs[index]))
   }
 
-  await clickAndApprove(1) // fees
+  if (hasHighFees) {
+    await clickAndApprove(1)
+  }
+  // The position of a chained tx is displayed again before the total sent, from group and fees
+  await clickAndApprove(isChainedTx ? 4 : 3)
 }
 
 interface Position {
@@ -137,42 +169,113 @@ export async function staxFlexApproveOnce() {
   }
 }
 
-async function touch(outputs: OutputType[], hasExternalInputs: boolean) {
+async function touch(
+  outputs: ReviewedOutput[],
+  hasExternalInputs: boolean,
+  hasHighFees: boolean,
+  isChainedTx: boolean = false
+) {
   await sleep(3000);
   if (hasExternalInputs) {
     await staxFlexApproveOnce()
   }
 
-  _touch(1) // the first review page
-  await sleep(1000)
-
+  // The warnings of the first output are reviewed before the first review page
   for (let index = 0; index < outputs.length; index += 1) {
+    for (let warning = 0; warning < (outputs[index].warnings ?? 0); warning += 1) {
+      await staxFlexApproveOnce()
+    }
+    if (index === 0) {
+      await _touch(1) // the first review page
+      if (isChainedTx) {
+        await _touch(1) // the position of the chained tx
+      }
+    }
     await _touch(getOutputClickSize(outputs[index]))
   }
 
+  if (hasHighFees) {
+    await staxFlexApproveOnce()
+  }
   await _touch(1, true) // fees
 }
 
-export async function approveTx(outputs: OutputType[], hasExternalInputs: boolean = false) {
+export async function approveTx(
+  outputs: (OutputType | ReviewedOutput)[],
+  hasExternalInputs: boolean = false,
+  hasHighFees: boolean = false
+) {
   if (!needToAutoApprove()) return
   await sleep(2000)
+  await approveNetworkWarning()
   const isSelfTransfer = outputs.length === 0 && !hasExternalInputs
   if (isSelfTransfer) {
     if (isStaxOrFlex()) {
-      await _touch(2, true)
+      await _touch(3, true)
     } else {
-      await clickAndApprove(2)
+      await clickAndApprove(3)
     }
     return
   }
 
+  const reviewedOutputs = outputs.map(toReviewedOutput)
+  if (isStaxOrFlex()) {
+    await touch(reviewedOutputs, hasExternalInputs, hasHighFees)
+  } else {
+    await click(reviewedOutputs, hasExternalInputs, hasHighFees)
+  }
+}
+
+// Each tx of a chained session is reviewed with its position in the session, and all the txs
+// are approved together after the last one
+export async function approveChainedTxs(txs: ReviewedOutput[][]) {
+  if (!needToAutoApprove()) return
+  for (const outputs of txs) {
+    await sleep(2000)
+    await approveNetworkWarning()
+    if (isStaxOrFlex()) {
+      await touch(outputs, false, false, true)
+    } else {
+      await click(outputs, false, false, true)
+    }
+  }
+  await sleep(1000)
+  if (isStaxOrFlex()) {
+    await _touch(2, true)
+  } else {
+    await clickAndApprove(3) // the number of txs and the total sent
+  }
+}
+
+// The deploy script approves ALPH to the contract, so the review starts with the contract
+// interaction section and the approved amount before the final page of the blind signing
+export async function approveScriptTx() {
+  if (!needToAutoApprove()) return
+  await sleep(2000)
+  await approveNetworkWarning()
   if (isStaxOrFlex()) {
-    await touch(outputs, hasExternalInputs)
+    await staxFlexAcceptRisk()
+    await _touch(1) // the first review page
+    await staxFlexApproveOnce() // the contract interaction section
+    await _touch(1) // the approved amount
+    await _touch(1, true) // fees
   } else {
-    await click(outputs, hasExternalInputs)
+    await clickAndApprove(1) // the contract interaction section
+    await clickAndApprove(2) // the approved amount
+    await clickAndApprove(3) // from group and fees
   }
 }
 
+// The test txs are for the devnet, so the non-mainnet network warning is shown before the review
+async function approveNetworkWarning() {
+  if (isStaxOrFlex()) {
+    await staxFlexApproveOnce()
+  } else {
+    await clickAndApprove(2)
+  }
+  await sleep(1000)
+}
+
 export async function approveHash() {
   if (!needToAutoApprove()) return
   if (isStaxOrFlex()) {
@@ -3,7 +3,7 @@ import { ALPH_TOKEN_ID, Address, DUST_AMOUNT, NodeProvider, ONE_ALPH, binToHex,
 import { getSigner, mintToken, transfer } from '@alephium/web3-test'
 import { PrivateKeyWallet } from '@alephium/web3-wallet'
 import blake from 'blakejs'
-import { approveAddress, approveHash, approveTx, createTransport, enableBlindSigning, getRandomInt, isStaxOrFlex, needToAutoApprove, OutputType, skipBlindSigningWarning, staxFlexAcceptRisk, staxFlexApproveOnce } from './utils'
+import { approveAddress, approveChainedTxs, approveHash, approveScriptTx, approveTx, createTransport, enableBlindSigning, getRandomInt, needToAutoApprove, OutputType, skipBlindSigningWarning, staxFlexApproveOnce } from './utils'
 import { TokenMetadata } from '../src/types'
 import { randomBytes } from 'crypto'
 import { merkleTokens, tokenMerkleProofs } from '../src/merkle'
@@ -33,6 +33,11 @@ describe('ledger wallet', () => {
     return BigInt(balances.balance)
   }
 
+  // The cross-group warning is reviewed before the first output to another group
+  function crossGroupWarnings(fromGroup: number, address: Address): number {
+    return groupOfAddress(address) === fromGroup ? 0 : 1
+  }
+
   it('should get version', async () => {
     const transport = await createTransport()
     const app = new AlephiumApp(transport)
@@ -75,9 +80,15 @@ describe('ledger wallet', () => {
   it('should get public key for group for Schnorr signature', async () => {
     const transport = await createTransport()
     const app = new AlephiumApp(transport)
+    // Schnorr keys are derived from the `1'` account of the path
+    const schnorrPath = `m/44'/1234'/1'/0/` + pathIndex
     for (let group = 0; group < GROUP_NUM; group++) {
-      await expect(app.getAccount(path, group, 'bip340-schnorr')).rejects.toThrow('BIP340-Schnorr is not supported yet')
+      const [account, hdIndex] = await app.getAccount(schnorrPath, group, 'bip340-schnorr')
+      expect(hdIndex >= pathIndex).toBe(true)
+      expect(groupOfAddress(account.address)).toBe(group)
+      expect(account.keyType).toBe('bip340-schnorr')
     }
+    await expect(app.getAccount(path, 0, 'bip340-schnorr')).rejects.toThrow(`The key type bip340-schnorr does not match the path ${path}`)
     await app.close()
   })
 
@@ -103,17 +114,18 @@ describe('ledger wallet', () => {
     const [testAccount] = await app.getAccount(path)
     await transferToAddress(testAccount.address)
 
+    const toAddress = '1BmVCLrjttchZMW7i6df7mTdCKzHpy38bgDbVL1GqV6P7'
     const buildTxResult = await nodeProvider.transactions.postTransactionsBuild({
       fromPublicKey: testAccount.publicKey,
       destinations: [
         {
-          address: '1BmVCLrjttchZMW7i6df7mTdCKzHpy38bgDbVL1GqV6P7',
+          address: toAddress,
           attoAlphAmount: (ONE_ALPH * 2n).toString(),
         }
       ]
     })
 
-    approveTx([OutputType.Base])
+    approveTx([{ type: OutputType.Base, warnings: crossGroupWarnings(testAccount.group, toAddress) }])
     const signature = await app.signUnsignedTx(path, Buffer.from(buildTxResult.unsignedTx, 'hex'))
     expect(transactionVerifySignature(buildTxResult.txId, testAccount.publicKey, signature)).toBe(true)
 
@@ -148,7 +160,12 @@ describe('ledger wallet', () => {
       ]
     })
 
-    approveTx(Array(2).fill(OutputType.Base))
+    const warnings0 = crossGroupWarnings(testAccount.group, '1BmVCLrjttchZMW7i6df7mTdCKzHpy38bgDbVL1GqV6P7')
+    const warnings1 = crossGroupWarnings(testAccount.group, '1F1fu6GjuN9yUVRFVcgQKWwiTg8RMzKFv1BZFDwFcfWJq')
+    approveTx([
+      { type: OutputType.Base, warnings: warnings0 },
+      { type: OutputType.Base, warnings: warnings0 === 0 ? warnings1 : 0 }
+    ])
     const signature = await app.signUnsignedTx(path, Buffer.from(buildTxResult.unsignedTx, 'hex'))
     expect(transactionVerifySignature(buildTxResult.txId, testAccount.publicKey, signature)).toBe(true)
 
@@ -180,7 +197,7 @@ describe('ledger wallet', () => {
       ]
     })
 
-    approveTx([OutputType.Multisig]);
+    approveTx([{ type: OutputType.Multisig, warnings: crossGroupWarnings(testAccount.group, multiSigAddress) }]);
     const signature = await app.signUnsignedTx(path, Buffer.from(buildTxResult.unsignedTx, 'hex'))
     expect(transactionVerifySignature(buildTxResult.txId, testAccount.publicKey, signature)).toBe(true)
 
@@ -220,7 +237,11 @@ describe('ledger wallet', () => {
       ]
     })
 
-    approveTx([OutputType.MultisigAndToken, OutputType.Multisig])
+    // The raw amount of the minted token is large, so the unknown token warning is shown
+    approveTx([
+      { type: OutputType.MultisigAndToken, warnings: crossGroupWarnings(testAccount.group, multiSigAddress) + 1 },
+      { type: OutputType.Multisig, isRepeatedAddress: true }
+    ])
     const signature = await app.signUnsignedTx(path, Buffer.from(buildTxResult.unsignedTx, 'hex'))
     expect(transactionVerifySignature(buildTxResult.txId, testAccount.publicKey, signature)).toBe(true)
 
@@ -309,7 +330,11 @@ describe('ledger wallet', () => {
     }
     const encodedUnsignedTx = codec.unsignedTxCodec.encodeApiUnsignedTx(unsignedTx)
 
-    approveTx(Array(5).fill(OutputType.BaseAndToken))
+    approveTx(selectedTokens.map((_, index) => {
+      return index === 0
+        ? { type: OutputType.VerifiedToken, warnings: crossGroupWarnings(testAccount.group, newAccount.address) }
+        : { type: OutputType.VerifiedToken, isRepeatedAddress: true }
+    }))
     const signature = await app.signUnsignedTx(path, Buffer.from(encodedUnsignedTx))
     const txId = blake.blake2b(encodedUnsignedTx, undefined, 32)
     expect(transactionVerifySignature(binToHex(txId), testAccount.publicKey, signature)).toBe(true)
@@ -397,17 +422,18 @@ describe('ledger wallet', () => {
       await transferToAddress(testAccount.address, ONE_ALPH)
     }
 
+    const toAddress = '1BmVCLrjttchZMW7i6df7mTdCKzHpy38bgDbVL1GqV6P7'
     const buildTxResult = await nodeProvider.transactions.postTransactionsBuild({
       fromPublicKey: testAccount.publicKey,
       destinations: [
         {
-          address: '1BmVCLrjttchZMW7i6df7mTdCKzHpy38bgDbVL1GqV6P7',
+          address: toAddress,
           attoAlphAmount: (ONE_ALPH * 19n).toString(),
         }
       ]
     })
 
-    approveTx([OutputType.Base])
+    approveTx([{ type: OutputType.Base, warnings: crossGroupWarnings(testAccount.group, toAddress) }])
     const signature = await app.signUnsignedTx(path, Buffer.from(buildTxResult.unsignedTx, 'hex'))
     expect(transactionVerifySignature(buildTxResult.txId, testAccount.publicKey, signature)).toBe(true)
 
@@ -422,23 +448,64 @@ describe('ledger wallet', () => {
     await app.close()
   }, 120000)
 
-  function getAccount(groupIndex: number): { account: PrivateKeyWallet, unlockScript: string } {
+  it('should sign chained txs', async () => {
+    const transport = await createTransport()
+    const app = new AlephiumApp(transport)
+    const [testAccount] = await app.getAccount(path)
+    await transferToAddress(testAccount.address)
+    await transferToAddress(testAccount.address)
+
+    // Each tx spends its own utxo, so that both txs can be submitted
+    const utxos = await nodeProvider.addresses.getAddressesAddressUtxos(testAccount.address)
+    expect(utxos.utxos.length).toEqual(2)
+    const toAddress = '1BmVCLrjttchZMW7i6df7mTdCKzHpy38bgDbVL1GqV6P7'
+    const buildTxResults = await Promise.all(utxos.utxos.map((utxo) => {
+      return nodeProvider.transactions.postTransactionsBuild({
+        fromPublicKey: testAccount.publicKey,
+        destinations: [{ address: toAddress, attoAlphAmount: (ONE_ALPH * 2n).toString() }],
+        utxos: [utxo.ref]
+      })
+    }))
+
+    const warnings = crossGroupWarnings(testAccount.group, toAddress)
+    approveChainedTxs(buildTxResults.map(() => [{ type: OutputType.Base, warnings }]))
+    const signatures = await app.signChainedTxs(buildTxResults.map((result) => {
+      return { path, unsignedTx: Buffer.from(result.unsignedTx, 'hex') }
+    }))
+    expect(signatures.length).toEqual(2)
+
+    for (let index = 0; index < buildTxResults.length; index += 1) {
+      const buildTxResult = buildTxResults[index]
+      expect(transactionVerifySignature(buildTxResult.txId, testAccount.publicKey, signatures[index])).toBe(true)
+      const submitResult = await nodeProvider.transactions.postTransactionsSubmit({
+        unsignedTx: buildTxResult.unsignedTx,
+        signature: signatures[index]
+      })
+      await waitForTxConfirmation(submitResult.txId, 1, 1000)
+    }
+    const balance = await getALPHBalance(testAccount.address)
+    expect(balance < (ONE_ALPH * 16n)).toEqual(true)
+
+    await app.close()
+  }, 120000)
+
+  function getAccount(groupIndex: number): { account: PrivateKeyWallet, unlockScript: string, isSchnorr: boolean } {
     const useDefaultKeyType = Math.random() >= 0.5
     if (useDefaultKeyType) {
       const account = PrivateKeyWallet.Random(groupIndex)
-      return { account, unlockScript: '00' + account.publicKey }
+      return { account, unlockScript: '00' + account.publicKey, isSchnorr: false }
     }
 
     const account = PrivateKeyWallet.Random(groupIndex, nodeProvider, 'bip340-schnorr')
     const unlockScript = '02' + `0101000000000458144020${account.publicKey}8685` + '00'
-    return { account, unlockScript }
+    return { account, unlockScript, isSchnorr: true }
   }
 
   it('should test external inputs', async () => {
     const transport = await createTransport()
     const app = new AlephiumApp(transport)
     const [testAccount] = await app.getAccount(path)
-    const { account: newAccount, unlockScript: unlockScript0 } = getAccount(testAccount.group)
+    const { account: newAccount, unlockScript: unlockScript0, isSchnorr } = getAccount(testAccount.group)
     for (let i = 0; i < 2; i += 1) {
       await transferToAddress(testAccount.address, ONE_ALPH)
       await transferToAddress(newAccount.address, ONE_ALPH)
@@ -482,7 +549,9 @@ describe('ledger wallet', () => {
       unsignedTx: binToHex(txBytes)
     })
 
-    approveTx([OutputType.Base], true)
+    // The input script reveals the Schnorr address of the output, and the fees are high
+    // compared to the amount sent
+    approveTx([{ type: OutputType.Base, isSchnorrAddress: isSchnorr }], true, true)
     const signature1 = await app.signUnsignedTx(path, Buffer.from(txBytes))
     expect(transactionVerifySignature(signResult0.txId, testAccount.publicKey, signature1)).toBe(true)
 
@@ -543,11 +612,7 @@ describe('ledger wallet', () => {
 
     await enableBlindSigning()
     if (needToAutoApprove()) {
-      if (isStaxOrFlex()) {
-        staxFlexAcceptRisk().then(() => approveTx([]))
-      } else {
-        approveTx([])
-      }
+      approveScriptTx()
     } else {
       // waiting for blind signing setting to be enabled
       await sleep(20000)
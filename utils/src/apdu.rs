@@ -0,0 +1,156 @@
+use crate::{HARDENED_OFFSET, PATH_LENGTH};
+
+// The protocol constants of the SignTx instruction, see `docs/apdu.md`
+pub const CLA: u8 = 0x80;
+pub const INS_SIGN_TX: u8 = 0x03;
+pub const MAX_PAYLOAD_SIZE: usize = 255;
+pub const ENCODED_PATH_LENGTH: usize = 4 * PATH_LENGTH;
+
+#[derive(Debug, PartialEq)]
+pub struct Frame<'a> {
+    pub p1: u8,
+    pub p2: u8,
+    // The prefix is only set for the first tx frame, which starts with the path
+    pub prefix: &'a [u8],
+    pub data: &'a [u8],
+}
+
+impl Frame<'_> {
+    // The token metadata frame of a tx without token metadata
+    pub const NO_TOKEN_METADATA: Frame<'static> = Frame {
+        p1: 0,
+        p2: 0,
+        prefix: &[],
+        data: &[0],
+    };
+
+    pub fn payload_len(&self) -> usize {
+        self.prefix.len() + self.data.len()
+    }
+}
+
+// Split an unsigned tx into the tx frames expected by the app, the first frame starts with the
+// encoded path and the following frames contain at most `MAX_PAYLOAD_SIZE` bytes of the tx
+pub struct TxFrames<'a> {
+    encoded_path: &'a [u8],
+    unsigned_tx: &'a [u8],
+    offset: Option<usize>,
+}
+
+impl<'a> TxFrames<'a> {
+    pub fn new(encoded_path: &'a [u8; ENCODED_PATH_LENGTH], unsigned_tx: &'a [u8]) -> Self {
+        Self {
+            encoded_path,
+            unsigned_tx,
+            offset: None,
+        }
+    }
+}
+
+impl<'a> Iterator for TxFrames<'a> {
+    type Item = Frame<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = match self.offset {
+            Some(offset) => offset,
+            None => {
+                let size = core::cmp::min(
+                    MAX_PAYLOAD_SIZE - self.encoded_path.len(),
+                    self.unsigned_tx.len(),
+                );
+                self.offset = Some(size);
+                return Some(Frame {
+                    p1: 1,
+                    p2: 0,
+                    prefix: self.encoded_path,
+                    data: &self.unsigned_tx[..size],
+                });
+            }
+        };
+        if offset >= self.unsigned_tx.len() {
+            return None;
+        }
+        let size = core::cmp::min(MAX_PAYLOAD_SIZE, self.unsigned_tx.len() - offset);
+        self.offset = Some(offset + size);
+        let data = &self.unsigned_tx[offset..(offset + size)];
+        Some(Frame {
+            p1: 1,
+            p2: 1,
+            prefix: &[],
+            data,
+        })
+    }
+}
+
+// Parse a path like `m/44'/1234'/0'/0/0` into the encoded path of the first tx frame
+pub fn encode_path(path: &str) -> Option<[u8; ENCODED_PATH_LENGTH]> {
+    let path = path.strip_prefix("m/").unwrap_or(path);
+    let mut encoded = [0u8; ENCODED_PATH_LENGTH];
+    let mut size = 0;
+    for node in path.split('/') {
+        if size == PATH_LENGTH {
+            return None;
+        }
+        let value = match node.strip_suffix('\'') {
+            Some(index) => index.parse::<u32>().ok()?.checked_add(HARDENED_OFFSET)?,
+            None => node.parse::<u32>().ok()?,
+        };
+        encoded[(size * 4)..(size * 4 + 4)].copy_from_slice(&value.to_be_bytes());
+        size += 1;
+    }
+    if size == PATH_LENGTH {
+        Some(encoded)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{encode_path, Frame, TxFrames, ENCODED_PATH_LENGTH, MAX_PAYLOAD_SIZE};
+    use crate::deserialize_path;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_encode_path() {
+        let encoded = encode_path("m/44'/1234'/0'/0/3").unwrap();
+        let mut path = [0u32; 5];
+        deserialize_path(&encoded, &mut path, ()).unwrap();
+        assert_eq!(path, [0x8000002c, 0x800004d2, 0x80000000, 0, 3]);
+
+        assert_eq!(encode_path("44'/1234'/0'/0/3"), Some(encoded));
+        assert_eq!(encode_path("m/44'/1234'/0'/0"), None);
+        assert_eq!(encode_path("m/44'/1234'/0'/0/0/0"), None);
+        assert_eq!(encode_path("m/44'/1234'/0'/0/a"), None);
+    }
+
+    #[test]
+    fn test_tx_frames() {
+        let encoded_path = [1u8; ENCODED_PATH_LENGTH];
+        let first_frame_size = MAX_PAYLOAD_SIZE - ENCODED_PATH_LENGTH;
+
+        let small_tx = [2u8; 10];
+        let frames: Vec<Frame> = TxFrames::new(&encoded_path, &small_tx).collect();
+        assert_eq!(frames.len(), 1);
+        assert_eq!((frames[0].p1, frames[0].p2), (1, 0));
+        assert_eq!(frames[0].prefix, encoded_path.as_slice());
+        assert_eq!(frames[0].data, small_tx.as_slice());
+
+        let large_tx: Vec<u8> = (0..(first_frame_size + MAX_PAYLOAD_SIZE + 1))
+            .map(|index| index as u8)
+            .collect();
+        let frames: Vec<Frame> = TxFrames::new(&encoded_path, &large_tx).collect();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].payload_len(), MAX_PAYLOAD_SIZE);
+        assert_eq!((frames[1].p1, frames[1].p2), (1, 1));
+        assert_eq!(frames[1].payload_len(), MAX_PAYLOAD_SIZE);
+        assert_eq!(frames[2].data, &large_tx[(large_tx.len() - 1)..]);
+        let data: Vec<u8> = frames
+            .iter()
+            .flat_map(|frame| frame.data.to_vec())
+            .collect();
+        assert_eq!(data, large_tx);
+    }
+}
@@ -47,11 +47,60 @@ pub fn base58_encode<'a>(input: &[u8], output: &'a mut [u8]) -> Option<&'a [u8]>
     base58_encode_inputs(&[input], output)
 }
 
+pub fn base58_decode<'a>(input: &[u8], output: &'a mut [u8]) -> Option<&'a [u8]> {
+    let mut index = 0;
+    for &char in input {
+        let mut carry = ALPHABET.iter().position(|&c| c == char)?;
+        for byte in &mut output[..index] {
+            carry += (*byte as usize) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            if index == output.len() {
+                return None;
+            }
+            output[index] = carry as u8;
+            index += 1;
+            carry >>= 8;
+        }
+    }
+
+    for _ in input.iter().take_while(|&&char| char == ALPHABET[0]) {
+        if index == output.len() {
+            return None;
+        }
+        output[index] = 0;
+        index += 1;
+    }
+
+    output[..index].reverse();
+    Some(&output[..index])
+}
+
+// The prefixes of the addresses that are the base58 encoding of the prefix and a 32-byte hash
+const P2PKH_PREFIX: u8 = 0x00;
+const P2SH_PREFIX: u8 = 0x02;
+const P2C_PREFIX: u8 = 0x03;
+
+// Decode a P2PKH, P2SH or P2C address provided by the host, the addresses have no checksum,
+// so the prefix and the length of the hash are checked
+pub fn decode_address(address: &[u8]) -> Option<(u8, [u8; 32])> {
+    let mut output = [0u8; 33];
+    let bytes = base58_decode(address, &mut output)?;
+    if bytes.len() != 33 || !matches!(bytes[0], P2PKH_PREFIX | P2SH_PREFIX | P2C_PREFIX) {
+        return None;
+    }
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&bytes[1..]);
+    Some((bytes[0], hash))
+}
+
 #[cfg(test)]
 mod tests {
     extern crate alloc;
     extern crate std;
-    use super::base58_encode;
+    use super::{base58_decode, base58_encode, decode_address};
     use crate::{base58::base58_encode_inputs, types::u256::tests::hex_to_bytes};
     use alloc::str::from_utf8;
     use std::vec;
@@ -113,6 +162,68 @@ mod tests {
             assert!(result.is_some());
             let expected = from_utf8(result.unwrap()).unwrap();
             assert_eq!(*expected, *str);
+
+            let mut decoded = [0; 150];
+            assert_eq!(base58_decode(str.as_bytes(), &mut decoded), Some(bytes));
+        }
+    }
+
+    #[test]
+    fn test_base58_decode_invalid() {
+        let mut output = [0; 33];
+        // Invalid chars
+        assert!(base58_decode(b"0", &mut output).is_none());
+        assert!(base58_decode(b"ZiCaO", &mut output).is_none());
+        assert!(base58_decode(b"Zi Ca", &mut output).is_none());
+        assert!(base58_decode(b"\xff", &mut output).is_none());
+        // The output is too small
+        assert_eq!(
+            base58_decode(b"1ZiCa", &mut output[..4]),
+            Some(&b"\0abc"[..])
+        );
+        assert!(base58_decode(b"1ZiCa", &mut output[..3]).is_none());
+        assert!(base58_decode(b"11", &mut output[..1]).is_none());
+    }
+
+    #[test]
+    fn test_decode_address() {
+        let cases = [
+            (
+                "1DkrQMni2h8KYpvY8t7dECshL66gwnxiR5uD2Udxps6og",
+                "00bd8813e79baa5fa1874ca8b70877d1b044e220ecd34a60eca3ba15fc36b378e7",
+            ),
+            (
+                "je9CrJD444xMSGDA2yr1XMvugoHuTc6pfYEaPYrKLuYa",
+                "02798e9e137aec7c2d59d9655b4ffa640f301f628bf7c365083bb255f6aa5f89ef",
+            ),
+        ];
+        for (address, hex) in cases {
+            let bytes = hex_to_bytes(hex).unwrap();
+            let (prefix, hash) = decode_address(address.as_bytes()).unwrap();
+            assert_eq!(prefix, bytes[0]);
+            assert_eq!(hash.as_slice(), &bytes[1..]);
+        }
+
+        let mut output = [0; 64];
+        let encode = |bytes: &[u8], output: &mut [u8; 64]| {
+            std::string::String::from(from_utf8(base58_encode(bytes, output).unwrap()).unwrap())
+        };
+        let p2c_address = encode(&[[3u8].as_slice(), &[7; 32]].concat(), &mut output);
+        assert_eq!(decode_address(p2c_address.as_bytes()), Some((3, [7; 32])));
+
+        // Multi-sig and groupless addresses, unknown prefixes and wrong hash lengths
+        let multi_sig_address =
+            "2jjvDdgGjC6X9HHMCMHohVfvp1uf3LHQrAGWaufR17P7AFwtxodTxSktqKc2urNEtaoUCy5xXpBUwpZ8QM8Q3e5BYCx";
+        assert!(decode_address(multi_sig_address.as_bytes()).is_none());
+        for prefix in [1u8, 4, 5, 6] {
+            let address = encode(&[[prefix].as_slice(), &[7; 32]].concat(), &mut output);
+            assert!(decode_address(address.as_bytes()).is_none());
         }
+        let short_address = encode(&[[0u8].as_slice(), &[7; 31]].concat(), &mut output);
+        assert!(decode_address(short_address.as_bytes()).is_none());
+        let long_address = encode(&[[0u8].as_slice(), &[7; 33]].concat(), &mut output);
+        assert!(decode_address(long_address.as_bytes()).is_none());
+        assert!(decode_address(b"").is_none());
+        assert!(decode_address(b"1DkrQMni2h8KYpvY8t7dECshL66gwnxiR5uD2Udxps6o0").is_none());
     }
 }
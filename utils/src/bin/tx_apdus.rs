@@ -0,0 +1,63 @@
+// Print the SignTx APDU commands of an unsigned tx, one hex encoded command per line, so that a tx
+// can be sent to a device manually or replayed by integration tests
+//
+// Usage: tx_apdus <path> [unsigned-tx-hex]
+// The unsigned tx is read from stdin if it is not provided as an argument
+use std::io::Read;
+use std::process::exit;
+
+use utils::apdu::{encode_path, Frame, TxFrames, CLA, INS_SIGN_TX};
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    exit(1)
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&hex[index..index + 2], 16).ok())
+        .collect()
+}
+
+fn print_apdu(frame: &Frame) {
+    let mut apdu = vec![
+        CLA,
+        INS_SIGN_TX,
+        frame.p1,
+        frame.p2,
+        frame.payload_len() as u8,
+    ];
+    apdu.extend_from_slice(frame.prefix);
+    apdu.extend_from_slice(frame.data);
+    let hex: String = apdu.iter().map(|byte| format!("{:02x}", byte)).collect();
+    println!("{}", hex);
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 || args.len() > 3 {
+        fail("Usage: tx_apdus <path> [unsigned-tx-hex]");
+    }
+    let encoded_path = encode_path(&args[1]).unwrap_or_else(|| fail("Invalid path"));
+    let tx_hex = match args.get(2) {
+        Some(hex) => hex.clone(),
+        None => {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .unwrap_or_else(|_| fail("Failed to read stdin"));
+            input
+        }
+    };
+    let unsigned_tx = from_hex(tx_hex.trim()).unwrap_or_else(|| fail("Invalid unsigned tx hex"));
+    if unsigned_tx.is_empty() {
+        fail("Empty unsigned tx");
+    }
+
+    print_apdu(&Frame::NO_TOKEN_METADATA);
+    TxFrames::new(&encoded_path, &unsigned_tx).for_each(|frame| print_apdu(&frame));
+}
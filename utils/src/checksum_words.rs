@@ -0,0 +1,97 @@
+// A fixed list of 256 short words, the host wallet uses the same list so that users can compare
+// the checksum words of an address instead of comparing all the base58 characters
+pub const WORDLIST: [&str; 256] = [
+    "acid", "acorn", "actor", "agent", "alarm", "album", "alien", "alley", "amber", "angle",
+    "ankle", "apple", "apron", "arena", "armor", "arrow", "atlas", "attic", "award", "axis",
+    "bacon", "badge", "bagel", "baker", "bamboo", "banjo", "barn", "basil", "beach", "beard",
+    "bench", "berry", "bison", "blade", "bloom", "board", "boat", "bonus", "boots", "brain",
+    "bread", "brick", "bridge", "broom", "bucket", "cabin", "cactus", "camel", "candle", "canoe",
+    "canyon", "cargo", "carpet", "castle", "cedar", "chain", "chalk", "cheese", "cherry", "chess",
+    "chief", "cider", "cloud", "clover", "coast", "cobra", "comet", "coral", "cotton", "crane",
+    "crown", "daisy", "dance", "delta", "denim", "desert", "diary", "dingo", "donkey", "dragon",
+    "drum", "duck", "eagle", "earth", "easel", "echo", "elbow", "elder", "ember", "engine",
+    "fabric", "falcon", "fence", "ferry", "fiber", "field", "flame", "flute", "foam", "forest",
+    "fossil", "frost", "fruit", "galaxy", "garden", "garlic", "gecko", "giant", "ginger", "globe",
+    "goat", "grape", "gravel", "guitar", "hammer", "harbor", "hawk", "hazel", "heart", "hedge",
+    "helmet", "heron", "honey", "horse", "hotel", "igloo", "island", "ivory", "jacket", "jaguar",
+    "jelly", "jewel", "jigsaw", "juice", "jungle", "kayak", "kettle", "kiwi", "knight", "koala",
+    "ladder", "lagoon", "lamp", "laser", "lava", "lemon", "lily", "lion", "lizard", "locket",
+    "lotus", "lunar", "magnet", "mango", "maple", "marble", "meadow", "melon", "mint", "mirror",
+    "moose", "motor", "muffin", "museum", "napkin", "nectar", "needle", "nest", "noodle", "oasis",
+    "ocean", "olive", "onion", "opera", "orange", "orbit", "otter", "oyster", "paddle", "palace",
+    "panda", "paper", "parrot", "peach", "pearl", "pebble", "pepper", "piano", "pillow", "pilot",
+    "pine", "planet", "plum", "pony", "potato", "puzzle", "quail", "quartz", "quilt", "rabbit",
+    "radar", "radio", "raven", "reef", "ribbon", "river", "robot", "rocket", "ruby", "saddle",
+    "salmon", "sand", "satin", "scarf", "shark", "shell", "silver", "skate", "snow", "socks",
+    "solar", "spider", "spoon", "squid", "stone", "storm", "sugar", "summit", "swan", "table",
+    "tiger", "timber", "toast", "tomato", "torch", "tower", "tulip", "tunnel", "turtle", "valley",
+    "velvet", "violin", "wagon", "walnut", "water", "whale", "wheat", "willow", "window", "wolf",
+    "wool", "yacht", "yarn", "yogurt", "zebra", "zinc",
+];
+pub const CHECKSUM_WORD_SIZE: usize = 3;
+pub const MAX_WORD_LENGTH: usize = 6;
+pub const MAX_CHECKSUM_WORDS_LENGTH: usize = CHECKSUM_WORD_SIZE * (MAX_WORD_LENGTH + 1) - 1;
+
+// Each word is selected by one byte of the hash, the hash is the blake2b hash of the base58 address
+pub fn checksum_words(hash: &[u8]) -> Option<[&'static str; CHECKSUM_WORD_SIZE]> {
+    if hash.len() < CHECKSUM_WORD_SIZE {
+        return None;
+    }
+    let mut words = [""; CHECKSUM_WORD_SIZE];
+    for (word, byte) in words.iter_mut().zip(hash) {
+        *word = WORDLIST[*byte as usize];
+    }
+    Some(words)
+}
+
+// Write the checksum words separated by spaces
+pub fn write_checksum_words<'a>(hash: &[u8], output: &'a mut [u8]) -> Option<&'a [u8]> {
+    let words = checksum_words(hash)?;
+    let mut index = 0;
+    for (i, word) in words.iter().enumerate() {
+        if i != 0 {
+            *output.get_mut(index)? = b' ';
+            index += 1;
+        }
+        let to_index = index + word.len();
+        output
+            .get_mut(index..to_index)?
+            .copy_from_slice(word.as_bytes());
+        index = to_index;
+    }
+    Some(&output[..index])
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::str::from_utf8;
+
+    #[test]
+    fn test_wordlist() {
+        for (i, word) in WORDLIST.iter().enumerate() {
+            assert!(!word.is_empty() && word.len() <= MAX_WORD_LENGTH);
+            assert!(word.bytes().all(|c| c.is_ascii_lowercase()));
+            assert!(WORDLIST[(i + 1)..].iter().all(|w| w != word));
+        }
+    }
+
+    #[test]
+    fn test_checksum_words() {
+        assert_eq!(checksum_words(&[0, 1]), None);
+        assert_eq!(
+            checksum_words(&[0, 1, 255, 3]),
+            Some([WORDLIST[0], WORDLIST[1], WORDLIST[255]])
+        );
+
+        let mut output = [0u8; MAX_CHECKSUM_WORDS_LENGTH];
+        let result = write_checksum_words(&[2, 0, 255], &mut output).unwrap();
+        let expected = std::format!("{} {} {}", WORDLIST[2], WORDLIST[0], WORDLIST[255]);
+        assert_eq!(from_utf8(result).unwrap(), expected);
+
+        let mut output = [0u8; 8];
+        assert_eq!(write_checksum_words(&[2, 0, 255], &mut output), None);
+    }
+}
@@ -159,7 +159,7 @@ impl<T: Default + RawDecoder> RawDecoder for Option<T> {
         }
 
         if self.is_none() {
-            let byte = buffer.consume_byte().unwrap();
+            let byte = buffer.consume_byte().ok_or(DecodeError::InternalError)?;
             if byte == 0 {
                 return Ok(DecodeStage::COMPLETE);
             } else if byte == 1 {
@@ -0,0 +1,72 @@
+pub const MAX_DISPLAYED_ADDRESSES: usize = 4;
+const HASH_SIZE: usize = 32;
+
+// The addresses that have been fully displayed in the current review, so that later outputs to
+// the same address can refer to the output that displayed it. The addresses are recorded by
+// their hash, and the oldest entry is replaced when the memory is full.
+pub struct DisplayedAddresses {
+    entries: [Option<([u8; HASH_SIZE], u16)>; MAX_DISPLAYED_ADDRESSES],
+    next_index: usize,
+}
+
+impl Default for DisplayedAddresses {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DisplayedAddresses {
+    pub const fn new() -> Self {
+        Self {
+            entries: [None; MAX_DISPLAYED_ADDRESSES],
+            next_index: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.entries = [None; MAX_DISPLAYED_ADDRESSES];
+        self.next_index = 0;
+    }
+
+    // Get the index of the output that displayed the address
+    pub fn get(&self, address_hash: &[u8; HASH_SIZE]) -> Option<u16> {
+        self.entries
+            .iter()
+            .flatten()
+            .find(|(hash, _)| hash == address_hash)
+            .map(|(_, output_index)| *output_index)
+    }
+
+    pub fn add(&mut self, address_hash: &[u8; HASH_SIZE], output_index: u16) {
+        self.entries[self.next_index] = Some((*address_hash, output_index));
+        self.next_index = (self.next_index + 1) % MAX_DISPLAYED_ADDRESSES;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DisplayedAddresses, MAX_DISPLAYED_ADDRESSES};
+
+    #[test]
+    fn test_displayed_addresses() {
+        let mut displayed_addresses = DisplayedAddresses::new();
+        assert_eq!(displayed_addresses.get(&[0; 32]), None);
+
+        displayed_addresses.add(&[0; 32], 1);
+        displayed_addresses.add(&[1; 32], 2);
+        assert_eq!(displayed_addresses.get(&[0; 32]), Some(1));
+        assert_eq!(displayed_addresses.get(&[1; 32]), Some(2));
+        assert_eq!(displayed_addresses.get(&[2; 32]), None);
+
+        // The oldest address is replaced when the memory is full
+        for index in 0..MAX_DISPLAYED_ADDRESSES {
+            displayed_addresses.add(&[index as u8 + 2; 32], index as u16 + 3);
+        }
+        assert_eq!(displayed_addresses.get(&[0; 32]), None);
+        assert_eq!(displayed_addresses.get(&[1; 32]), None);
+        assert_eq!(displayed_addresses.get(&[2; 32]), Some(3));
+
+        displayed_addresses.reset();
+        assert_eq!(displayed_addresses.get(&[2; 32]), None);
+    }
+}
@@ -0,0 +1,99 @@
+use crate::types::{compact_integer::decode_length, U256};
+
+pub const MAX_ESTIMATED_OUTPUTS: usize = 4;
+const HASH_SIZE: usize = 32;
+
+#[cfg_attr(test, derive(Debug))]
+#[derive(PartialEq, Clone)]
+pub struct EstimatedOutput {
+    pub address_prefix: u8,
+    pub address_hash: [u8; HASH_SIZE],
+    pub amount: U256,
+}
+
+// The outputs of a script tx that the host simulated with a full node. The device cannot
+// verify them, so they are only displayed as estimates after the verified tx details.
+// Each output is encoded as the address prefix, the 32-byte address hash and the encoded
+// ALPH amount.
+#[derive(Default)]
+pub struct EstimatedOutputs {
+    outputs: [Option<EstimatedOutput>; MAX_ESTIMATED_OUTPUTS],
+}
+
+impl EstimatedOutputs {
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let mut result = Self::default();
+        let mut remain = data;
+        let mut index = 0;
+        while !remain.is_empty() {
+            if index == MAX_ESTIMATED_OUTPUTS || remain.len() < 1 + HASH_SIZE + 1 {
+                return None;
+            }
+            let address_prefix = remain[0];
+            let address_hash = remain[1..(1 + HASH_SIZE)].try_into().unwrap();
+            let amount_bytes = &remain[(1 + HASH_SIZE)..];
+            let amount_length = decode_length(amount_bytes[0]);
+            if amount_length > amount_bytes.len() || amount_length > 33 {
+                return None;
+            }
+            result.outputs[index] = Some(EstimatedOutput {
+                address_prefix,
+                address_hash,
+                amount: U256::from_encoded_bytes(&amount_bytes[..amount_length]),
+            });
+            remain = &amount_bytes[amount_length..];
+            index += 1;
+        }
+        Some(result)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.outputs[0].is_none()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &EstimatedOutput> {
+        self.outputs.iter().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{EstimatedOutputs, MAX_ESTIMATED_OUTPUTS};
+    use crate::types::U256;
+    use std::vec::Vec;
+
+    fn encode_output(prefix: u8, hash_byte: u8, amount: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.push(prefix);
+        bytes.extend_from_slice(&[hash_byte; 32]);
+        bytes.extend_from_slice(amount);
+        bytes
+    }
+
+    #[test]
+    fn test_decode_estimated_outputs() {
+        let amount = [0xc4, 0x14, 0xd1, 0x12, 0x0d, 0x7b, 0x16, 0x00, 0x00];
+        let mut data = encode_output(0, 1, &amount);
+        data.extend(encode_output(2, 2, &[0x01]));
+        let outputs = EstimatedOutputs::decode(&data).unwrap();
+        let outputs: Vec<_> = outputs.iter().collect();
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0].address_prefix, 0);
+        assert_eq!(outputs[0].address_hash, [1; 32]);
+        assert_eq!(outputs[0].amount, U256::from_encoded_bytes(&amount));
+        assert_eq!(outputs[1].address_prefix, 2);
+        assert_eq!(outputs[1].amount, U256::from_encoded_bytes(&[0x01]));
+
+        assert!(EstimatedOutputs::decode(&[]).unwrap().is_empty());
+        // The amount is truncated
+        assert!(EstimatedOutputs::decode(&encode_output(0, 1, &amount[..4])).is_none());
+        assert!(EstimatedOutputs::decode(&encode_output(0, 1, &[])).is_none());
+
+        let too_many: Vec<u8> = (0..=MAX_ESTIMATED_OUTPUTS)
+            .flat_map(|index| encode_output(0, index as u8, &[0x01]))
+            .collect();
+        assert!(EstimatedOutputs::decode(&too_many).is_none());
+    }
+}
@@ -0,0 +1,315 @@
+// The state of the app across instructions:
+// * `Idle`: no transaction is in progress, all the instructions are accepted
+// * `Uploading`: the token metadata of a transaction is being uploaded
+// * `Reviewing`: the transaction is being decoded and reviewed
+// * `Signing`: the transaction is approved and the sweep signatures are being returned
+// * `UploadingMessage`: a message to sign is being uploaded
+// The discriminants are returned by `GetSignTxStatus`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(u8)]
+pub enum InstructionState {
+    #[default]
+    Idle = 0,
+    Uploading = 1,
+    Reviewing = 2,
+    Signing = 3,
+    UploadingMessage = 4,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    // Instructions that do not change the state, e.g. `GetVersion`
+    Info,
+    // Instructions that require user interaction outside of a transaction, e.g. `GetPubKey`
+    Standalone,
+    // The first token metadata frame, which starts a new transaction
+    FirstTokenMetadata,
+    // The following token metadata and token proof frames
+    TokenMetadata,
+    // The outputs of a script tx simulated by the host
+    EstimatedOutputs,
+    // The collection contract and sub-contract path of an NFT
+    NftMetadata,
+    // The paths of the other device addresses that the tx inputs are spent from
+    InputPaths,
+    // The first tx data frame
+    FirstTxChunk,
+    // The following tx data frames, and the end frame of a tx of an unrecognized format
+    TxChunk,
+    // The signature of the next path of an approved sweep transaction
+    NextSignature,
+    // The first message frame, which starts a new message
+    FirstMessageChunk,
+    // The following message frames
+    MessageChunk,
+    // Abort the transaction or the message in progress
+    Abort,
+}
+
+impl Command {
+    // Get the command of the `p1` and `p2` of a `SignTx` instruction
+    pub fn from_sign_tx(p1: u8, p2: u8) -> Option<Command> {
+        match (p1, p2) {
+            (0, 0) => Some(Command::FirstTokenMetadata),
+            (0, 1) | (0, 2) => Some(Command::TokenMetadata),
+            (3, 0) => Some(Command::EstimatedOutputs),
+            (4, 0) => Some(Command::NftMetadata),
+            (5, 0) => Some(Command::InputPaths),
+            (1, 0) | (1, 2) | (1, 3) | (1, 5) => Some(Command::FirstTxChunk),
+            (1, 1) | (1, 4) => Some(Command::TxChunk),
+            (2, _) => Some(Command::NextSignature),
+            _ => None,
+        }
+    }
+
+    // Get the command of the `p1` of a `SignMessage` instruction
+    pub fn from_sign_message(p1: u8) -> Option<Command> {
+        match p1 {
+            0 => Some(Command::FirstMessageChunk),
+            1 => Some(Command::MessageChunk),
+            _ => None,
+        }
+    }
+}
+
+impl InstructionState {
+    // Get the state after receiving the command, or `None` if the command is out of order.
+    // A new transaction can be started in any state, which discards the current transaction.
+    pub fn next(self, command: Command) -> Option<InstructionState> {
+        match (self, command) {
+            (state, Command::Info) => Some(state),
+            (Self::Idle, Command::Standalone) => Some(Self::Idle),
+            (_, Command::FirstTokenMetadata) => Some(Self::Uploading),
+            (Self::Uploading, Command::TokenMetadata) => Some(Self::Uploading),
+            (Self::Uploading, Command::EstimatedOutputs) => Some(Self::Uploading),
+            (Self::Uploading, Command::NftMetadata) => Some(Self::Uploading),
+            (Self::Uploading, Command::InputPaths) => Some(Self::Uploading),
+            (Self::Uploading, Command::FirstTxChunk) => Some(Self::Reviewing),
+            (Self::Reviewing, Command::TxChunk) => Some(Self::Reviewing),
+            (Self::Signing, Command::NextSignature) => Some(Self::Signing),
+            (Self::Idle | Self::UploadingMessage, Command::FirstMessageChunk) => {
+                Some(Self::UploadingMessage)
+            }
+            (Self::UploadingMessage, Command::MessageChunk) => Some(Self::UploadingMessage),
+            (_, Command::Abort) => Some(Self::Idle),
+            _ => None,
+        }
+    }
+
+    // Get the state after the transaction is approved and signed with the first path
+    pub fn approved(self, has_pending_signatures: bool) -> InstructionState {
+        assert!(self == Self::Reviewing);
+        if has_pending_signatures {
+            Self::Signing
+        } else {
+            Self::Idle
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Command, InstructionState};
+
+    const STATES: [InstructionState; 5] = [
+        InstructionState::Idle,
+        InstructionState::Uploading,
+        InstructionState::Reviewing,
+        InstructionState::Signing,
+        InstructionState::UploadingMessage,
+    ];
+
+    const COMMANDS: [Command; 13] = [
+        Command::Info,
+        Command::Standalone,
+        Command::FirstTokenMetadata,
+        Command::TokenMetadata,
+        Command::EstimatedOutputs,
+        Command::NftMetadata,
+        Command::InputPaths,
+        Command::FirstTxChunk,
+        Command::TxChunk,
+        Command::NextSignature,
+        Command::FirstMessageChunk,
+        Command::MessageChunk,
+        Command::Abort,
+    ];
+
+    #[test]
+    fn test_all_transitions() {
+        use Command::*;
+        use InstructionState::*;
+
+        // The expected next state of each state and command, in the order of `COMMANDS`
+        let expected: [[Option<InstructionState>; 13]; 5] = [
+            [
+                Some(Idle),
+                Some(Idle),
+                Some(Uploading),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(UploadingMessage),
+                None,
+                Some(Idle),
+            ],
+            [
+                Some(Uploading),
+                None,
+                Some(Uploading),
+                Some(Uploading),
+                Some(Uploading),
+                Some(Uploading),
+                Some(Uploading),
+                Some(Reviewing),
+                None,
+                None,
+                None,
+                None,
+                Some(Idle),
+            ],
+            [
+                Some(Reviewing),
+                None,
+                Some(Uploading),
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(Reviewing),
+                None,
+                None,
+                None,
+                Some(Idle),
+            ],
+            [
+                Some(Signing),
+                None,
+                Some(Uploading),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(Signing),
+                None,
+                None,
+                Some(Idle),
+            ],
+            [
+                Some(UploadingMessage),
+                None,
+                Some(Uploading),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(UploadingMessage),
+                Some(UploadingMessage),
+                Some(Idle),
+            ],
+        ];
+        for (state, expected_states) in STATES.iter().zip(expected.iter()) {
+            for (command, expected_state) in COMMANDS.iter().zip(expected_states.iter()) {
+                assert_eq!(state.next(*command), *expected_state);
+            }
+        }
+
+        assert_eq!(Reviewing.approved(true), Signing);
+        assert_eq!(Reviewing.approved(false), Idle);
+        assert_eq!(Command::from_sign_tx(0, 0), Some(FirstTokenMetadata));
+        assert_eq!(Command::from_sign_tx(0, 2), Some(TokenMetadata));
+        assert_eq!(Command::from_sign_tx(3, 0), Some(EstimatedOutputs));
+        assert_eq!(Command::from_sign_tx(4, 0), Some(NftMetadata));
+        assert_eq!(Command::from_sign_tx(5, 0), Some(InputPaths));
+        assert_eq!(Command::from_sign_tx(1, 2), Some(FirstTxChunk));
+        assert_eq!(Command::from_sign_tx(1, 3), Some(FirstTxChunk));
+        assert_eq!(Command::from_sign_tx(1, 1), Some(TxChunk));
+        assert_eq!(Command::from_sign_tx(2, 3), Some(NextSignature));
+        assert_eq!(Command::from_sign_tx(1, 4), Some(TxChunk));
+        assert_eq!(Command::from_sign_tx(1, 5), Some(FirstTxChunk));
+        assert_eq!(Command::from_sign_tx(1, 6), None);
+        assert_eq!(Command::from_sign_tx(3, 1), None);
+        assert_eq!(Command::from_sign_tx(4, 1), None);
+        assert_eq!(Command::from_sign_tx(5, 1), None);
+        assert_eq!(Command::from_sign_message(0), Some(FirstMessageChunk));
+        assert_eq!(Command::from_sign_message(1), Some(MessageChunk));
+        assert_eq!(Command::from_sign_message(2), None);
+    }
+
+    #[test]
+    fn test_abort() {
+        let sign_tx = [
+            Command::FirstTokenMetadata,
+            Command::FirstTxChunk,
+            Command::Abort,
+        ];
+        let state = sign_tx
+            .iter()
+            .try_fold(InstructionState::Idle, |state, command| {
+                state.next(*command)
+            });
+        assert_eq!(state, Some(InstructionState::Idle));
+        // The frames of the aborted transaction are rejected
+        assert_eq!(InstructionState::Idle.next(Command::TxChunk), None);
+    }
+
+    #[test]
+    fn test_sign_tx_flows() {
+        let run = |commands: &[Command]| {
+            commands
+                .iter()
+                .try_fold(InstructionState::Idle, |state, command| {
+                    state.next(*command)
+                })
+        };
+
+        let sign_tx = [
+            Command::FirstTokenMetadata,
+            Command::TokenMetadata,
+            Command::FirstTxChunk,
+            Command::TxChunk,
+            Command::TxChunk,
+        ];
+        assert_eq!(run(&sign_tx), Some(InstructionState::Reviewing));
+
+        // Tx frames cannot be sent before the token metadata frame
+        assert_eq!(run(&sign_tx[2..]), None);
+        // Other instructions cannot interrupt a transaction
+        assert_eq!(
+            run(&[Command::FirstTokenMetadata, Command::Standalone]),
+            None
+        );
+        assert_eq!(
+            run(&[Command::FirstTokenMetadata, Command::Info]),
+            Some(InstructionState::Uploading)
+        );
+        // A transaction discards the message in progress
+        assert_eq!(
+            run(&[Command::FirstMessageChunk, Command::FirstTokenMetadata]),
+            Some(InstructionState::Uploading)
+        );
+        assert_eq!(
+            run(&[Command::FirstTokenMetadata, Command::FirstMessageChunk]),
+            None
+        );
+        // A new transaction discards the current one
+        assert_eq!(
+            run(&[
+                Command::FirstTokenMetadata,
+                Command::FirstTxChunk,
+                Command::FirstTokenMetadata
+            ]),
+            Some(InstructionState::Uploading)
+        );
+    }
+}
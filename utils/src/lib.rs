@@ -1,10 +1,20 @@
 #![no_std]
 
+pub mod apdu;
 pub mod base58;
 pub mod buffer;
+pub mod checksum_words;
 pub mod decode;
+pub mod displayed_addresses;
+pub mod estimated_outputs;
+pub mod instruction_state;
+pub mod message;
+pub mod public_key_hash_cache;
+pub mod sub_contract;
 #[cfg(test)]
 pub mod temp_data;
+pub mod token_totals;
+pub mod typed_data;
 pub mod types;
 
 use core::char;
@@ -12,6 +22,13 @@ use core::num::Wrapping;
 #[cfg(test)]
 pub use temp_data::TempData;
 
+pub const PATH_LENGTH: usize = 5;
+pub const HARDENED_OFFSET: u32 = 0x80000000;
+const COIN_TYPE_PATH_INDEX: usize = 1;
+const CHANGE_PATH_INDEX: usize = 3;
+// Paths with the testnet coin type `m/44'/1'/...` are accepted, the keys are flagged on the device
+const TESTNET_COIN_TYPE: u32 = HARDENED_OFFSET + 1;
+
 #[inline]
 pub fn to_hex<const N: usize>(m: &[u8]) -> Option<[u8; N]> {
     if 2 * m.len() > N {
@@ -57,7 +74,12 @@ pub fn xor_bytes(data: i32) -> u8 {
     bytes[0] ^ bytes[1] ^ bytes[2] ^ bytes[3]
 }
 
-pub const PATH_LENGTH: usize = 5;
+// The group of a lockup script is derived from the script hint of its hash, the script hint
+// of a multisig lockup script is the script hint of its first public key hash
+pub fn get_script_group(lockup_script_hash: &[u8], group_num: u8) -> u8 {
+    let script_hint = djb_hash(lockup_script_hash) | 1;
+    xor_bytes(script_hint) % group_num
+}
 
 // Deserialize a path from a byte array
 pub fn deserialize_path<T>(data: &[u8], path: &mut [u32; 5], t: T) -> Result<(), T> {
@@ -71,9 +93,133 @@ pub fn deserialize_path<T>(data: &[u8], path: &mut [u32; 5], t: T) -> Result<(),
         path[i] = u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap());
     }
 
+    if !has_valid_hardening(path) {
+        return Err(t);
+    }
     Ok(())
 }
 
+// Deserialize a path of at least `min_depth` nodes, returns the depth of the path
+pub fn deserialize_partial_path<T>(
+    data: &[u8],
+    min_depth: usize,
+    path: &mut [u32; 5],
+    t: T,
+) -> Result<usize, T> {
+    let depth = data.len() / 4;
+    if !data.len().is_multiple_of(4) || depth < min_depth || depth > PATH_LENGTH {
+        return Err(t);
+    }
+    for (node, bytes) in path.iter_mut().zip(data.chunks(4)) {
+        *node = u32::from_be_bytes(bytes.try_into().unwrap());
+    }
+    if !has_valid_hardening(&path[..depth]) {
+        return Err(t);
+    }
+    Ok(depth)
+}
+
+// The key of a path shorter than an address path, i.e. an account or a chain key, reveals all
+// the addresses below it
+pub fn is_above_address_level(path: &[u32]) -> bool {
+    path.len() < PATH_LENGTH
+}
+
+// The purpose, coin type and account nodes are hardened, the change and index nodes are not
+const HARDENED_DEPTH: usize = 3;
+
+fn has_valid_hardening(path: &[u32]) -> bool {
+    path.iter()
+        .enumerate()
+        .all(|(i, node)| (node & HARDENED_OFFSET != 0) == (i < HARDENED_DEPTH))
+}
+
+const PURPOSE: u32 = HARDENED_OFFSET + 44;
+const COIN_TYPE: u32 = HARDENED_OFFSET + 1234;
+const MAX_CHANGE: u32 = 1;
+
+// Whether the path follows `m/44'/1234'/account'/change/index` with the testnet coin type allowed,
+// the device warns before deriving keys from other paths
+pub fn is_standard_path(path: &[u32]) -> bool {
+    path.first() == Some(&PURPOSE)
+        && matches!(
+            path.get(COIN_TYPE_PATH_INDEX),
+            Some(&COIN_TYPE) | Some(&TESTNET_COIN_TYPE)
+        )
+        && path
+            .get(CHANGE_PATH_INDEX)
+            .is_none_or(|&change| change <= MAX_CHANGE)
+}
+
+// The maximum length of a formatted path, i.e. `m` followed by 5 hardened nodes of 10 digits
+pub const MAX_PATH_STR_LENGTH: usize = 1 + PATH_LENGTH * 12;
+
+// Format a path as `m/44'/1234'/0'/0/0`, returns the length of the formatted path
+pub fn format_path(path: &[u32], output: &mut [u8]) -> Option<usize> {
+    let mut length = 0;
+    write_bytes(output, &mut length, b"m")?;
+    for &node in path {
+        let mut digits = [0u8; 10];
+        let mut from_index = digits.len();
+        let mut value = node & !HARDENED_OFFSET;
+        loop {
+            from_index -= 1;
+            digits[from_index] = b'0' + (value % 10) as u8;
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+        write_bytes(output, &mut length, b"/")?;
+        write_bytes(output, &mut length, &digits[from_index..])?;
+        if node & HARDENED_OFFSET != 0 {
+            write_bytes(output, &mut length, b"'")?;
+        }
+    }
+    Some(length)
+}
+
+fn write_bytes(output: &mut [u8], length: &mut usize, bytes: &[u8]) -> Option<()> {
+    output
+        .get_mut(*length..*length + bytes.len())?
+        .copy_from_slice(bytes);
+    *length += bytes.len();
+    Some(())
+}
+
+// The account node of the path selects the key type, following the Alephium wallet convention:
+// `m/44'/1234'/0'/0/index` for default secp256k1 keys and `m/44'/1234'/1'/0/index` for Schnorr keys
+const KEY_TYPE_PATH_INDEX: usize = 2;
+
+#[cfg_attr(test, derive(Debug))]
+#[derive(Clone, Copy, PartialEq)]
+pub enum KeyType {
+    Default,
+    Schnorr,
+}
+
+impl KeyType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyType::Default => "Default",
+            KeyType::Schnorr => "Schnorr",
+        }
+    }
+}
+
+// Get the key type from a path, the key type node has to be hardened
+pub fn get_key_type<T>(path: &[u32], t: T) -> Result<KeyType, T> {
+    match path.get(KEY_TYPE_PATH_INDEX) {
+        Some(&HARDENED_OFFSET) => Ok(KeyType::Default),
+        Some(&node) if node == HARDENED_OFFSET + 1 => Ok(KeyType::Schnorr),
+        _ => Err(t),
+    }
+}
+
+pub fn is_testnet_path(path: &[u32]) -> bool {
+    path.get(COIN_TYPE_PATH_INDEX) == Some(&TESTNET_COIN_TYPE)
+}
+
 // If the group number is 0, the target group must also be 0, meaning all groups are allowed
 // If the group number is not 0, the target group must be less than the group number
 pub fn check_group<T>(group_num: u8, target_group: u8, t: T) -> Result<(), T> {
@@ -129,6 +275,20 @@ mod tests {
         assert_eq!(input, &x.as_str()[0..16]);
     }
 
+    #[test]
+    fn test_get_script_group() {
+        let hash = [0u8; 32];
+        let script_hint = djb_hash(&hash) | 1;
+        assert_eq!(get_script_group(&hash, 4), xor_bytes(script_hint) % 4);
+        assert_eq!(get_script_group(&hash, 1), 0);
+        // The groups are evenly distributed
+        let mut counts = [0usize; 4];
+        for byte in 0..=255u8 {
+            counts[get_script_group(&[byte; 32], 4) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&count| count > 32));
+    }
+
     #[test]
     fn test_djb_hash() {
         assert_eq!(djb_hash(&[]), 5381);
@@ -157,12 +317,132 @@ mod tests {
     fn test_deserialize_path() {
         assert_eq!(deserialize_path(&[], &mut [0; 5], ()), Err(()));
         assert_eq!(deserialize_path(&[0; 19], &mut [0; 5], ()), Err(()));
-        assert_eq!(deserialize_path(&[0; 20], &mut [0; 5], ()), Ok(()));
+        assert_eq!(deserialize_path(&[0; 20], &mut [0; 5], ()), Err(()));
         assert_eq!(deserialize_path(&[0; 21], &mut [0; 5], ()), Err(()));
 
+        let encode = |path: &[u32]| {
+            path.iter()
+                .flat_map(|node| node.to_be_bytes())
+                .collect::<Vec<_>>()
+        };
+        let mut path = [0; 5];
+        let data = encode(&[0x8000002c, 0x800004d2, 0x80000000, 0, 0x01010101]);
+        assert_eq!(deserialize_path(&data, &mut path, ()), Ok(()));
+        assert_eq!(&path, &[0x8000002c, 0x800004d2, 0x80000000, 0, 0x01010101]);
+
+        // The first three nodes are hardened, the last two are not
+        let data = encode(&[0x8000002c, 0x800004d2, 0, 0, 0]);
+        assert_eq!(deserialize_path(&data, &mut path, ()), Err(()));
+        let data = encode(&[0x8000002c, 0x800004d2, 0x80000000, 0, 0x80000000]);
+        assert_eq!(deserialize_path(&data, &mut path, ()), Err(()));
+    }
+
+    #[test]
+    fn test_deserialize_partial_path() {
+        let mut path = [0; 5];
+        assert_eq!(deserialize_partial_path(&[], 1, &mut path, ()), Err(()));
+        assert_eq!(deserialize_partial_path(&[0; 8], 3, &mut path, ()), Err(()));
+        assert_eq!(
+            deserialize_partial_path(&[0; 13], 3, &mut path, ()),
+            Err(())
+        );
+        assert_eq!(
+            deserialize_partial_path(&[0; 24], 3, &mut path, ()),
+            Err(())
+        );
+        assert_eq!(
+            deserialize_partial_path(&[0; 20], 3, &mut path, ()),
+            Err(())
+        );
+
+        let data = [[0x80, 0, 0, 0x2c], [0x80, 0, 0x04, 0xd2], [0x80, 0, 0, 0]].concat();
+        assert_eq!(deserialize_partial_path(&data, 3, &mut path, ()), Ok(3));
+        assert_eq!(&path[..3], &[0x8000002c, 0x800004d2, 0x80000000]);
+
+        let data = [&data[..], &[0, 0, 0, 1], &[0, 0, 0, 2]].concat();
+        assert_eq!(deserialize_partial_path(&data, 3, &mut path, ()), Ok(5));
+        let data = [&data[..16], &[0x80, 0, 0, 2]].concat();
+        assert_eq!(deserialize_partial_path(&data, 3, &mut path, ()), Err(()));
+    }
+
+    #[test]
+    fn test_is_above_address_level() {
         let mut path = [0; 5];
-        let _ = deserialize_path(&[1; 20], &mut path, ());
-        assert_eq!(&path, &[0x01010101; 5]);
+        let data = [
+            [0x80, 0, 0, 0x2c],
+            [0x80, 0, 0x04, 0xd2],
+            [0x80, 0, 0, 0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 7],
+        ]
+        .concat();
+        // The account and the chain keys are above the address level, the address key is not
+        for (length, expected) in [(12, true), (16, true), (20, false)] {
+            let depth = deserialize_partial_path(&data[..length], 3, &mut path, ()).unwrap();
+            assert_eq!(is_above_address_level(&path[..depth]), expected);
+        }
+    }
+
+    #[test]
+    fn test_is_standard_path() {
+        assert!(is_standard_path(&[
+            0x8000002c, 0x800004d2, 0x80000000, 0, 7
+        ]));
+        assert!(is_standard_path(&[
+            0x8000002c, 0x800004d2, 0x80000001, 1, 7
+        ]));
+        assert!(is_standard_path(&[
+            0x8000002c, 0x80000001, 0x80000000, 0, 0
+        ]));
+        assert!(is_standard_path(&[0x8000002c, 0x800004d2, 0x80000000]));
+        assert!(!is_standard_path(&[
+            0x8000002b, 0x800004d2, 0x80000000, 0, 0
+        ]));
+        assert!(!is_standard_path(&[
+            0x8000002c, 0x8000003c, 0x80000000, 0, 0
+        ]));
+        assert!(!is_standard_path(&[
+            0x8000002c, 0x800004d2, 0x80000000, 2, 0
+        ]));
+        assert!(!is_standard_path(&[]));
+    }
+
+    #[test]
+    fn test_format_path() {
+        let format = |path: &[u32]| {
+            let mut output = [0u8; MAX_PATH_STR_LENGTH];
+            let length = format_path(path, &mut output).unwrap();
+            String::from_utf8(output[..length].to_vec()).unwrap()
+        };
+        assert_eq!(
+            format(&[0x8000002c, 0x800004d2, 0x80000000, 0, 12]),
+            "m/44'/1234'/0'/0/12"
+        );
+        assert_eq!(format(&[]), "m");
+        assert_eq!(format(&[u32::MAX; 5]).len(), MAX_PATH_STR_LENGTH);
+        assert_eq!(format(&[0x7fffffff]), "m/2147483647");
+        assert_eq!(format_path(&[0x8000002c, 1], &mut [0u8; 6]), None);
+    }
+
+    #[test]
+    fn test_get_key_type() {
+        let path = |key_type: u32| [0x8000002c, 0x800004d2, key_type, 0, 0];
+        assert_eq!(get_key_type(&path(0x80000000), ()), Ok(KeyType::Default));
+        assert_eq!(get_key_type(&path(0x80000001), ()), Ok(KeyType::Schnorr));
+        assert_eq!(get_key_type(&path(0), ()), Err(()));
+        assert_eq!(get_key_type(&path(1), ()), Err(()));
+        assert_eq!(get_key_type(&path(0x80000002), ()), Err(()));
+        assert_eq!(get_key_type(&[0x8000002c, 0x800004d2], ()), Err(()));
+    }
+
+    #[test]
+    fn test_is_testnet_path() {
+        assert!(is_testnet_path(&[0x8000002c, 0x80000001, 0x80000000, 0, 0]));
+        assert!(!is_testnet_path(&[
+            0x8000002c, 0x800004d2, 0x80000000, 0, 0
+        ]));
+        assert!(!is_testnet_path(&[0x8000002c, 1, 0x80000000, 0, 0]));
+        assert!(!is_testnet_path(&[0x8000002c]));
     }
 
     #[test]
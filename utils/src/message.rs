@@ -0,0 +1,47 @@
+use core::str::from_utf8;
+
+// The prefix of signed messages, the signed hash is the Blake2b hash of the prefixed message
+pub const MESSAGE_PREFIX: &[u8] = b"Alephium Signed Message: ";
+// Longer messages are reviewed by their hash
+pub const MAX_DISPLAYED_MESSAGE_SIZE: usize = 256;
+
+// Get the text of a message if it can be displayed, the message has to be short enough,
+// valid UTF-8 and without control characters, otherwise only the hash is displayed
+pub fn displayable_message(message: &[u8]) -> Option<&str> {
+    if message.is_empty() || message.len() > MAX_DISPLAYED_MESSAGE_SIZE {
+        return None;
+    }
+    let text = from_utf8(message).ok()?;
+    if text.chars().any(|c| c.is_control()) {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{displayable_message, MAX_DISPLAYED_MESSAGE_SIZE};
+
+    #[test]
+    fn test_displayable_message() {
+        assert_eq!(displayable_message(b"Hello"), Some("Hello"));
+        assert_eq!(
+            displayable_message("Bonjour à tous".as_bytes()),
+            Some("Bonjour à tous")
+        );
+        assert_eq!(
+            displayable_message(&[b'a'; MAX_DISPLAYED_MESSAGE_SIZE]).map(|s| s.len()),
+            Some(MAX_DISPLAYED_MESSAGE_SIZE)
+        );
+
+        assert_eq!(displayable_message(b""), None);
+        assert_eq!(
+            displayable_message(&[b'a'; MAX_DISPLAYED_MESSAGE_SIZE + 1]),
+            None
+        );
+        assert_eq!(displayable_message(&[0xff, 0xfe]), None);
+        assert_eq!(displayable_message(b"line\nbreak"), None);
+        assert_eq!(displayable_message(b"\x1b[31mred"), None);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::types::PublicKey;
+
+pub const CACHE_SIZE: usize = 4;
+const HASH_SIZE: usize = 32;
+
+struct Entry {
+    prefix: u64,
+    public_key: [u8; PublicKey::ENCODED_LENGTH],
+    hash: [u8; HASH_SIZE],
+}
+
+impl Entry {
+    // The key prefix skips the parity byte of the compressed public key
+    fn prefix_of(public_key: &[u8; PublicKey::ENCODED_LENGTH]) -> u64 {
+        u64::from_be_bytes(public_key[1..9].try_into().unwrap())
+    }
+
+    fn matches(&self, prefix: u64, public_key: &[u8; PublicKey::ENCODED_LENGTH]) -> bool {
+        self.prefix == prefix && &self.public_key == public_key
+    }
+}
+
+// A small cache of the hashes of the input public keys, so that the hash of a public key that is
+// used by many inputs is only computed once. The entries are looked up by the key prefix, and the
+// full key is compared before a cached hash is returned.
+pub struct PublicKeyHashCache {
+    entries: [Option<Entry>; CACHE_SIZE],
+    next_index: usize,
+}
+
+impl Default for PublicKeyHashCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PublicKeyHashCache {
+    pub const fn new() -> Self {
+        Self {
+            entries: [None, None, None, None],
+            next_index: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.entries = [None, None, None, None];
+        self.next_index = 0;
+    }
+
+    // Get the cached hash of the public key, or compute it and replace the oldest entry
+    pub fn get_or_insert_with<E, F>(
+        &mut self,
+        public_key: &[u8; PublicKey::ENCODED_LENGTH],
+        hash: F,
+    ) -> Result<[u8; HASH_SIZE], E>
+    where
+        F: FnOnce(&[u8]) -> Result<[u8; HASH_SIZE], E>,
+    {
+        let prefix = Entry::prefix_of(public_key);
+        let cached = self
+            .entries
+            .iter()
+            .flatten()
+            .find(|entry| entry.matches(prefix, public_key));
+        if let Some(entry) = cached {
+            return Ok(entry.hash);
+        }
+
+        let result = hash(public_key)?;
+        self.entries[self.next_index] = Some(Entry {
+            prefix,
+            public_key: *public_key,
+            hash: result,
+        });
+        self.next_index = (self.next_index + 1) % CACHE_SIZE;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PublicKeyHashCache, CACHE_SIZE, HASH_SIZE};
+    use crate::types::PublicKey;
+
+    fn public_key(byte: u8) -> [u8; PublicKey::ENCODED_LENGTH] {
+        let mut public_key = [byte; PublicKey::ENCODED_LENGTH];
+        public_key[0] = 0x02;
+        public_key
+    }
+
+    fn fake_hash(public_key: &[u8]) -> Result<[u8; HASH_SIZE], ()> {
+        Ok([public_key[PublicKey::ENCODED_LENGTH - 1]; HASH_SIZE])
+    }
+
+    #[test]
+    fn test_cache_hit_and_miss() {
+        let mut cache = PublicKeyHashCache::new();
+        let mut hash_count = 0;
+        let mut hash = |public_key: &[u8; PublicKey::ENCODED_LENGTH]| {
+            cache.get_or_insert_with(public_key, |bytes| {
+                hash_count += 1;
+                fake_hash(bytes)
+            })
+        };
+
+        assert_eq!(hash(&public_key(1)), Ok([1; HASH_SIZE]));
+        assert_eq!(hash(&public_key(1)), Ok([1; HASH_SIZE]));
+        assert_eq!(hash(&public_key(2)), Ok([2; HASH_SIZE]));
+        assert_eq!(hash(&public_key(1)), Ok([1; HASH_SIZE]));
+        assert_eq!(hash_count, 2);
+    }
+
+    #[test]
+    fn test_same_prefix_different_key() {
+        let mut cache = PublicKeyHashCache::new();
+        let key0 = public_key(1);
+        let mut key1 = public_key(1);
+        key1[PublicKey::ENCODED_LENGTH - 1] = 2;
+
+        assert_eq!(
+            cache.get_or_insert_with(&key0, fake_hash),
+            Ok([1; HASH_SIZE])
+        );
+        assert_eq!(
+            cache.get_or_insert_with(&key1, fake_hash),
+            Ok([2; HASH_SIZE])
+        );
+    }
+
+    #[test]
+    fn test_evict_oldest_entry() {
+        let mut cache = PublicKeyHashCache::new();
+        for byte in 0..=(CACHE_SIZE as u8) {
+            cache
+                .get_or_insert_with(&public_key(byte), fake_hash)
+                .unwrap();
+        }
+        // The first key has been evicted
+        let result = cache.get_or_insert_with(&public_key(0), |_| Err(()));
+        assert_eq!(result, Err(()));
+        let result = cache.get_or_insert_with(&public_key(CACHE_SIZE as u8), |_| Err(()));
+        assert_eq!(result, Ok([CACHE_SIZE as u8; HASH_SIZE]));
+
+        cache.reset();
+        let result = cache.get_or_insert_with(&public_key(CACHE_SIZE as u8), |_| Err(()));
+        assert_eq!(result, Err(()));
+    }
+}
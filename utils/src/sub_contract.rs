@@ -0,0 +1,171 @@
+use crate::types::{compact_integer::decode_length, U256};
+
+pub const MAX_NFT_COLLECTIONS: usize = 2;
+pub const MAX_PATH_SIZE: usize = 32;
+const HASH_SIZE: usize = 32;
+
+// The sub-contract id is the double Blake2b hash of the parent contract id and the path,
+// with the last byte replaced by the group of the parent contract
+pub fn sub_contract_id<E, F>(
+    parent_id: &[u8; HASH_SIZE],
+    path: &[u8],
+    mut hash: F,
+) -> Result<[u8; HASH_SIZE], E>
+where
+    F: FnMut(&[&[u8]]) -> Result<[u8; HASH_SIZE], E>,
+{
+    let first_hash = hash(&[parent_id, path])?;
+    let mut contract_id = hash(&[&first_hash])?;
+    contract_id[HASH_SIZE - 1] = parent_id[HASH_SIZE - 1];
+    Ok(contract_id)
+}
+
+// An NFT whose token id is a sub-contract id of its collection contract
+pub struct NftCollection {
+    pub token_id: [u8; HASH_SIZE],
+    pub parent_id: [u8; HASH_SIZE],
+    path: [u8; MAX_PATH_SIZE],
+    path_length: usize,
+}
+
+impl NftCollection {
+    pub fn path(&self) -> &[u8] {
+        &self.path[..self.path_length]
+    }
+
+    // NFT collections use the encoded item index as the sub-contract path
+    pub fn item_index(&self) -> Option<U256> {
+        let path = self.path();
+        if !path.is_empty() && path.len() <= 33 && decode_length(path[0]) == path.len() {
+            Some(U256::from_encoded_bytes(path))
+        } else {
+            None
+        }
+    }
+}
+
+// The NFT collections that the host provided for the tokens of a transaction. The data of
+// each NFT is the collection contract id followed by the sub-contract path, and the token id
+// is derived on the device.
+#[derive(Default)]
+pub struct NftCollections {
+    entries: [Option<NftCollection>; MAX_NFT_COLLECTIONS],
+}
+
+impl NftCollections {
+    pub fn reset(&mut self) {
+        self.entries = Default::default();
+    }
+
+    pub fn add<E, F>(&mut self, data: &[u8], hash: F, error: E) -> Result<(), E>
+    where
+        F: FnMut(&[&[u8]]) -> Result<[u8; HASH_SIZE], E>,
+    {
+        let path_length = data.len().wrapping_sub(HASH_SIZE);
+        if data.len() <= HASH_SIZE || path_length > MAX_PATH_SIZE {
+            return Err(error);
+        }
+        let entry = match self.entries.iter_mut().find(|entry| entry.is_none()) {
+            Some(entry) => entry,
+            None => return Err(error),
+        };
+        let parent_id: [u8; HASH_SIZE] = data[..HASH_SIZE].try_into().unwrap();
+        let token_id = sub_contract_id(&parent_id, &data[HASH_SIZE..], hash)?;
+        let mut path = [0u8; MAX_PATH_SIZE];
+        path[..path_length].copy_from_slice(&data[HASH_SIZE..]);
+        *entry = Some(NftCollection {
+            token_id,
+            parent_id,
+            path,
+            path_length,
+        });
+        Ok(())
+    }
+
+    pub fn get(&self, token_id: &[u8; HASH_SIZE]) -> Option<&NftCollection> {
+        self.entries
+            .iter()
+            .flatten()
+            .find(|entry| &entry.token_id == token_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{NftCollections, MAX_NFT_COLLECTIONS, MAX_PATH_SIZE};
+    use crate::types::u256::tests::hex_to_bytes;
+    use crate::types::U256;
+    use blake2::{Blake2b, Digest};
+    use digest::consts::U32;
+    use std::vec::Vec;
+
+    fn blake2b(inputs: &[&[u8]]) -> Result<[u8; 32], ()> {
+        let mut hasher = Blake2b::<U32>::new();
+        inputs.iter().for_each(|input| hasher.update(input));
+        Ok(hasher.finalize().into())
+    }
+
+    fn nft_data(parent_byte: Option<u8>, path: &[u8]) -> Vec<u8> {
+        let mut data: Vec<u8> = match parent_byte {
+            Some(byte) => [byte; 32].to_vec(),
+            None => (0..32).collect(),
+        };
+        data.extend_from_slice(path);
+        data
+    }
+
+    #[test]
+    fn test_nft_collections() {
+        let token_id: [u8; 32] =
+            hex_to_bytes("e85244e697fc3b5c8dfc2055a6d3568b53f583734d84c31d57855b0ad5c7cc1f")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let mut collections = NftCollections::default();
+        collections
+            .add(&nft_data(None, &[0x2a]), blake2b, ())
+            .unwrap();
+
+        let nft = collections.get(&token_id).unwrap();
+        assert_eq!(nft.parent_id.to_vec(), (0..32).collect::<Vec<u8>>());
+        assert_eq!(nft.path(), &[0x2a]);
+        assert_eq!(nft.item_index(), Some(U256::from_encoded_bytes(&[0x2a])));
+        assert!(collections.get(&[0; 32]).is_none());
+
+        collections.reset();
+        assert!(collections.get(&token_id).is_none());
+    }
+
+    #[test]
+    fn test_invalid_nft_data() {
+        let mut collections = NftCollections::default();
+        assert_eq!(
+            collections.add(&nft_data(Some(0), &[]), blake2b, ()),
+            Err(())
+        );
+        let long_path = [0u8; MAX_PATH_SIZE + 1];
+        assert_eq!(
+            collections.add(&nft_data(Some(0), &long_path), blake2b, ()),
+            Err(())
+        );
+        for index in 0..MAX_NFT_COLLECTIONS {
+            collections
+                .add(&nft_data(Some(index as u8), &[0x01]), blake2b, ())
+                .unwrap();
+        }
+        assert_eq!(
+            collections.add(&nft_data(Some(9), &[0x01]), blake2b, ()),
+            Err(())
+        );
+
+        // The path is not an encoded item index
+        collections.reset();
+        collections
+            .add(&nft_data(Some(0), b"abc"), blake2b, ())
+            .unwrap();
+        let token_id = super::sub_contract_id(&[0; 32], b"abc", blake2b).unwrap();
+        assert!(collections.get(&token_id).unwrap().item_index().is_none());
+    }
+}
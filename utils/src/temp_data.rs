@@ -27,12 +27,13 @@ impl TempData {
     }
 
     #[inline]
-    pub fn write_byte(&mut self, byte: u8) {
+    pub fn write_byte(&mut self, byte: u8) -> bool {
         if self.is_overflow() {
-            panic!();
+            return false;
         }
         self.data[self.size] = byte;
         self.size += 1;
+        true
     }
 
     pub fn get(&self) -> &[u8] {
@@ -42,10 +43,11 @@ impl TempData {
 
 impl Writable for TempData {
     fn write(&mut self, bytes: &[u8]) -> bool {
-        for byte in bytes {
-            self.write_byte(*byte)
+        // Reject the bytes as a whole instead of writing a truncated prefix
+        if self.size + bytes.len() > Self::MAX_SIZE {
+            return false;
         }
-        true
+        bytes.iter().all(|byte| self.write_byte(*byte))
     }
 }
 
@@ -92,6 +94,10 @@ mod tests {
         assert_eq!(temp_data.size, TempData::MAX_SIZE);
         assert!(temp_data.is_overflow());
 
+        assert!(!temp_data.write(&bytes3));
+        assert!(!temp_data.write_byte(0));
+        assert_eq!(temp_data.size, TempData::MAX_SIZE);
+
         temp_data.reset();
         assert_eq!(temp_data.size, 0);
         assert!(!temp_data.is_overflow());
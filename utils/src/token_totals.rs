@@ -0,0 +1,88 @@
+use crate::types::{Hash, Token};
+
+pub const MAX_TOKEN_TOTALS: usize = 4;
+
+// The total amount of each token sent by the outputs of a tx. The totals are incomplete if
+// the outputs send more distinct tokens than `MAX_TOKEN_TOTALS`.
+#[derive(Default)]
+pub struct TokenTotals {
+    totals: [Token; MAX_TOKEN_TOTALS],
+    size: usize,
+    output_count: usize,
+    is_incomplete: bool,
+}
+
+impl TokenTotals {
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    // Add the token amount of an output, returns `None` if the total overflows
+    pub fn add(&mut self, token: &Token) -> Option<()> {
+        self.output_count += 1;
+        let total = self.totals[..self.size]
+            .iter_mut()
+            .find(|total| total.id.0 == token.id.0);
+        if let Some(total) = total {
+            total.amount = total.amount.add(&token.amount)?;
+            return Some(());
+        }
+        if self.size == MAX_TOKEN_TOTALS {
+            self.is_incomplete = true;
+            return Some(());
+        }
+        self.totals[self.size] = Token::from(Hash::from_bytes(token.id.0), token.amount.clone());
+        self.size += 1;
+        Some(())
+    }
+
+    pub fn get_totals(&self) -> &[Token] {
+        &self.totals[..self.size]
+    }
+
+    // The number of outputs that send tokens
+    pub fn output_count(&self) -> usize {
+        self.output_count
+    }
+
+    pub fn is_incomplete(&self) -> bool {
+        self.is_incomplete
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TokenTotals, MAX_TOKEN_TOTALS};
+    use crate::types::{Hash, Token, U256};
+
+    fn token(id: u8, amount: u8) -> Token {
+        Token::from(
+            Hash::from_bytes([id; 32]),
+            U256::from_encoded_bytes(&[amount]),
+        )
+    }
+
+    #[test]
+    fn test_token_totals() {
+        let mut token_totals = TokenTotals::default();
+        assert!(token_totals.get_totals().is_empty());
+
+        token_totals.add(&token(0, 1)).unwrap();
+        token_totals.add(&token(1, 2)).unwrap();
+        token_totals.add(&token(0, 3)).unwrap();
+        assert_eq!(token_totals.output_count(), 3);
+        assert_eq!(token_totals.get_totals(), &[token(0, 4), token(1, 2)]);
+        assert!(!token_totals.is_incomplete());
+
+        for id in 2..=(MAX_TOKEN_TOTALS as u8) {
+            token_totals.add(&token(id, 1)).unwrap();
+        }
+        assert_eq!(token_totals.get_totals().len(), MAX_TOKEN_TOTALS);
+        assert!(token_totals.is_incomplete());
+
+        token_totals.reset();
+        assert!(token_totals.get_totals().is_empty());
+        assert_eq!(token_totals.output_count(), 0);
+        assert!(!token_totals.is_incomplete());
+    }
+}
@@ -0,0 +1,253 @@
+use crate::{
+    message::{displayable_message, MAX_DISPLAYED_MESSAGE_SIZE},
+    types::{compact_integer::decode_length, U256},
+};
+
+// The prefix of signed typed data, the signed hash is the Blake2b hash of the prefix, the domain
+// hash and the message hash. Tx ids are not prefixed and signed messages use another prefix,
+// so a typed data signature cannot be replayed as a tx or a message signature.
+pub const TYPED_DATA_PREFIX: &[u8] = b"Alephium Signed Typed Data: ";
+// The typed data is kept in the message buffer until it is complete
+pub const MAX_TYPED_DATA_SIZE: usize = MAX_DISPLAYED_MESSAGE_SIZE;
+pub const MAX_TYPED_FIELDS: usize = 6;
+pub const MAX_TYPED_BYTES_SIZE: usize = 32;
+const MAX_NAME_SIZE: usize = 32;
+const HASH_SIZE: usize = 32;
+
+const BOOL_TYPE: u8 = 0;
+const U256_TYPE: u8 = 1;
+const BYTES_TYPE: u8 = 2;
+const STRING_TYPE: u8 = 3;
+const ADDRESS_TYPE: u8 = 4;
+
+#[cfg_attr(test, derive(Debug))]
+#[derive(PartialEq)]
+pub enum TypedValue<'a> {
+    Bool(bool),
+    U256(U256),
+    Bytes(&'a [u8]),
+    String(&'a str),
+    // The address prefix and the 32-byte address hash
+    Address(u8, &'a [u8; HASH_SIZE]),
+}
+
+#[cfg_attr(test, derive(Debug))]
+#[derive(PartialEq)]
+pub struct TypedField<'a> {
+    pub name: &'a str,
+    pub value: TypedValue<'a>,
+}
+
+// The typed data is the domain followed by the message struct:
+// * the domain is the name of the dApp (1-byte length, displayable UTF-8) and the network id
+// * the message is the field count and the fields, each field is the name (1-byte length,
+//   displayable UTF-8), the type tag and the value
+// The values are encoded as: bool (1 byte), U256 (compact encoding), bytes (1-byte length),
+// string (1-byte length, displayable UTF-8) and address (prefix and 32-byte hash)
+pub struct TypedData<'a> {
+    pub domain_name: &'a str,
+    pub network_id: u8,
+    pub encoded_domain: &'a [u8],
+    pub encoded_message: &'a [u8],
+    fields: [Option<TypedField<'a>>; MAX_TYPED_FIELDS],
+}
+
+impl<'a> TypedData<'a> {
+    pub fn decode(data: &'a [u8]) -> Option<Self> {
+        let (domain_name, remain) = decode_name(data)?;
+        let (&network_id, encoded_message) = remain.split_first()?;
+        let encoded_domain = &data[..(data.len() - encoded_message.len())];
+
+        let (&field_count, mut remain) = encoded_message.split_first()?;
+        let field_count = field_count as usize;
+        if field_count == 0 || field_count > MAX_TYPED_FIELDS {
+            return None;
+        }
+        let mut fields = [const { None }; MAX_TYPED_FIELDS];
+        for field in fields.iter_mut().take(field_count) {
+            let (name, rest) = decode_name(remain)?;
+            let (value, rest) = decode_value(rest)?;
+            *field = Some(TypedField { name, value });
+            remain = rest;
+        }
+        if !remain.is_empty() {
+            return None;
+        }
+        Some(Self {
+            domain_name,
+            network_id,
+            encoded_domain,
+            encoded_message,
+            fields,
+        })
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = &TypedField<'a>> {
+        self.fields.iter().flatten()
+    }
+}
+
+fn split_length_prefixed(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (&length, remain) = data.split_first()?;
+    if remain.len() < length as usize {
+        return None;
+    }
+    Some(remain.split_at(length as usize))
+}
+
+fn decode_name(data: &[u8]) -> Option<(&str, &[u8])> {
+    let (name, remain) = split_length_prefixed(data)?;
+    if name.len() > MAX_NAME_SIZE {
+        return None;
+    }
+    Some((displayable_message(name)?, remain))
+}
+
+fn decode_value(data: &[u8]) -> Option<(TypedValue<'_>, &[u8])> {
+    let (&tag, remain) = data.split_first()?;
+    match tag {
+        BOOL_TYPE => match remain.split_first()? {
+            (0, rest) => Some((TypedValue::Bool(false), rest)),
+            (1, rest) => Some((TypedValue::Bool(true), rest)),
+            _ => None,
+        },
+        U256_TYPE => {
+            let length = decode_length(*remain.first()?);
+            if length > remain.len() || length > 33 {
+                return None;
+            }
+            let (bytes, rest) = remain.split_at(length);
+            Some((TypedValue::U256(U256::from_encoded_bytes(bytes)), rest))
+        }
+        BYTES_TYPE => {
+            let (bytes, rest) = split_length_prefixed(remain)?;
+            if bytes.len() > MAX_TYPED_BYTES_SIZE {
+                return None;
+            }
+            Some((TypedValue::Bytes(bytes), rest))
+        }
+        STRING_TYPE => {
+            let (bytes, rest) = split_length_prefixed(remain)?;
+            Some((TypedValue::String(displayable_message(bytes)?), rest))
+        }
+        ADDRESS_TYPE => {
+            if remain.len() < 1 + HASH_SIZE {
+                return None;
+            }
+            let hash = remain[1..(1 + HASH_SIZE)].try_into().unwrap();
+            Some((
+                TypedValue::Address(remain[0], hash),
+                &remain[(1 + HASH_SIZE)..],
+            ))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{TypedData, TypedField, TypedValue, MAX_TYPED_FIELDS};
+    use crate::types::U256;
+    use std::{vec, vec::Vec};
+
+    fn encode_domain(name: &str, network_id: u8) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.push(name.len() as u8);
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.push(network_id);
+        bytes
+    }
+
+    fn encode_field(name: &str, tag: u8, value: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.push(name.len() as u8);
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.push(tag);
+        bytes.extend_from_slice(value);
+        bytes
+    }
+
+    #[test]
+    fn test_decode_typed_data() {
+        let domain = encode_domain("Alephium DEX", 0);
+        let mut data = domain.clone();
+        data.push(5);
+        data.extend(encode_field("approved", 0, &[1]));
+        data.extend(encode_field("amount", 1, &[0x05]));
+        data.extend(encode_field("nonce", 2, &[2, 0xab, 0xcd]));
+        data.extend(encode_field("memo", 3, &[2, b'h', b'i']));
+        data.extend(encode_field(
+            "to",
+            4,
+            &[[0u8].as_slice(), &[7; 32]].concat(),
+        ));
+
+        let typed_data = TypedData::decode(&data).unwrap();
+        assert_eq!(typed_data.domain_name, "Alephium DEX");
+        assert_eq!(typed_data.network_id, 0);
+        assert_eq!(typed_data.encoded_domain, &domain[..]);
+        assert_eq!(typed_data.encoded_message, &data[domain.len()..]);
+        let fields = typed_data.fields().collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            [
+                &TypedField {
+                    name: "approved",
+                    value: TypedValue::Bool(true)
+                },
+                &TypedField {
+                    name: "amount",
+                    value: TypedValue::U256(U256::from_encoded_bytes(&[0x05]))
+                },
+                &TypedField {
+                    name: "nonce",
+                    value: TypedValue::Bytes(&[0xab, 0xcd])
+                },
+                &TypedField {
+                    name: "memo",
+                    value: TypedValue::String("hi")
+                },
+                &TypedField {
+                    name: "to",
+                    value: TypedValue::Address(0, &[7; 32])
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid_typed_data() {
+        let decode = |fields: &[Vec<u8>], count: u8| {
+            let mut data = encode_domain("dApp", 1);
+            data.push(count);
+            data.extend(fields.concat());
+            TypedData::decode(&data).is_some()
+        };
+        let field = encode_field("flag", 0, &[0]);
+        assert!(decode(core::slice::from_ref(&field), 1));
+        assert!(decode(
+            &vec![field.clone(); MAX_TYPED_FIELDS],
+            MAX_TYPED_FIELDS as u8
+        ));
+
+        // Wrong field count
+        assert!(!decode(&[], 0));
+        assert!(!decode(core::slice::from_ref(&field), 2));
+        assert!(!decode(&[field.clone(), field.clone()], 1));
+        assert!(!decode(
+            &vec![field.clone(); MAX_TYPED_FIELDS + 1],
+            MAX_TYPED_FIELDS as u8 + 1
+        ));
+        // Invalid values
+        assert!(!decode(&[encode_field("flag", 0, &[2])], 1));
+        assert!(!decode(&[encode_field("flag", 5, &[0])], 1));
+        assert!(!decode(&[encode_field("data", 2, &[33; 34])], 1));
+        assert!(!decode(&[encode_field("memo", 3, &[2, b'\n', b'a'])], 1));
+        assert!(!decode(&[encode_field("to", 4, &[0; 32])], 1));
+        assert!(!decode(&[encode_field("", 0, &[0])], 1));
+        // Truncated domain
+        assert!(TypedData::decode(&[4, b'd', b'A', b'p', b'p']).is_none());
+    }
+}
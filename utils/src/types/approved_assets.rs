@@ -0,0 +1,531 @@
+use super::*;
+use crate::decode::Reset;
+
+pub const MAX_APPROVED_ASSETS: usize = 4;
+pub const ALPH_TOKEN_ID: [u8; 32] = [0; 32];
+pub const MAX_CONTRACT_CALLS: usize = 2;
+pub const MAX_CALL_ARGS: usize = 3;
+const MAX_TRACKED_LOCALS: usize = 8;
+// A contract call pops the args, the args length, the returns length and the contract id
+const MAX_TRACKED_STACK_SIZE: usize = MAX_CALL_ARGS + 3;
+// The encoded `BytesConst` instr of a token id: the instr code, the length and the 32 bytes
+const TOKEN_ID_CONST_SIZE: usize = 35;
+
+// The value of a constant that is pushed on the stack
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Default, Clone)]
+pub enum Const {
+    #[default]
+    Unknown,
+    Bool(bool),
+    U256(U256),
+    Bytes32([u8; 32]),
+}
+
+// The method of a contract call, either the method index or the method selector
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone)]
+pub enum CallMethod {
+    Index(u8),
+    Selector(i32),
+}
+
+impl Default for CallMethod {
+    fn default() -> Self {
+        CallMethod::Index(0)
+    }
+}
+
+// A call of the main method of a script to a constant contract id
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Default, Clone)]
+pub struct ContractCall {
+    pub contract_id: [u8; 32],
+    pub method: CallMethod,
+    args: [Const; MAX_CALL_ARGS],
+    args_length: usize,
+}
+
+impl ContractCall {
+    // The args are only available if there are at most `MAX_CALL_ARGS` args
+    pub fn args(&self) -> Option<&[Const]> {
+        self.args.get(..self.args_length)
+    }
+
+    pub fn args_length(&self) -> usize {
+        self.args_length
+    }
+}
+
+// The contract calls of the main method of a script.
+// The calls are only determinable if all the called contract ids and the args lengths are constants.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Default)]
+pub struct ContractCalls {
+    calls: [ContractCall; MAX_CONTRACT_CALLS],
+    size: usize,
+    is_undetermined: bool,
+}
+
+impl Reset for ContractCalls {
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl ContractCalls {
+    pub fn is_determinable(&self) -> bool {
+        !self.is_undetermined
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    pub fn get_calls(&self) -> &[ContractCall] {
+        &self.calls[..self.size]
+    }
+
+    fn add(&mut self, call: ContractCall) {
+        if self.size == MAX_CONTRACT_CALLS {
+            self.is_undetermined = true;
+            return;
+        }
+        self.calls[self.size] = call;
+        self.size += 1;
+    }
+
+    pub fn merge(&mut self, other: &ContractCalls) {
+        for call in other.get_calls() {
+            self.add(call.clone());
+        }
+        self.is_undetermined |= other.is_undetermined;
+    }
+
+    pub fn mark_undetermined(&mut self) {
+        self.is_undetermined = true;
+    }
+}
+
+// The assets approved by the main method of a script. ALPH is recorded with the `ALPH_TOKEN_ID`.
+// The approved assets are only determinable if all the approved amounts and token ids are constants.
+// The approved assets are spent by the next contract call, so the index of that call is recorded.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Default)]
+pub struct ApprovedAssets {
+    assets: [Token; MAX_APPROVED_ASSETS],
+    call_indexes: [usize; MAX_APPROVED_ASSETS],
+    size: usize,
+    is_undetermined: bool,
+    has_backward_jump: bool,
+}
+
+impl Reset for ApprovedAssets {
+    fn reset(&mut self) {
+        self.assets.iter_mut().for_each(|asset| asset.reset());
+        self.call_indexes = [0; MAX_APPROVED_ASSETS];
+        self.size = 0;
+        self.is_undetermined = false;
+        self.has_backward_jump = false;
+    }
+}
+
+impl ApprovedAssets {
+    // Approvals within a loop can be executed more than once
+    pub fn is_determinable(&self) -> bool {
+        !(self.is_undetermined || (self.has_backward_jump && self.size > 0))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    pub fn get_assets(&self) -> &[Token] {
+        &self.assets[..self.size]
+    }
+
+    // The index of the contract call that spends the approved asset, the call may not exist
+    // if there is no contract call after the approval
+    pub fn get_call_index(&self, asset_index: usize) -> usize {
+        self.call_indexes[asset_index]
+    }
+
+    fn add(&mut self, token_id: [u8; 32], amount: U256, call_index: usize) {
+        if self.size == MAX_APPROVED_ASSETS {
+            self.is_undetermined = true;
+            return;
+        }
+        self.assets[self.size] = Token::from(Hash::from_bytes(token_id), amount);
+        self.call_indexes[self.size] = call_index;
+        self.size += 1;
+    }
+
+    // The call indexes are kept, so the contract calls of the same method are merged alongside
+    pub fn merge(&mut self, other: &ApprovedAssets) {
+        for (asset, call_index) in other.get_assets().iter().zip(other.call_indexes) {
+            self.add(asset.id.0, asset.amount.clone(), call_index);
+        }
+        self.is_undetermined |= other.is_undetermined;
+        self.has_backward_jump |= other.has_backward_jump;
+    }
+
+    pub fn mark_undetermined(&mut self) {
+        self.is_undetermined = true;
+    }
+}
+
+// `ApprovalTracker` follows the constants pushed on the stack by the instrs of a method,
+// so that the amounts and token ids used by `ApproveAlph` and `ApproveToken` can be determined.
+// The contract ids and the args of contract calls are determined in the same way.
+// Only the top few stack items and the first few locals are tracked, any other instr makes
+// the tracked stack items unknown.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct ApprovalTracker {
+    pub approved_assets: ApprovedAssets,
+    pub contract_calls: ContractCalls,
+    stack: [Const; MAX_TRACKED_STACK_SIZE],
+    locals: [Const; MAX_TRACKED_LOCALS],
+    instr_bytes: [u8; TOKEN_ID_CONST_SIZE],
+    instr_length: usize,
+}
+
+impl Default for ApprovalTracker {
+    fn default() -> Self {
+        ApprovalTracker {
+            approved_assets: ApprovedAssets::default(),
+            contract_calls: ContractCalls::default(),
+            stack: Default::default(),
+            locals: Default::default(),
+            instr_bytes: [0; TOKEN_ID_CONST_SIZE],
+            instr_length: 0,
+        }
+    }
+}
+
+impl Reset for ApprovalTracker {
+    fn reset(&mut self) {
+        self.approved_assets.reset();
+        self.contract_calls.reset();
+        self.stack = Default::default();
+        self.locals = Default::default();
+        self.instr_length = 0;
+    }
+}
+
+impl ApprovalTracker {
+    // The top of the stack is the last item
+    #[inline]
+    fn push(&mut self, value: Const) {
+        self.stack.rotate_left(1);
+        self.stack[MAX_TRACKED_STACK_SIZE - 1] = value;
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Const {
+        let value = core::mem::take(&mut self.stack[MAX_TRACKED_STACK_SIZE - 1]);
+        self.stack.rotate_right(1);
+        value
+    }
+
+    #[inline]
+    fn clear_stack(&mut self) {
+        self.stack = Default::default();
+    }
+
+    fn token_id_const(&self) -> Const {
+        if self.instr_length != TOKEN_ID_CONST_SIZE {
+            return Const::Unknown;
+        }
+        let mut token_id = [0u8; 32];
+        token_id.copy_from_slice(&self.instr_bytes[(TOKEN_ID_CONST_SIZE - 32)..]);
+        Const::Bytes32(token_id)
+    }
+
+    fn on_contract_call(&mut self, method: CallMethod) {
+        let contract_id = self.pop();
+        let _returns_length = self.pop();
+        let args_length = self.pop();
+        let args_length = match args_length {
+            Const::U256(length) => length.to_u128(),
+            _ => None,
+        };
+        match (contract_id, args_length) {
+            (Const::Bytes32(contract_id), Some(args_length)) => {
+                let mut call = ContractCall {
+                    contract_id,
+                    method,
+                    args: Default::default(),
+                    args_length: args_length as usize,
+                };
+                if call.args_length <= MAX_CALL_ARGS {
+                    for index in (0..call.args_length).rev() {
+                        call.args[index] = self.pop();
+                    }
+                }
+                self.contract_calls.add(call)
+            }
+            _ => self.contract_calls.mark_undetermined(),
+        }
+        self.clear_stack();
+    }
+}
+
+impl InstrTracker for ApprovalTracker {
+    type Summary = ScriptApprovals;
+
+    // Keep the encoded bytes of the current instr, only the token id constant is needed
+    fn on_instr_bytes(&mut self, bytes: &[u8]) {
+        let to_index = self.instr_length + bytes.len();
+        if to_index <= TOKEN_ID_CONST_SIZE {
+            self.instr_bytes[self.instr_length..to_index].copy_from_slice(bytes);
+        }
+        self.instr_length = to_index;
+    }
+
+    fn on_instr(&mut self, instr: &Instr) {
+        match instr {
+            Instr::ConstTrue => self.push(Const::Bool(true)),
+            Instr::ConstFalse => self.push(Const::Bool(false)),
+            Instr::U256Const0 => self.push(Const::U256(U256::from_encoded_bytes(&[0]))),
+            Instr::U256Const1 => self.push(Const::U256(U256::from_encoded_bytes(&[1]))),
+            Instr::U256Const2 => self.push(Const::U256(U256::from_encoded_bytes(&[2]))),
+            Instr::U256Const3 => self.push(Const::U256(U256::from_encoded_bytes(&[3]))),
+            Instr::U256Const4 => self.push(Const::U256(U256::from_encoded_bytes(&[4]))),
+            Instr::U256Const5 => self.push(Const::U256(U256::from_encoded_bytes(&[5]))),
+            Instr::U256Const(value) => self.push(Const::U256(value.clone())),
+            Instr::BytesConst(_) => self.push(self.token_id_const()),
+            Instr::ALPHTokenId => self.push(Const::Bytes32(ALPH_TOKEN_ID)),
+            Instr::AddressConst(_)
+            | Instr::CallerAddress
+            | Instr::SelfAddress
+            | Instr::SelfContractId
+            | Instr::CallerContractId => self.push(Const::Unknown),
+            Instr::LoadLocal(index) => {
+                let value = self
+                    .locals
+                    .get(index.0 as usize)
+                    .cloned()
+                    .unwrap_or_default();
+                self.push(value)
+            }
+            Instr::StoreLocal(index) => {
+                let value = self.pop();
+                if let Some(local) = self.locals.get_mut(index.0 as usize) {
+                    *local = value;
+                }
+            }
+            Instr::StoreLocalByIndex => {
+                self.locals = Default::default();
+                self.clear_stack();
+            }
+            Instr::ApproveAlph => {
+                let amount = self.pop();
+                self.clear_stack();
+                match amount {
+                    Const::U256(amount) => {
+                        let call_index = self.contract_calls.size;
+                        self.approved_assets.add(ALPH_TOKEN_ID, amount, call_index)
+                    }
+                    _ => self.approved_assets.mark_undetermined(),
+                }
+            }
+            Instr::ApproveToken => {
+                let amount = self.pop();
+                let token_id = self.pop();
+                self.clear_stack();
+                match (token_id, amount) {
+                    (Const::Bytes32(token_id), Const::U256(amount)) => {
+                        let call_index = self.contract_calls.size;
+                        self.approved_assets.add(token_id, amount, call_index)
+                    }
+                    _ => self.approved_assets.mark_undetermined(),
+                }
+            }
+            Instr::CallExternal(index) => self.on_contract_call(CallMethod::Index(index.0)),
+            Instr::CallExternalBySelector(selector) => {
+                self.on_contract_call(CallMethod::Selector(selector.0))
+            }
+            Instr::Jump(offset) | Instr::IfTrue(offset) | Instr::IfFalse(offset) => {
+                if offset.inner < 0 {
+                    self.approved_assets.has_backward_jump = true;
+                }
+                self.clear_stack();
+            }
+            Instr::Unknown => {
+                self.approved_assets.mark_undetermined();
+                self.contract_calls.mark_undetermined();
+                self.clear_stack();
+            }
+            _ => self.clear_stack(),
+        }
+        self.instr_length = 0;
+    }
+
+    fn on_method(&self, method_index: usize, summary: &mut ScriptApprovals) {
+        if method_index == 0 {
+            summary.approved_assets.merge(&self.approved_assets);
+            summary.contract_calls.merge(&self.contract_calls);
+        } else if !self.approved_assets.is_empty() {
+            // Approvals in other methods depend on how they are called
+            summary.approved_assets.mark_undetermined();
+        }
+    }
+}
+
+// The assets approved and the contracts called by the main method of a tx script
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Default)]
+pub struct ScriptApprovals {
+    pub approved_assets: ApprovedAssets,
+    pub contract_calls: ContractCalls,
+}
+
+impl Reset for ScriptApprovals {
+    fn reset(&mut self) {
+        self.approved_assets.reset();
+        self.contract_calls.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{ApprovalTracker, CallMethod, Const, ALPH_TOKEN_ID};
+    use crate::buffer::Buffer;
+    use crate::decode::{new_decoder, Decoder};
+    use crate::types::u256::tests::hex_to_bytes;
+    use crate::types::InstrTracker;
+    use crate::types::{Instr, U256};
+    use crate::TempData;
+
+    fn track(tracker: &mut ApprovalTracker, instrs_hex: &str) {
+        let bytes = hex_to_bytes(instrs_hex).unwrap();
+        let mut temp_data = TempData::new();
+        let mut buffer = Buffer::new(&bytes, &mut temp_data);
+        while !buffer.is_empty() {
+            let mut decoder = new_decoder::<Instr>();
+            let from_index = buffer.get_index();
+            let instr = decoder.decode(&mut buffer).unwrap().unwrap();
+            tracker.on_instr_bytes(buffer.get_range(from_index, buffer.get_index()));
+            tracker.on_instr(instr);
+        }
+    }
+
+    #[test]
+    fn test_approve_token_from_local() {
+        let token_id = "5bf2f559ae714dab83ff36bed4d9e634dfda3ca9ed755d60f00be89e2a20bd00";
+        let amount = "c5056bc75e2d63100000";
+        let mut tracker = ApprovalTracker::default();
+        // BytesConst, StoreLocal(0), CallerAddress, LoadLocal(0), U256Const, ApproveToken
+        let instrs = std::format!("144020{}1700b4160013{}a3", token_id, amount);
+        track(&mut tracker, &instrs);
+
+        let approved_assets = &tracker.approved_assets;
+        assert!(approved_assets.is_determinable());
+        assert_eq!(approved_assets.get_assets().len(), 1);
+        let asset = &approved_assets.get_assets()[0];
+        assert_eq!(asset.id.0.as_slice(), hex_to_bytes(token_id).unwrap());
+        let expected_amount = U256::from_encoded_bytes(&hex_to_bytes(amount).unwrap());
+        assert_eq!(asset.amount, expected_amount);
+    }
+
+    #[test]
+    fn test_approve_alph() {
+        let mut tracker = ApprovalTracker::default();
+        // CallerAddress, U256Const2, ApproveAlph
+        track(&mut tracker, "b40ea2");
+        let approved_assets = &tracker.approved_assets;
+        assert!(approved_assets.is_determinable());
+        assert_eq!(approved_assets.get_assets().len(), 1);
+        assert_eq!(approved_assets.get_assets()[0].id.0, ALPH_TOKEN_ID);
+        assert_eq!(
+            approved_assets.get_assets()[0].amount,
+            U256::from_encoded_bytes(&[2])
+        );
+    }
+
+    #[test]
+    fn test_undetermined_approvals() {
+        let mut tracker = ApprovalTracker::default();
+        // CallerAddress, U256Const1, U256Const2, U256Add, ApproveAlph
+        track(&mut tracker, "b40d0e2aa2");
+        assert!(!tracker.approved_assets.is_determinable());
+
+        let mut tracker = ApprovalTracker::default();
+        // CallerAddress, U256Const2, ApproveAlph, Jump(-4)
+        track(&mut tracker, "b40ea24a3c");
+        assert!(!tracker.approved_assets.is_determinable());
+
+        let mut tracker = ApprovalTracker::default();
+        // Jump(-4) without approvals
+        track(&mut tracker, "4a3c");
+        assert!(tracker.approved_assets.is_determinable());
+        assert!(tracker.approved_assets.is_empty());
+    }
+
+    #[test]
+    fn test_contract_calls() {
+        let contract_id = "25469eb0d0d0a55deea832924547b7b166c70a3d4e1f9a73c2bbc8ad8bd31101";
+        let mut tracker = ApprovalTracker::default();
+        // U256Const1, ConstTrue, U256Const2, U256Const0, BytesConst, CallExternal(3)
+        let instrs = std::format!("0d030e0c144020{}0103", contract_id);
+        track(&mut tracker, &instrs);
+        // U256Const0, U256Const1, BytesConst, CallExternalBySelector
+        let instrs = std::format!("0c0d144020{}d40a0b0c0d", contract_id);
+        track(&mut tracker, &instrs);
+
+        let contract_calls = &tracker.contract_calls;
+        assert!(contract_calls.is_determinable());
+        let calls = contract_calls.get_calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(
+            calls[0].contract_id.as_slice(),
+            hex_to_bytes(contract_id).unwrap()
+        );
+        assert_eq!(calls[0].method, CallMethod::Index(3));
+        assert_eq!(
+            calls[0].args(),
+            Some(
+                [
+                    Const::U256(U256::from_encoded_bytes(&[1])),
+                    Const::Bool(true)
+                ]
+                .as_slice()
+            )
+        );
+        assert_eq!(calls[1].method, CallMethod::Selector(0x0a0b0c0d));
+        assert_eq!(calls[1].args(), Some([].as_slice()));
+
+        // The contract id is not a constant
+        let mut tracker = ApprovalTracker::default();
+        // U256Const0, U256Const0, LoadLocal(5), CallExternal(0)
+        track(&mut tracker, "0c0c16050100");
+        assert!(!tracker.contract_calls.is_determinable());
+        assert!(tracker.contract_calls.is_empty());
+    }
+
+    #[test]
+    fn test_approved_assets_call_index() {
+        let contract_id = "25469eb0d0d0a55deea832924547b7b166c70a3d4e1f9a73c2bbc8ad8bd31101";
+        let mut tracker = ApprovalTracker::default();
+        // CallerAddress, U256Const2, ApproveAlph
+        track(&mut tracker, "b40ea2");
+        // U256Const0, U256Const0, BytesConst, CallExternal(0)
+        let instrs = std::format!("0c0c144020{}0100", contract_id);
+        track(&mut tracker, &instrs);
+        // CallerAddress, U256Const1, ApproveAlph, U256Const0, U256Const0, BytesConst, CallExternal(1)
+        let instrs = std::format!("b40da20c0c144020{}0101", contract_id);
+        track(&mut tracker, &instrs);
+        // CallerAddress, U256Const3, ApproveAlph
+        track(&mut tracker, "b40fa2");
+
+        let approved_assets = &tracker.approved_assets;
+        assert_eq!(approved_assets.get_assets().len(), 3);
+        assert_eq!(tracker.contract_calls.get_calls().len(), 2);
+        assert_eq!(approved_assets.get_call_index(0), 0);
+        assert_eq!(approved_assets.get_call_index(1), 1);
+        // The last approval is not spent by a contract call
+        assert_eq!(approved_assets.get_call_index(2), 2);
+    }
+}
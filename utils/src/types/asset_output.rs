@@ -2,6 +2,12 @@ use super::*;
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
 
+// The additional data is displayed up to this size
+pub const MAX_ADDITIONAL_DATA_PREFIX_SIZE: usize = 32;
+// The decoder only keeps the current token of the token vector, so the decoded tokens are
+// copied until this size, outputs with more tokens are rejected
+pub const MAX_OUTPUT_TOKENS: usize = 4;
+
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Default)]
 pub struct AssetOutput {
@@ -9,7 +15,9 @@ pub struct AssetOutput {
     pub lockup_script: LockupScript,
     pub lock_time: TimeStamp,
     pub tokens: AVector<Token>,
+    pub decoded_tokens: [Token; MAX_OUTPUT_TOKENS],
     pub additional_data: ByteString,
+    pub additional_data_prefix: [u8; MAX_ADDITIONAL_DATA_PREFIX_SIZE],
 }
 
 impl Reset for AssetOutput {
@@ -18,7 +26,57 @@ impl Reset for AssetOutput {
         self.lockup_script.reset();
         self.lock_time.reset();
         self.tokens.reset();
+        self.decoded_tokens
+            .iter_mut()
+            .for_each(|token| token.reset());
         self.additional_data.reset();
+        self.additional_data_prefix = [0; MAX_ADDITIONAL_DATA_PREFIX_SIZE];
+    }
+}
+
+impl AssetOutput {
+    // All the tokens of the output, once the token vector is decoded
+    pub fn get_tokens(&self) -> &[Token] {
+        let size = core::cmp::min(self.tokens.size(), MAX_OUTPUT_TOKENS);
+        &self.decoded_tokens[..size]
+    }
+
+    // Copy the token that has just been decoded
+    fn on_token(&mut self) -> DecodeResult<()> {
+        let token = self
+            .tokens
+            .get_current_item()
+            .ok_or(DecodeError::InternalError)?;
+        let index = self.tokens.current_index as usize;
+        if index >= MAX_OUTPUT_TOKENS {
+            return Err(DecodeError::Overflow);
+        }
+        self.decoded_tokens[index] =
+            Token::from(Hash::from_bytes(token.id.0), token.amount.clone());
+        Ok(())
+    }
+
+    // The displayed prefix of the additional data
+    pub fn additional_data(&self) -> &[u8] {
+        let size = core::cmp::min(self.additional_data.size(), MAX_ADDITIONAL_DATA_PREFIX_SIZE);
+        &self.additional_data_prefix[..size]
+    }
+
+    pub fn is_additional_data_truncated(&self) -> bool {
+        self.additional_data.size() > MAX_ADDITIONAL_DATA_PREFIX_SIZE
+    }
+
+    // Keep the prefix of the additional data bytes consumed by the decoder
+    fn on_additional_data<W: Writable>(&mut self, buffer: &Buffer<'_, W>, decoded_size: usize) {
+        let size = core::cmp::max(self.additional_data.current_index, 0) as usize;
+        let to_index = buffer.get_index();
+        let bytes = buffer.get_range(to_index - (size - decoded_size), to_index);
+        for (index, byte) in (decoded_size..size).zip(bytes) {
+            if index >= MAX_ADDITIONAL_DATA_PREFIX_SIZE {
+                break;
+            }
+            self.additional_data_prefix[index] = *byte;
+        }
     }
 }
 
@@ -37,10 +95,73 @@ impl RawDecoder for AssetOutput {
             1 => self.lockup_script.decode(buffer, stage),
             2 => self.lock_time.decode(buffer, stage),
             step if step > 2 && step <= (2 + self.tokens.step_size()) => {
-                self.tokens.decode(buffer, stage)
+                let result = self.tokens.decode(buffer, stage)?;
+                if result.is_complete() && self.tokens.get_current_item().is_some() {
+                    self.on_token()?;
+                }
+                Ok(result)
+            }
+            step if step < self.step_size() => {
+                let decoded_size = core::cmp::max(self.additional_data.current_index, 0) as usize;
+                let result = self.additional_data.decode(buffer, stage)?;
+                self.on_additional_data(buffer, decoded_size);
+                Ok(result)
             }
-            step if step < self.step_size() => self.additional_data.decode(buffer, stage),
             _ => Err(DecodeError::InternalError),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{AssetOutput, MAX_OUTPUT_TOKENS};
+    use crate::buffer::Buffer;
+    use crate::decode::{new_decoder, DecodeError, Decoder};
+    use crate::types::{Hash, Token, U256};
+    use crate::TempData;
+    use std::vec;
+    use std::vec::Vec;
+
+    fn encode_output(token_size: usize) -> Vec<u8> {
+        let mut bytes = vec![0x01, 0x00];
+        bytes.extend([0x11; 32]);
+        bytes.extend([0x00; 8]);
+        bytes.push(token_size as u8);
+        for index in 0..token_size {
+            bytes.extend([index as u8; 32]);
+            bytes.push(index as u8 + 1);
+        }
+        bytes.push(0x00);
+        bytes
+    }
+
+    #[test]
+    fn test_decode_output_with_tokens() {
+        let mut temp_data = TempData::new();
+        for token_size in 0..=MAX_OUTPUT_TOKENS {
+            let bytes = encode_output(token_size);
+            let mut buffer = Buffer::new(&bytes, &mut temp_data);
+            let mut decoder = new_decoder::<AssetOutput>();
+            let output = decoder.decode(&mut buffer).unwrap().unwrap();
+            let expected: Vec<Token> = (0..token_size)
+                .map(|index| {
+                    Token::from(
+                        Hash::from_bytes([index as u8; 32]),
+                        U256::from_encoded_bytes(&[index as u8 + 1]),
+                    )
+                })
+                .collect();
+            assert_eq!(output.get_tokens(), expected.as_slice());
+        }
+
+        let bytes = encode_output(MAX_OUTPUT_TOKENS + 1);
+        let mut buffer = Buffer::new(&bytes, &mut temp_data);
+        let mut decoder = new_decoder::<AssetOutput>();
+        assert!(matches!(
+            decoder.decode(&mut buffer),
+            Err(DecodeError::Overflow)
+        ));
+    }
+}
@@ -53,6 +53,12 @@ impl<T> AVector<T> {
         self.total_size.inner as usize
     }
 
+    // The length of the encoded size, the size is decoded with the first item
+    #[inline]
+    pub fn size_length(&self) -> usize {
+        self.total_size.get_length()
+    }
+
     #[inline]
     pub fn is_empty(&self) -> bool {
         self.size() == 0
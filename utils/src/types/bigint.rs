@@ -64,7 +64,7 @@ impl RawDecoder for BigInt {
             return Ok(DecodeStage { ..*stage });
         }
         let from_index = if stage.index == 0 {
-            self.bytes[0] = buffer.consume_byte().unwrap();
+            self.bytes[0] = buffer.consume_byte().ok_or(DecodeError::InternalError)?;
             1
         } else {
             stage.index
@@ -72,7 +72,8 @@ impl RawDecoder for BigInt {
         let length = self.get_length();
         let mut idx = 0;
         while !buffer.is_empty() && idx < (length - (from_index as usize)) {
-            self.bytes[(from_index as usize) + idx] = buffer.consume_byte().unwrap();
+            self.bytes[(from_index as usize) + idx] =
+                buffer.consume_byte().ok_or(DecodeError::InternalError)?;
             idx += 1;
         }
         let new_index = (from_index as usize) + idx;
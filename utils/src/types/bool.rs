@@ -24,7 +24,7 @@ impl RawDecoder for Bool {
         if buffer.is_empty() {
             return Ok(DecodeStage { ..*stage });
         }
-        let byte = buffer.consume_byte().unwrap();
+        let byte = buffer.consume_byte().ok_or(DecodeError::InternalError)?;
         self.0 = byte == 1;
         Ok(DecodeStage::COMPLETE)
     }
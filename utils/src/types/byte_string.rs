@@ -69,7 +69,7 @@ impl RawDecoder for ByteString {
         }
 
         while (self.current_index as usize) < self.size() && !buffer.is_empty() {
-            let _ = buffer.consume_byte().unwrap();
+            let _ = buffer.consume_byte().ok_or(DecodeError::InternalError)?;
             self.current_index += 1;
         }
 
@@ -134,9 +134,11 @@ impl I32 {
         from_index: usize,
     ) -> usize {
         let mut index = from_index;
-        while !buffer.is_empty() && index < length {
-            let byte = buffer.consume_byte().unwrap() as u32;
-            self.inner |= ((byte & 0xff) as i32) << ((length - index - 1) * 8);
+        while index < length {
+            let Some(byte) = buffer.consume_byte() else {
+                break;
+            };
+            self.inner |= (byte as i32) << ((length - index - 1) * 8);
             index += 1;
         }
         index
@@ -163,7 +165,7 @@ impl RawDecoder for I32 {
             return Ok(DecodeStage { ..*stage });
         }
         if stage.index == 0 {
-            self.first_byte = buffer.consume_byte().unwrap();
+            self.first_byte = buffer.consume_byte().ok_or(DecodeError::InternalError)?;
         }
         let length = self.get_length();
         if length > 5 {
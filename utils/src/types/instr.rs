@@ -445,7 +445,7 @@ impl RawDecoder for Instr {
             return Ok(DecodeStage { ..*stage });
         }
         if let Self::Unknown = self {
-            let tpe = buffer.consume_byte().unwrap();
+            let tpe = buffer.consume_byte().ok_or(DecodeError::InternalError)?;
             let result = Self::from_type(tpe);
             if let Some(instr) = result {
                 *self = instr;
@@ -1,4 +1,4 @@
-use super::{Byte32, Hash, U16};
+use super::{public_key::PublicKeyLike, Byte32, Hash, Hint, U16};
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
 
@@ -38,7 +38,7 @@ impl RawDecoder for P2MPKH {
                 let total_length = (self.size.inner as usize) * Byte32::ENCODED_LENGTH;
                 let mut index = stage.index;
                 while !buffer.is_empty() && (index as usize) < total_length {
-                    let _ = buffer.consume_byte().unwrap();
+                    let _ = buffer.consume_byte().ok_or(DecodeError::InternalError)?;
                     index += 1;
                 }
                 if (index as usize) == total_length {
@@ -64,6 +64,122 @@ impl RawDecoder for P2MPKH {
     }
 }
 
+// The size of the script hint of a groupless lockup script
+const SCRIPT_HINT_SIZE: usize = 4;
+
+// The groupless lockup script of a public key, the public key is prefixed with its type and
+// followed by the script hint, which the group of the lockup script is derived from
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct P2PK {
+    bytes: [u8; P2PK::MAX_ENCODED_LENGTH],
+    length: usize,
+}
+
+impl Default for P2PK {
+    fn default() -> Self {
+        Self {
+            bytes: [0; P2PK::MAX_ENCODED_LENGTH],
+            length: 0,
+        }
+    }
+}
+
+impl Reset for P2PK {
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl P2PK {
+    pub const MAX_ENCODED_LENGTH: usize = PublicKeyLike::MAX_ENCODED_LENGTH + SCRIPT_HINT_SIZE;
+
+    // The type of the public key followed by the public key
+    pub fn public_key(&self) -> &[u8] {
+        &self.bytes[..(self.length - SCRIPT_HINT_SIZE)]
+    }
+
+    pub fn script_hint(&self) -> i32 {
+        let mut hint = [0u8; SCRIPT_HINT_SIZE];
+        hint.copy_from_slice(&self.bytes[(self.length - SCRIPT_HINT_SIZE)..self.length]);
+        i32::from_be_bytes(hint)
+    }
+
+    pub fn get_group(&self, group_num: u8) -> u8 {
+        crate::xor_bytes(self.script_hint()) % group_num
+    }
+}
+
+impl RawDecoder for P2PK {
+    fn step_size(&self) -> u16 {
+        1
+    }
+
+    fn decode<W: Writable>(
+        &mut self,
+        buffer: &mut Buffer<'_, W>,
+        stage: &DecodeStage,
+    ) -> DecodeResult<DecodeStage> {
+        let mut index = stage.index as usize;
+        while !buffer.is_empty() && (index == 0 || index < self.length) {
+            let byte = buffer.consume_byte().ok_or(DecodeError::InternalError)?;
+            if index == 0 {
+                let key_length = PublicKeyLike::key_length(byte).ok_or(DecodeError::InvalidData)?;
+                self.length = 1 + key_length + SCRIPT_HINT_SIZE;
+            }
+            self.bytes[index] = byte;
+            index += 1;
+        }
+        if index != 0 && index == self.length {
+            Ok(DecodeStage::COMPLETE)
+        } else {
+            Ok(DecodeStage {
+                step: stage.step,
+                index: index as u16,
+            })
+        }
+    }
+}
+
+// The groupless lockup script of a multi-sig wallet, the hash of the public keys and the
+// threshold followed by the script hint
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Default)]
+pub struct P2HMPK {
+    pub hash: Hash,
+    pub script_hint: Hint,
+}
+
+impl Reset for P2HMPK {
+    fn reset(&mut self) {
+        self.hash.reset();
+        self.script_hint.reset();
+    }
+}
+
+impl P2HMPK {
+    pub fn get_group(&self, group_num: u8) -> u8 {
+        crate::xor_bytes(i32::from_be_bytes(self.script_hint.0)) % group_num
+    }
+}
+
+impl RawDecoder for P2HMPK {
+    fn step_size(&self) -> u16 {
+        2
+    }
+
+    fn decode<W: Writable>(
+        &mut self,
+        buffer: &mut Buffer<'_, W>,
+        stage: &DecodeStage,
+    ) -> DecodeResult<DecodeStage> {
+        match stage.step {
+            0 => self.hash.decode(buffer, stage),
+            1 => self.script_hint.decode(buffer, stage),
+            _ => Err(DecodeError::InternalError),
+        }
+    }
+}
+
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Default)]
 pub enum LockupScript {
@@ -71,6 +187,8 @@ pub enum LockupScript {
     P2MPKH(StreamingDecoder<P2MPKH>),
     P2SH(Hash),
     P2C(Hash),
+    P2PK(P2PK),
+    P2HMPK(StreamingDecoder<P2HMPK>),
     #[default]
     Unknown,
 }
@@ -88,6 +206,8 @@ impl LockupScript {
             1 => Some(LockupScript::P2MPKH(StreamingDecoder::default())),
             2 => Some(LockupScript::P2SH(Hash::default())),
             3 => Some(LockupScript::P2C(Hash::default())),
+            4 => Some(LockupScript::P2PK(P2PK::default())),
+            5 => Some(LockupScript::P2HMPK(StreamingDecoder::default())),
             _ => None,
         }
     }
@@ -98,6 +218,8 @@ impl LockupScript {
             LockupScript::P2MPKH(_) => 1,
             LockupScript::P2SH(_) => 2,
             LockupScript::P2C(_) => 3,
+            LockupScript::P2PK(_) => 4,
+            LockupScript::P2HMPK(_) => 5,
             _ => 0xff, // dead branch
         }
     }
@@ -117,18 +239,16 @@ impl RawDecoder for LockupScript {
             return Ok(DecodeStage { ..*stage });
         }
         if let LockupScript::Unknown = self {
-            let tpe = buffer.consume_byte().unwrap();
-            let result = LockupScript::from_type(tpe);
-            if result.is_none() {
-                return Err(DecodeError::InvalidData);
-            }
-            *self = result.unwrap();
+            let tpe = buffer.consume_byte().ok_or(DecodeError::InternalError)?;
+            *self = LockupScript::from_type(tpe).ok_or(DecodeError::InvalidData)?;
         };
         match self {
             LockupScript::P2PKH(hash) => hash.decode(buffer, stage),
             LockupScript::P2MPKH(hashes) => hashes.decode_children(buffer, stage),
             LockupScript::P2SH(hash) => hash.decode(buffer, stage),
             LockupScript::P2C(hash) => hash.decode(buffer, stage),
+            LockupScript::P2PK(p2pk) => p2pk.decode(buffer, stage),
+            LockupScript::P2HMPK(p2hmpk) => p2hmpk.decode_children(buffer, stage),
             LockupScript::Unknown => Err(DecodeError::InternalError),
         }
     }
@@ -197,6 +317,81 @@ mod tests {
         test(3, |hash| LockupScript::P2C(hash))
     }
 
+    #[test]
+    fn test_decode_p2pk() {
+        // A secp256k1 key and an ed25519 key, each followed by the script hint
+        let secp256k1_key = "00034a6b2c7f6de91d2d1f0bb2e1a4a55ffbdd5b9c1a9e31f44c0e2d1f6b7e8a9c1d";
+        let ed25519_key = "024a6b2c7f6de91d2d1f0bb2e1a4a55ffbdd5b9c1a9e31f44c0e2d1f6b7e8a9c1d";
+        for key in [secp256k1_key, ed25519_key] {
+            let bytes = hex_to_bytes(&std::format!("04{}0a0b0c0e", key)).unwrap();
+            let mut temp_data = TempData::new();
+            let mut length: usize = 0;
+            let mut decoder = new_decoder::<LockupScript>();
+
+            while length < bytes.len() {
+                let remain = bytes.len() - length;
+                let size = random_usize(0, remain);
+                let mut buffer = Buffer::new(&bytes[length..(length + size)], &mut temp_data);
+                length += size;
+
+                let result = decoder.decode(&mut buffer).unwrap();
+                if length == bytes.len() {
+                    match result {
+                        Some(LockupScript::P2PK(p2pk)) => {
+                            assert_eq!(p2pk.public_key(), hex_to_bytes(key).unwrap());
+                            assert_eq!(p2pk.script_hint(), 0x0a0b0c0e);
+                            assert_eq!(p2pk.get_group(4), 3);
+                        }
+                        _ => panic!("expected a P2PK lockup script"),
+                    }
+                    assert!(decoder.stage.is_complete());
+                } else {
+                    assert_eq!(result, None);
+                }
+            }
+        }
+
+        // Unknown public key type
+        let bytes = hex_to_bytes("0405").unwrap();
+        let mut temp_data = TempData::new();
+        let mut buffer = Buffer::new(&bytes, &mut temp_data);
+        let mut decoder = new_decoder::<LockupScript>();
+        assert!(decoder.decode(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn test_decode_p2hmpk() {
+        let bytes = hex_to_bytes(
+            "05a3cd757be03c7dac8d48bf79e2a7d6e735e018a9c054b99138c7b29738c437ec0a0b0c0e",
+        )
+        .unwrap();
+        let mut temp_data = TempData::new();
+        let mut length: usize = 0;
+        let mut decoder = new_decoder::<LockupScript>();
+
+        while length < bytes.len() {
+            let remain = bytes.len() - length;
+            let size = random_usize(0, remain);
+            let mut buffer = Buffer::new(&bytes[length..(length + size)], &mut temp_data);
+            length += size;
+
+            let result = decoder.decode(&mut buffer).unwrap();
+            if length == bytes.len() {
+                match result {
+                    Some(LockupScript::P2HMPK(p2hmpk)) => {
+                        assert_eq!(p2hmpk.inner.hash.0, &bytes[1..33]);
+                        assert_eq!(p2hmpk.inner.script_hint.0, [0x0a, 0x0b, 0x0c, 0x0e]);
+                        assert_eq!(p2hmpk.inner.get_group(4), 3);
+                    }
+                    _ => panic!("expected a P2HMPK lockup script"),
+                }
+                assert!(decoder.stage.is_complete());
+            } else {
+                assert_eq!(result, None);
+            }
+        }
+    }
+
     #[test]
     fn test_decode_p2mpkh() {
         let bytes = hex_to_bytes("0103a3cd757be03c7dac8d48bf79e2a7d6e735e018a9c054b99138c7b29738c437ecef51c98556924afa1cd1a8026c3d2d33ee1d491e1fe77c73a75a2d0129f061951dd2aa371711d1faea1c96d395f08eb94de1f388993e8be3f4609dc327ab513a02").unwrap();
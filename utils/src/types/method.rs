@@ -2,18 +2,62 @@ use super::*;
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
 
+// Follows the instrs of the methods of a script as they are decoded. Only the main script of a tx
+// is tracked, the scripts of P2SH unlock scripts use the empty tracker `()`
+pub trait InstrTracker: Default + Reset {
+    // The result of all the methods of a script
+    type Summary: Default + Reset;
+
+    // The encoded bytes of the current instr, called once or more before `on_instr`
+    fn on_instr_bytes(&mut self, bytes: &[u8]);
+    fn on_instr(&mut self, instr: &Instr);
+    fn on_method(&self, method_index: usize, summary: &mut Self::Summary);
+}
+
+impl Reset for () {
+    fn reset(&mut self) {}
+}
+
+impl InstrTracker for () {
+    type Summary = ();
+
+    fn on_instr_bytes(&mut self, _bytes: &[u8]) {}
+    fn on_instr(&mut self, _instr: &Instr) {}
+    fn on_method(&self, _method_index: usize, _summary: &mut ()) {}
+}
+
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Default)]
-pub struct Method {
+pub struct Method<T: InstrTracker = ()> {
     is_public: Byte,
     asset_modifier: Byte,
     args_length: U16,
     locals_length: U16,
     return_length: U16,
     instrs: AVector<Instr>,
+    tracker: T,
+}
+
+impl<T: InstrTracker> Method<T> {
+    #[inline]
+    pub fn tracker(&self) -> &T {
+        &self.tracker
+    }
 }
 
-impl Reset for Method {
+impl Method<ApprovalTracker> {
+    #[inline]
+    pub fn approved_assets(&self) -> &ApprovedAssets {
+        &self.tracker.approved_assets
+    }
+
+    #[inline]
+    pub fn contract_calls(&self) -> &ContractCalls {
+        &self.tracker.contract_calls
+    }
+}
+
+impl<T: InstrTracker> Reset for Method<T> {
     fn reset(&mut self) {
         self.is_public.reset();
         self.asset_modifier.reset();
@@ -21,10 +65,11 @@ impl Reset for Method {
         self.locals_length.reset();
         self.return_length.reset();
         self.instrs.reset();
+        self.tracker.reset();
     }
 }
 
-impl RawDecoder for Method {
+impl<T: InstrTracker> RawDecoder for Method<T> {
     fn step_size(&self) -> u16 {
         5 + self.instrs.step_size()
     }
@@ -40,8 +85,77 @@ impl RawDecoder for Method {
             2 => self.args_length.decode(buffer, stage),
             3 => self.locals_length.decode(buffer, stage),
             4 => self.return_length.decode(buffer, stage),
-            step if step < self.step_size() => self.instrs.decode(buffer, stage),
+            step if step < self.step_size() => {
+                // The instr count is decoded with the first instr, its bytes are skipped so that
+                // the bytes of the first instr are tracked alone. The stage index is the number
+                // of bytes of the count that were decoded in the previous frames
+                let decoded_size_length = if self.instrs.current_index < 0 {
+                    Some(stage.index as usize)
+                } else {
+                    None
+                };
+                let mut from_index = buffer.get_index();
+                let result = self.instrs.decode(buffer, stage)?;
+                if let Some(decoded_size_length) = decoded_size_length {
+                    if self.instrs.current_index < 0 {
+                        return Ok(result);
+                    }
+                    from_index += self.instrs.size_length() - decoded_size_length;
+                }
+                let to_index = buffer.get_index();
+                self.tracker
+                    .on_instr_bytes(buffer.get_range(from_index, to_index));
+                if result.is_complete() {
+                    if let Some(instr) = self.instrs.get_current_item() {
+                        self.tracker.on_instr(instr);
+                    }
+                }
+                Ok(result)
+            }
             _ => Err(DecodeError::InternalError),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::Method;
+    use crate::buffer::Buffer;
+    use crate::decode::{new_decoder, Decoder};
+    use crate::types::u256::tests::hex_to_bytes;
+    use crate::types::ApprovalTracker;
+    use crate::TempData;
+    use std::format;
+
+    #[test]
+    fn test_approvals_with_token_id_const_at_method_start() {
+        let token_id = "5bf2f559ae714dab83ff36bed4d9e634dfda3ca9ed755d60f00be89e2a20bd00";
+        // BytesConst, StoreLocal(0), CallerAddress, LoadLocal(0), U256Const, ApproveToken
+        let instrs = format!("144020{}1700b4160013c5056bc75e2d63100000a3", token_id);
+        let methods = [
+            format!("010100010006{}", instrs),
+            // The 2-byte instr count of 64 instrs
+            format!("0101000100{}{}{}", "4040", instrs, "02".repeat(58)),
+        ];
+        for method_hex in methods.iter() {
+            let bytes = hex_to_bytes(method_hex).unwrap();
+            for frame_size in [1, 7, bytes.len()] {
+                let mut temp_data = TempData::new();
+                let mut decoder = new_decoder::<Method<ApprovalTracker>>();
+                let mut chunks = bytes.chunks(frame_size).peekable();
+                while let Some(chunk) = chunks.next() {
+                    let mut buffer = Buffer::new(chunk, &mut temp_data);
+                    let result = decoder.decode(&mut buffer).unwrap();
+                    assert_eq!(result.is_some(), chunks.peek().is_none());
+                }
+                let approved_assets = decoder.inner.approved_assets();
+                assert!(approved_assets.is_determinable());
+                assert_eq!(approved_assets.get_assets().len(), 1);
+                let asset = &approved_assets.get_assets()[0];
+                assert_eq!(asset.id.0.as_slice(), hex_to_bytes(token_id).unwrap());
+            }
+        }
+    }
+}
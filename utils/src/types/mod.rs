@@ -1,9 +1,10 @@
+pub mod approved_assets;
 pub mod asset_output;
 pub mod avector;
 pub mod bool;
 pub mod byte;
 pub mod byte32;
-mod compact_integer;
+pub(crate) mod compact_integer;
 pub mod hint;
 pub mod i256;
 pub mod i32;
@@ -33,6 +34,7 @@ pub use u256::U256;
 pub use self::bool::Bool;
 pub use self::i32::I32;
 pub use self::u16::U16;
+pub use approved_assets::{ApprovalTracker, ApprovedAssets, ContractCalls, ScriptApprovals};
 pub use asset_output::AssetOutput;
 pub use avector::AVector;
 pub use bigint::BigInt;
@@ -41,9 +43,9 @@ pub use byte_string::ByteString;
 pub use hint::Hint;
 pub use instr::Instr;
 pub use lockup_script::LockupScript;
-pub use method::Method;
+pub use method::{InstrTracker, Method};
 pub use public_key::PublicKey;
-pub use script::Script;
+pub use script::{Script, TxScript};
 pub use timestamp::TimeStamp;
 pub use token::Token;
 pub use tx_input::TxInput;
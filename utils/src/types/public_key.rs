@@ -1,17 +1,143 @@
-use crate::buffer::Buffer;
+use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
 use crate::fixed_size_bytes;
 
 fixed_size_bytes!(PublicKey, 33);
 
+pub const UNCOMPRESSED_PUBLIC_KEY_SIZE: usize = 65;
+
+// The prefix of a compressed secp256k1 public key is the parity of the y coordinate
+pub fn compress_public_key(pub_key: &[u8]) -> [u8; PublicKey::ENCODED_LENGTH] {
+    assert!(pub_key.len() == UNCOMPRESSED_PUBLIC_KEY_SIZE);
+    let mut compressed = [0_u8; PublicKey::ENCODED_LENGTH];
+    compressed[1..].copy_from_slice(&pub_key[1..PublicKey::ENCODED_LENGTH]);
+    if pub_key.last().unwrap().is_multiple_of(2) {
+        compressed[0] = 0x02
+    } else {
+        compressed[0] = 0x03
+    }
+    compressed
+}
+
+// The encoding of the public key returned by `GetPubKey`
+#[cfg_attr(test, derive(Debug))]
+#[derive(Clone, Copy, PartialEq)]
+pub enum PublicKeyEncoding {
+    Uncompressed,
+    Compressed,
+}
+
+impl PublicKeyEncoding {
+    // The highest bit of P2 selects the compressed encoding, the other bits are the target group
+    const COMPRESSED_FLAG: u8 = 0x80;
+
+    pub fn from_p2(p2: u8) -> (Self, u8) {
+        if p2 & Self::COMPRESSED_FLAG != 0 {
+            (Self::Compressed, p2 & !Self::COMPRESSED_FLAG)
+        } else {
+            (Self::Uncompressed, p2)
+        }
+    }
+
+    // Encode an uncompressed public key
+    pub fn encode<'a>(
+        &self,
+        pub_key: &[u8],
+        output: &'a mut [u8; UNCOMPRESSED_PUBLIC_KEY_SIZE],
+    ) -> &'a [u8] {
+        match self {
+            Self::Uncompressed => {
+                output.copy_from_slice(pub_key);
+                &output[..]
+            }
+            Self::Compressed => {
+                output[..PublicKey::ENCODED_LENGTH].copy_from_slice(&compress_public_key(pub_key));
+                &output[..PublicKey::ENCODED_LENGTH]
+            }
+        }
+    }
+}
+
+// A public key of a groupless address prefixed with its type, the public keys of secp256k1,
+// secp256r1 and WebAuthn are compressed, ed25519 keys are 32 bytes
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct PublicKeyLike {
+    bytes: [u8; PublicKeyLike::MAX_ENCODED_LENGTH],
+    length: usize,
+}
+
+impl Default for PublicKeyLike {
+    fn default() -> Self {
+        Self {
+            bytes: [0; PublicKeyLike::MAX_ENCODED_LENGTH],
+            length: 0,
+        }
+    }
+}
+
+impl Reset for PublicKeyLike {
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl PublicKeyLike {
+    pub const MAX_ENCODED_LENGTH: usize = 1 + PublicKey::ENCODED_LENGTH;
+
+    pub fn key_length(key_type: u8) -> Option<usize> {
+        match key_type {
+            0 | 1 | 3 => Some(33),
+            2 => Some(32),
+            _ => None,
+        }
+    }
+
+    // The type of the public key followed by the public key
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.length]
+    }
+}
+
+impl RawDecoder for PublicKeyLike {
+    fn step_size(&self) -> u16 {
+        1
+    }
+
+    fn decode<W: Writable>(
+        &mut self,
+        buffer: &mut Buffer<'_, W>,
+        stage: &DecodeStage,
+    ) -> DecodeResult<DecodeStage> {
+        let mut index = stage.index as usize;
+        while !buffer.is_empty() && (index == 0 || index < self.length) {
+            let byte = buffer.consume_byte().ok_or(DecodeError::InternalError)?;
+            if index == 0 {
+                let key_length = Self::key_length(byte).ok_or(DecodeError::InvalidData)?;
+                self.length = 1 + key_length;
+            }
+            self.bytes[index] = byte;
+            index += 1;
+        }
+        if index != 0 && index == self.length {
+            Ok(DecodeStage::COMPLETE)
+        } else {
+            Ok(DecodeStage {
+                step: stage.step,
+                index: index as u16,
+            })
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     extern crate std;
 
-    use super::PublicKey;
+    use super::{PublicKey, PublicKeyEncoding, PublicKeyLike, UNCOMPRESSED_PUBLIC_KEY_SIZE};
     use crate::buffer::Buffer;
     use crate::decode::{new_decoder, Decoder};
     use crate::types::byte32::tests::gen_bytes;
+    use crate::types::u256::tests::hex_to_bytes;
     use crate::TempData;
     use std::vec;
 
@@ -37,4 +163,66 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_public_key_encoding() {
+        // The public key of the private key 1 has an even y coordinate, only the parity of the
+        // last byte is used for the prefix
+        let even_key = hex_to_bytes("0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8").unwrap();
+        let mut odd_key = even_key.clone();
+        odd_key[UNCOMPRESSED_PUBLIC_KEY_SIZE - 1] |= 1;
+
+        for (key, prefix) in [(even_key, 0x02), (odd_key, 0x03)] {
+            let mut output = [0u8; UNCOMPRESSED_PUBLIC_KEY_SIZE];
+            let encoded = PublicKeyEncoding::Uncompressed.encode(&key, &mut output);
+            assert_eq!(encoded, &key[..]);
+
+            let mut output = [0u8; UNCOMPRESSED_PUBLIC_KEY_SIZE];
+            let encoded = PublicKeyEncoding::Compressed.encode(&key, &mut output);
+            assert_eq!(encoded.len(), PublicKey::ENCODED_LENGTH);
+            assert_eq!(encoded[0], prefix);
+            assert_eq!(&encoded[1..], &key[1..PublicKey::ENCODED_LENGTH]);
+        }
+
+        assert_eq!(
+            PublicKeyEncoding::from_p2(0),
+            (PublicKeyEncoding::Uncompressed, 0)
+        );
+        assert_eq!(
+            PublicKeyEncoding::from_p2(3),
+            (PublicKeyEncoding::Uncompressed, 3)
+        );
+        assert_eq!(
+            PublicKeyEncoding::from_p2(0x80),
+            (PublicKeyEncoding::Compressed, 0)
+        );
+        assert_eq!(
+            PublicKeyEncoding::from_p2(0x82),
+            (PublicKeyEncoding::Compressed, 2)
+        );
+    }
+
+    #[test]
+    fn test_decode_public_key_like() {
+        let mut temp_data = TempData::new();
+        // A secp256k1 key and an ed25519 key
+        for (key_type, key_length) in [(0u8, 33), (2u8, 32)] {
+            let mut bytes = vec![key_type];
+            bytes.extend(gen_bytes(key_length, key_length));
+            bytes.push(0xff); // the next field
+
+            let mut decoder = new_decoder::<PublicKeyLike>();
+            let mut buffer = Buffer::new(&bytes[..key_length], &mut temp_data);
+            assert_eq!(decoder.decode(&mut buffer), Ok(None));
+            let mut buffer = Buffer::new(&bytes[key_length..], &mut temp_data);
+            let result = decoder.decode(&mut buffer).unwrap().unwrap();
+            assert_eq!(result.as_bytes(), &bytes[..(key_length + 1)]);
+            assert!(!buffer.is_empty());
+        }
+
+        // Unknown public key type
+        let mut decoder = new_decoder::<PublicKeyLike>();
+        let mut buffer = Buffer::new(&[4u8, 0], &mut temp_data);
+        assert!(decoder.decode(&mut buffer).is_err());
+    }
 }
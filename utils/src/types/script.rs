@@ -4,15 +4,33 @@ use crate::decode::*;
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Default)]
-pub struct Script(AVector<Method>);
+pub struct Script<T: InstrTracker = ()>(AVector<Method<T>>, T::Summary);
 
-impl Reset for Script {
+// The main script of a tx, its approvals and contract calls are displayed
+pub type TxScript = Script<ApprovalTracker>;
+
+impl<T: InstrTracker> Reset for Script<T> {
     fn reset(&mut self) {
         self.0.reset();
+        self.1.reset();
+    }
+}
+
+impl TxScript {
+    // The assets approved by the main method of the script
+    #[inline]
+    pub fn approved_assets(&self) -> &ApprovedAssets {
+        &self.1.approved_assets
+    }
+
+    // The contract calls of the main method of the script
+    #[inline]
+    pub fn contract_calls(&self) -> &ContractCalls {
+        &self.1.contract_calls
     }
 }
 
-impl RawDecoder for Script {
+impl<T: InstrTracker> RawDecoder for Script<T> {
     fn step_size(&self) -> u16 {
         self.0.step_size()
     }
@@ -23,8 +41,156 @@ impl RawDecoder for Script {
         stage: &DecodeStage,
     ) -> DecodeResult<DecodeStage> {
         match stage.step {
-            step if step < self.step_size() => self.0.decode(buffer, stage),
+            step if step < self.step_size() => {
+                let result = self.0.decode(buffer, stage)?;
+                if result.is_complete() {
+                    if let Some(method) = self.0.get_current_item() {
+                        let method_index = self.0.current_index as usize;
+                        method.tracker().on_method(method_index, &mut self.1);
+                    }
+                }
+                Ok(result)
+            }
             _ => Err(DecodeError::InternalError),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::TxScript;
+    use crate::buffer::Buffer;
+    use crate::decode::{new_decoder, Decoder};
+    use crate::types::approved_assets::{CallMethod, Const, ALPH_TOKEN_ID};
+    use crate::types::u256::tests::hex_to_bytes;
+    use crate::types::U256;
+    use crate::TempData;
+    use std::format;
+
+    const TOKEN_ID: &str = "5bf2f559ae714dab83ff36bed4d9e634dfda3ca9ed755d60f00be89e2a20bd00";
+    const AMOUNT: &str = "c5056bc75e2d63100000";
+
+    // A method without args and returns, with 1 local
+    fn method(instr_count: u8, instrs: &str) -> std::string::String {
+        format!("0101000100{:02x}{}", instr_count, instrs)
+    }
+
+    // BytesConst, StoreLocal(0), CallerAddress, LoadLocal(0), U256Const, ApproveToken
+    fn approve_token_instrs() -> std::string::String {
+        format!("144020{}1700b4160013{}a3", TOKEN_ID, AMOUNT)
+    }
+
+    fn decode_script(script_hex: &str, frame_size: usize) -> TxScript {
+        let bytes = hex_to_bytes(script_hex).unwrap();
+        let mut temp_data = TempData::new();
+        let mut decoder = new_decoder::<TxScript>();
+        let mut chunks = bytes.chunks(frame_size).peekable();
+        while let Some(chunk) = chunks.next() {
+            let mut buffer = Buffer::new(chunk, &mut temp_data);
+            let result = decoder.decode(&mut buffer).unwrap();
+            assert_eq!(result.is_some(), chunks.peek().is_none());
+            assert!(buffer.is_empty());
+        }
+        decoder.inner
+    }
+
+    #[test]
+    fn test_decode_script() {
+        // The main method approves a token, the second method only returns
+        let script_hex = format!(
+            "02{}{}",
+            method(6, &approve_token_instrs()),
+            method(1, "02")
+        );
+        for frame_size in [1, 7, script_hex.len() / 2] {
+            let script = decode_script(&script_hex, frame_size);
+            assert_eq!(script.0.size(), 2);
+
+            let approved_assets = script.approved_assets();
+            assert!(approved_assets.is_determinable());
+            assert_eq!(approved_assets.get_assets().len(), 1);
+            let asset = &approved_assets.get_assets()[0];
+            assert_eq!(asset.id.0.as_slice(), hex_to_bytes(TOKEN_ID).unwrap());
+            let amount = U256::from_encoded_bytes(&hex_to_bytes(AMOUNT).unwrap());
+            assert_eq!(asset.amount, amount);
+            assert!(script.contract_calls().is_empty());
+            assert!(script.contract_calls().is_determinable());
+        }
+    }
+
+    #[test]
+    fn test_decode_script_with_large_instr_count() {
+        // The 2-byte instr count of 64 instrs is split across the frames of 1 byte
+        let instrs = format!("{}{}", approve_token_instrs(), "02".repeat(58));
+        let script_hex = format!("010101000100{}{}", "4040", instrs);
+        for frame_size in [1, 7, script_hex.len() / 2] {
+            let script = decode_script(&script_hex, frame_size);
+            let approved_assets = script.approved_assets();
+            assert!(approved_assets.is_determinable());
+            assert_eq!(approved_assets.get_assets().len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_decode_script_with_approvals_in_other_methods() {
+        // The approvals of a method that is not the main method depend on how it is called
+        let script_hex = format!(
+            "02{}{}",
+            method(1, "02"),
+            method(6, &approve_token_instrs())
+        );
+        let script = decode_script(&script_hex, 1);
+        assert!(script.approved_assets().is_empty());
+        assert!(!script.approved_assets().is_determinable());
+    }
+
+    // The scripts of the upstream tx vectors in `unsigned_tx.rs`
+    const TOKEN_SCRIPT: &str = "0101030001000b1440205bf2f559ae714dab83ff36bed4d9e634dfda3ca9ed755d60f00be89e2a20bd001700b4160013c5056bc75e2d63100000a313c5056bc75e2d631000000d0c1440205bf2f559ae714dab83ff36bed4d9e634dfda3ca9ed755d60f00be89e2a20bd00010e";
+    const RHONE_SCRIPT: &str = "0101030002000f144020923e3a6a5136338eed4fe14d96f219b192cea5c0c38a00b24186dc377b6b8b5c13016413006417011700150070f425d78af36c5c555b99098089da6a7ba34af9128e6e5d2dff1d457422a42d13c4016345785d8a0000a214409f0101409b01010000001cd38d0b363615036e06d76427087982b93922f578c6be64e0e69d60cfe6866f810a5877971d14008c0c2f0c7b15036e06d76427087982b93922f578c6be64e0e69d60cfe6866f810a5877971d1400d1a2140a5f5f6d61705f5f305f5f0c40440c0db1d20300b281d015036e06d76427087982b93922f578c6be64e0e69d60cfe6866f810a5877971d1400d1a20c0cce00d41e406a2f16001601ad18";
+
+    #[test]
+    fn test_decode_upstream_token_script() {
+        // Approves a token and calls the method 14 of the token contract with the amount
+        for frame_size in [1, 7, TOKEN_SCRIPT.len() / 2] {
+            let script = decode_script(TOKEN_SCRIPT, frame_size);
+            assert_eq!(script.0.size(), 1);
+
+            let approved_assets = script.approved_assets();
+            assert!(approved_assets.is_determinable());
+            assert_eq!(approved_assets.get_assets().len(), 1);
+            let asset = &approved_assets.get_assets()[0];
+            assert_eq!(asset.id.0.as_slice(), hex_to_bytes(TOKEN_ID).unwrap());
+            let amount = U256::from_encoded_bytes(&hex_to_bytes(AMOUNT).unwrap());
+            assert_eq!(asset.amount, amount);
+
+            let contract_calls = script.contract_calls();
+            assert!(contract_calls.is_determinable());
+            assert_eq!(contract_calls.get_calls().len(), 1);
+            let call = &contract_calls.get_calls()[0];
+            assert_eq!(call.contract_id.as_slice(), hex_to_bytes(TOKEN_ID).unwrap());
+            assert_eq!(call.method, CallMethod::Index(14));
+            assert_eq!(call.args(), Some(&[Const::U256(amount)][..]));
+        }
+    }
+
+    #[test]
+    fn test_decode_upstream_rhone_script() {
+        // Approves 0.1 ALPH to create a contract, the contract bytecode is a bytes constant
+        for frame_size in [1, 7, RHONE_SCRIPT.len() / 2] {
+            let script = decode_script(RHONE_SCRIPT, frame_size);
+            assert_eq!(script.0.size(), 1);
+
+            let approved_assets = script.approved_assets();
+            assert!(approved_assets.is_determinable());
+            assert_eq!(approved_assets.get_assets().len(), 1);
+            let asset = &approved_assets.get_assets()[0];
+            assert_eq!(asset.id.0, ALPH_TOKEN_ID);
+            assert_eq!(asset.amount, U256::encode_u128(100000000000000000));
+
+            assert!(script.contract_calls().is_determinable());
+            assert!(script.contract_calls().is_empty());
+        }
+    }
+}
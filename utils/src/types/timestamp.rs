@@ -4,6 +4,54 @@ use crate::fixed_size_integer;
 
 fixed_size_integer!(TimeStamp, 8, u64);
 
+impl TimeStamp {
+    // The length of a formatted timestamp, e.g. `2025-03-01 12:00:00 UTC`
+    pub const UTC_STR_LENGTH: usize = 23;
+
+    // Format the timestamp in milliseconds as a UTC date time, returns `None` if the year exceeds 9999
+    pub fn to_utc_str<'a>(&self, output: &'a mut [u8; Self::UTC_STR_LENGTH]) -> Option<&'a [u8]> {
+        let seconds = self.0 / 1000;
+        let days = seconds / 86400;
+        let seconds_of_day = seconds % 86400;
+
+        // Convert the days since 1970-01-01 to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+        let z = days + 719468;
+        let era = z / 146097;
+        let day_of_era = z - era * 146097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+        if year > 9999 {
+            return None;
+        }
+
+        let parts = [
+            (year, 4, b'-'),
+            (month, 2, b'-'),
+            (day, 2, b' '),
+            (seconds_of_day / 3600, 2, b':'),
+            (seconds_of_day % 3600 / 60, 2, b':'),
+            (seconds_of_day % 60, 2, b' '),
+        ];
+        let mut index = 0;
+        for (value, digits, separator) in parts {
+            for i in 0..digits {
+                let divisor = 10u64.pow((digits - i - 1) as u32);
+                output[index] = b'0' + ((value / divisor) % 10) as u8;
+                index += 1;
+            }
+            output[index] = separator;
+            index += 1;
+        }
+        output[index..].copy_from_slice(b"UTC");
+        Some(&output[..])
+    }
+}
+
 #[cfg(test)]
 mod tests {
     extern crate std;
@@ -45,4 +93,23 @@ mod tests {
             assert_eq!(decoder.decode(&mut buffer), Ok(Some(&TimeStamp(number))));
         }
     }
+
+    #[test]
+    fn test_to_utc_str() {
+        let cases: [(u64, &[u8]); 5] = [
+            (0, b"1970-01-01 00:00:00 UTC"),
+            (1705610859116, b"2024-01-18 20:47:39 UTC"),
+            (1709251199999, b"2024-02-29 23:59:59 UTC"),
+            (1740830400000, b"2025-03-01 12:00:00 UTC"),
+            (253402300799999, b"9999-12-31 23:59:59 UTC"),
+        ];
+        for (millis, expected) in cases {
+            let mut output = [0u8; TimeStamp::UTC_STR_LENGTH];
+            assert_eq!(TimeStamp(millis).to_utc_str(&mut output), Some(expected));
+        }
+
+        let mut output = [0u8; TimeStamp::UTC_STR_LENGTH];
+        assert_eq!(TimeStamp(253402300800000).to_utc_str(&mut output), None);
+        assert_eq!(TimeStamp(u64::MAX).to_utc_str(&mut output), None);
+    }
 }
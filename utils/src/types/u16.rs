@@ -50,7 +50,7 @@ impl RawDecoder for U16 {
             return Ok(DecodeStage { ..*stage });
         }
         if stage.index == 0 {
-            self.first_byte = buffer.consume_byte().unwrap();
+            self.first_byte = buffer.consume_byte().ok_or(DecodeError::InternalError)?;
         }
         let length = self.get_length();
         if length > 4 {
@@ -65,7 +65,7 @@ impl RawDecoder for U16 {
         };
 
         while !buffer.is_empty() && index < length {
-            let byte = buffer.consume_byte().unwrap() as u32;
+            let byte = buffer.consume_byte().ok_or(DecodeError::InternalError)? as u32;
             self.inner |= ((byte & 0xff) << ((length - index - 1) * 8)) as u16;
             index += 1;
         }
@@ -3,11 +3,34 @@ use crate::decode::*;
 use crate::types::compact_integer::*;
 
 use super::{reset, BigInt};
+use core::cmp::Ordering;
 
 #[cfg_attr(test, derive(Debug))]
-#[derive(Default, PartialEq, Clone)]
+#[derive(Default, Clone)]
 pub struct U256(pub BigInt);
 
+// The amounts are compared by value, so that the same value in the fixed size and the
+// multi-byte encodings is equal
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.to_be_bytes().cmp(&other.to_be_bytes())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for U256 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for U256 {}
+
 impl Reset for U256 {
     fn reset(&mut self) {
         self.0.reset();
@@ -30,6 +53,42 @@ fn trim(dest: &[u8]) -> &[u8] {
     }
 }
 
+// Insert the separator between the groups of 3 digits of the integer part of a formatted
+// number, e.g. `1234567.25` is formatted as `1,234,567.25`
+fn group_digits(output: &mut [u8], length: usize, separator: &[u8]) -> Option<usize> {
+    let integer_length = output[..length]
+        .iter()
+        .position(|&c| c == b'.')
+        .unwrap_or(length);
+    let separators_length = (integer_length.saturating_sub(1) / 3) * separator.len();
+    let total_length = length + separators_length;
+    if total_length > output.len() {
+        return None;
+    }
+    // The digits are moved from the end, so that they are not overwritten before they are moved
+    output.copy_within(integer_length..length, integer_length + separators_length);
+    let mut to_index = integer_length + separators_length;
+    for (count, from_index) in (0..integer_length).rev().enumerate() {
+        if count != 0 && count % 3 == 0 {
+            to_index -= separator.len();
+            output[to_index..(to_index + separator.len())].copy_from_slice(separator);
+        }
+        to_index -= 1;
+        output[to_index] = output[from_index];
+    }
+    Some(total_length)
+}
+
+// The separator of the groups of 3 digits of the displayed amounts
+pub const THOUSANDS_SEPARATOR: &[u8] = b",";
+// An ALPH amount up to the total supply with separators, e.g. `ALPH 1,000,000,000.000000000000000001`
+pub const MAX_ALPH_AMOUNT_LENGTH: usize = 40;
+pub const MAX_TOKEN_SYMBOL_LENGTH: usize = 12;
+// A token amount with the longest symbol and separators. The decimals are a u8, so the U256 max
+// formatted with 255 decimals, i.e. `0.` followed by 255 digits, is longer than any amount with
+// an integer part, e.g. the U256 max with 1 decimal and separators has 104 chars
+pub const MAX_TOKEN_AMOUNT_LENGTH: usize = MAX_TOKEN_SYMBOL_LENGTH + 1 + 2 + u8::MAX as usize;
+
 impl U256 {
     const ALPH_DECIMALS: usize = 18;
 
@@ -57,9 +116,71 @@ impl U256 {
         }
     }
 
+    // Multiply the amount, returns `None` if the product overflows
     pub fn multiply(&self, num: u32) -> Option<U256> {
         self.to_u128()
-            .map(|value| U256::encode_u128(value * num as u128))
+            .and_then(|value| value.checked_mul(num as u128))
+            .map(U256::encode_u128)
+    }
+
+    // Add two amounts, returns `None` if the sum overflows
+    pub fn add(&self, other: &U256) -> Option<U256> {
+        let lhs = self.to_be_bytes();
+        let rhs = other.to_be_bytes();
+        let mut result = [0u8; 32];
+        let mut carry = 0u16;
+        for index in (0..32).rev() {
+            let sum = (lhs[index] as u16) + (rhs[index] as u16) + carry;
+            result[index] = sum as u8;
+            carry = sum >> 8;
+        }
+        if carry != 0 {
+            return None;
+        }
+        Some(U256::from_be_bytes(&result))
+    }
+
+    // Subtract two amounts, returns `None` if the other amount is larger
+    pub fn sub(&self, other: &U256) -> Option<U256> {
+        let lhs = self.to_be_bytes();
+        let rhs = other.to_be_bytes();
+        let mut result = [0u8; 32];
+        let mut borrow = 0i16;
+        for index in (0..32).rev() {
+            let diff = (lhs[index] as i16) - (rhs[index] as i16) - borrow;
+            result[index] = diff as u8;
+            borrow = (diff < 0) as i16;
+        }
+        if borrow != 0 {
+            return None;
+        }
+        Some(U256::from_be_bytes(&result))
+    }
+
+    fn to_be_bytes(&self) -> [u8; 32] {
+        let length = self.0.get_length();
+        let mut bytes = [0u8; 32];
+        if self.0.is_fixed_size() {
+            let value = Self::decode_fixed_size(&self.0.bytes[..length]);
+            bytes[28..].copy_from_slice(&value.to_be_bytes());
+        } else {
+            bytes[(33 - length)..].copy_from_slice(&self.0.bytes[1..length])
+        }
+        bytes
+    }
+
+    fn from_be_bytes(bytes: &[u8; 32]) -> U256 {
+        let index = bytes.iter().position(|v| *v != 0).unwrap_or(31);
+        if index >= 16 {
+            let mut value = [0u8; 16];
+            value.copy_from_slice(&bytes[16..]);
+            return U256::encode_u128(u128::from_be_bytes(value));
+        }
+        let length = bytes.len() - index;
+        let mut bs = [0u8; 33];
+        bs[0] = ((length - 4) as u8) | 0xc0;
+        bs[1..(length + 1)].copy_from_slice(&bytes[index..]);
+        Self(BigInt { bytes: bs })
     }
 
     fn encode_fixed_bytes(n: u32) -> U256 {
@@ -69,7 +190,7 @@ impl U256 {
             U256::from_encoded_bytes(&[((n >> 8) + 0x40) as u8, n as u8])
         } else if n < (0x40 << 24) {
             U256::from_encoded_bytes(&[
-                ((n >> 24) + 0x40) as u8,
+                ((n >> 24) + 0x80) as u8,
                 (n >> 16) as u8,
                 (n >> 8) as u8,
                 n as u8,
@@ -79,7 +200,7 @@ impl U256 {
         }
     }
 
-    fn encode_u128(value: u128) -> U256 {
+    pub fn encode_u128(value: u128) -> U256 {
         if value < (0x40 << 24) {
             U256::encode_fixed_bytes(value as u32)
         } else {
@@ -121,14 +242,7 @@ impl U256 {
             return Some(&output[..1]);
         }
 
-        let length = self.0.get_length();
-        let mut bytes = [0u8; 32];
-        if self.0.is_fixed_size() {
-            let value = Self::decode_fixed_size(&self.0.bytes[..length]);
-            bytes[28..].copy_from_slice(&value.to_be_bytes());
-        } else {
-            bytes[(33 - length)..].copy_from_slice(&self.0.bytes[1..length])
-        }
+        let mut bytes = self.to_be_bytes();
         let mut index = output.len();
         while !bytes.into_iter().all(|v| v == 0) {
             if index == 0 {
@@ -148,11 +262,25 @@ impl U256 {
         Some(&output[..(output.len() - index)])
     }
 
+    // Format the amount with the decimals, the digits of the integer part are grouped by 3 with
+    // the separator if there is one
     pub fn to_str_with_decimals<'a>(
         &self,
         output: &'a mut [u8],
         decimals: usize,
+        separator: Option<&[u8]>,
     ) -> Option<&'a [u8]> {
+        let length = self.format_decimals(output, decimals)?.len();
+        match separator {
+            Some(separator) => {
+                let length = group_digits(output, length, separator)?;
+                Some(&output[..length])
+            }
+            None => Some(&output[..length]),
+        }
+    }
+
+    fn format_decimals<'a>(&self, output: &'a mut [u8], decimals: usize) -> Option<&'a [u8]> {
         reset(output);
         let str = self.to_str(output)?;
         let str_length = str.len();
@@ -161,12 +289,18 @@ impl U256 {
         }
 
         if str_length > decimals {
+            if str_length == output.len() {
+                return None;
+            }
             let decimal_index = str_length - decimals;
             output.copy_within(decimal_index..str_length, decimal_index + 1);
             output[decimal_index] = b'.';
             return Some(trim(&output[..(decimal_index + decimals + 1)]));
         }
 
+        if 2 + decimals > output.len() {
+            return None;
+        }
         let pad_size = decimals - str_length;
         output.copy_within(0..str_length, 2 + pad_size);
         for (i, element) in output.iter_mut().enumerate().take(2 + pad_size) {
@@ -179,24 +313,33 @@ impl U256 {
         return Some(trim(&output[..(2 + decimals)]));
     }
 
-    pub fn to_alph<'a>(&self, output: &'a mut [u8]) -> Option<&'a [u8]> {
-        reset(output);
-        let prefix = b"ALPH ";
-        if self.is_zero() {
-            let total_size = 1 + prefix.len();
-            output[..prefix.len()].copy_from_slice(prefix);
-            output[prefix.len()] = b'0';
-            return Some(&output[..total_size]);
-        }
+    pub fn to_alph<'a>(&self, output: &'a mut [u8], separator: Option<&[u8]>) -> Option<&'a [u8]> {
+        self.to_token_str(output, Self::ALPH_DECIMALS, b"ALPH", separator)
+    }
 
-        if output.len() < 28 + prefix.len() {
-            // max ALPH amount
+    // Format the amount of a token with its decimals, prefixed with the token symbol,
+    // e.g. `USDT 1.5`
+    pub fn to_token_str<'a>(
+        &self,
+        output: &'a mut [u8],
+        decimals: usize,
+        symbol: &[u8],
+        separator: Option<&[u8]>,
+    ) -> Option<&'a [u8]> {
+        reset(output);
+        let prefix_length = symbol.len() + 1;
+        if output.len() <= prefix_length {
             return None;
         }
+        output[..symbol.len()].copy_from_slice(symbol);
+        output[symbol.len()] = b' ';
+        if self.is_zero() {
+            output[prefix_length] = b'0';
+            return Some(&output[..(prefix_length + 1)]);
+        }
 
-        output[..prefix.len()].copy_from_slice(prefix);
-        let str = self.to_str_with_decimals(&mut output[prefix.len()..], Self::ALPH_DECIMALS)?;
-        let total_size = prefix.len() + str.len();
+        let str = self.to_str_with_decimals(&mut output[prefix_length..], decimals, separator)?;
+        let total_size = prefix_length + str.len();
         Some(&output[..total_size])
     }
 }
@@ -221,7 +364,9 @@ pub mod tests {
     extern crate std;
 
     use crate::buffer::Buffer;
-    use crate::types::u256::U256;
+    use crate::types::u256::{
+        MAX_TOKEN_AMOUNT_LENGTH, MAX_TOKEN_SYMBOL_LENGTH, THOUSANDS_SEPARATOR, U256,
+    };
     use crate::{decode::*, TempData};
     use alloc::borrow::ToOwned;
     use core::str::from_utf8;
@@ -374,6 +519,114 @@ pub mod tests {
             .unwrap();
         assert!(u256.to_u128().unwrap() == fee);
         assert!(U256::encode_u128(u128::MAX).multiply(2).is_none());
+        assert!(U256::encode_u128(u128::MAX >> 1).multiply(3).is_none());
+    }
+
+    #[test]
+    fn test_add() {
+        for _ in 0..100 {
+            let a = random_usize(0, usize::MAX >> 1) as u128;
+            let b = random_usize(0, usize::MAX >> 1) as u128;
+            let sum = U256::encode_u128(a).add(&U256::encode_u128(b)).unwrap();
+            assert_eq!(sum, U256::encode_u128(a + b));
+        }
+
+        // The sum can exceed the range of u128
+        let max_u128 = U256::encode_u128(u128::MAX);
+        let sum = max_u128.add(&U256::encode_u128(1)).unwrap();
+        assert_eq!(sum.to_u128(), None);
+        let mut output = [0u8; 40];
+        assert_eq!(
+            sum.to_str(&mut output).unwrap(),
+            b"340282366920938463463374607431768211456"
+        );
+
+        let mut max_u256_bytes = [0xff; 33];
+        max_u256_bytes[0] = 0xdc;
+        let max_u256 = U256::from_encoded_bytes(&max_u256_bytes);
+        assert_eq!(max_u256.add(&U256::encode_u128(0)), Some(max_u256.clone()));
+        assert!(max_u256.add(&U256::encode_u128(1)).is_none());
+    }
+
+    #[test]
+    fn test_encode_u128() {
+        // The 1, 2 and 4-byte compact headers and the multi-byte header
+        let cases: [(u128, &[u8]); 8] = [
+            (0x3f, &[0x3f]),
+            (0x40, &[0x40, 0x40]),
+            (0x3fff, &[0x7f, 0xff]),
+            (0x4000, &[0x80, 0x00, 0x40, 0x00]),
+            (0x123456, &[0x80, 0x12, 0x34, 0x56]),
+            (0x3fffffff, &[0xbf, 0xff, 0xff, 0xff]),
+            (0x40000000, &[0xc0, 0x40, 0x00, 0x00, 0x00]),
+            (
+                0x0102030405060708,
+                &[0xc4, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08],
+            ),
+        ];
+        for (value, encoded) in cases.iter() {
+            let u256 = U256::encode_u128(*value);
+            // The amounts are compared by value, the encoded bytes are compared directly
+            assert_eq!(&u256.0.bytes[..encoded.len()], *encoded);
+            assert_eq!(u256.to_u128(), Some(*value));
+        }
+    }
+
+    #[test]
+    fn test_ordering() {
+        for _ in 0..100 {
+            let a = random_usize(0, usize::MAX >> 1) as u128;
+            let b = random_usize(0, usize::MAX >> 1) as u128;
+            assert_eq!(U256::encode_u128(a).cmp(&U256::encode_u128(b)), a.cmp(&b));
+        }
+
+        let _1000_nano_alph = U256::encode_u128(_1000_NANO_ALPH as u128);
+        assert!(U256::encode_u128((_1000_NANO_ALPH - 1) as u128) < _1000_nano_alph);
+        assert!(U256::encode_u128((_1000_NANO_ALPH + 1) as u128) > _1000_nano_alph);
+        // The values of both encodings of the compact integers are compared
+        assert!(U256::encode_u128(0x3f) < U256::encode_u128(0x40));
+        assert!(U256::encode_u128(0x3fff) < U256::encode_u128(0x4000));
+        assert!(U256::encode_u128(0x3fffffff) < U256::encode_u128(0x40000000));
+        assert_eq!(
+            U256::from_encoded_bytes(&[0x80, 0x00, 0x00, 0x05]),
+            U256::encode_u128(5)
+        );
+        assert_eq!(
+            U256::from_encoded_bytes(&[0xc0, 0x00, 0x00, 0x01, 0x00]),
+            U256::encode_u128(0x100)
+        );
+
+        let mut max_u256_bytes = [0xff; 33];
+        max_u256_bytes[0] = 0xdc;
+        let max_u256 = U256::from_encoded_bytes(&max_u256_bytes);
+        assert!(U256::encode_u128(u128::MAX) < max_u256);
+        assert_eq!(max_u256.cmp(&max_u256.clone()), core::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn test_sub() {
+        for _ in 0..100 {
+            let a = random_usize(0, usize::MAX >> 1) as u128;
+            let b = random_usize(0, usize::MAX >> 1) as u128;
+            let (a, b) = if a >= b { (a, b) } else { (b, a) };
+            let diff = U256::encode_u128(a).sub(&U256::encode_u128(b)).unwrap();
+            assert_eq!(diff, U256::encode_u128(a - b));
+            assert!(b == a || U256::encode_u128(b).sub(&U256::encode_u128(a)).is_none());
+        }
+
+        let zero = U256::encode_u128(0);
+        assert_eq!(zero.sub(&zero), Some(zero.clone()));
+        assert!(zero.sub(&U256::encode_u128(1)).is_none());
+
+        // The difference of amounts beyond the range of u128
+        let mut max_u256_bytes = [0xff; 33];
+        max_u256_bytes[0] = 0xdc;
+        let max_u256 = U256::from_encoded_bytes(&max_u256_bytes);
+        assert_eq!(max_u256.sub(&max_u256), Some(zero.clone()));
+        let max_u128 = U256::encode_u128(u128::MAX);
+        let sum = max_u128.add(&U256::encode_u128(1)).unwrap();
+        assert_eq!(sum.sub(&U256::encode_u128(1)), Some(max_u128.clone()));
+        assert_eq!(sum.sub(&max_u128), Some(U256::encode_u128(1)));
     }
 
     #[test]
@@ -414,7 +667,7 @@ pub mod tests {
         for (number, str) in cases {
             let u256 = U256::encode_u128(number);
             let mut output = [0u8; 33];
-            let result = u256.to_alph(&mut output);
+            let result = u256.to_alph(&mut output, None);
             assert!(result.is_some());
             let expected = from_utf8(result.unwrap()).unwrap();
             let amount_str = "ALPH ".to_owned() + str;
@@ -424,7 +677,83 @@ pub mod tests {
         let test_vector = get_test_vector();
         let u256 = U256::from_encoded_bytes(&test_vector[test_vector.len() - 1].0);
         let mut output = [0u8; 33];
-        assert!(u256.to_alph(&mut output).is_none());
+        assert!(u256.to_alph(&mut output, None).is_none());
+    }
+
+    #[test]
+    fn test_to_token_str() {
+        let format = |number: u128, decimals: usize, symbol: &str, output: &mut [u8]| {
+            U256::encode_u128(number)
+                .to_token_str(output, decimals, symbol.as_bytes(), None)
+                .map(|result| String::from(from_utf8(result).unwrap()))
+        };
+        let mut output = [0u8; 40];
+        assert_eq!(format(0, 6, "USDT", &mut output).unwrap(), "USDT 0");
+        assert_eq!(format(1500000, 6, "USDT", &mut output).unwrap(), "USDT 1.5");
+        assert_eq!(format(1, 6, "USDT", &mut output).unwrap(), "USDT 0.000001");
+        assert_eq!(format(1234, 0, "NFT", &mut output).unwrap(), "NFT 1234");
+        assert_eq!(format(10, 1, "T", &mut output).unwrap(), "T 1");
+        let mut alph_output = [0u8; 40];
+        let alph = U256::encode_u128(15 * 10_u128.pow(17)).to_alph(&mut alph_output, None);
+        assert_eq!(
+            format(15 * 10_u128.pow(17), 18, "ALPH", &mut output).unwrap(),
+            from_utf8(alph.unwrap()).unwrap()
+        );
+
+        let mut output = [0u8; 40];
+        let result = U256::encode_u128(1234567890)
+            .to_token_str(&mut output, 3, b"WBTC", Some(THOUSANDS_SEPARATOR))
+            .unwrap();
+        assert_eq!(from_utf8(result).unwrap(), "WBTC 1,234,567.89");
+
+        // The output is too small for the symbol, the digits or the decimals
+        assert!(format(1, 0, "USDT", &mut [0u8; 5]).is_none());
+        assert!(format(123, 0, "USDT", &mut [0u8; 7]).is_none());
+        assert!(format(123, 2, "USDT", &mut [0u8; 8]).is_none());
+        assert!(format(1, 6, "USDT", &mut [0u8; 12]).is_none());
+        assert_eq!(format(123, 2, "USDT", &mut [0u8; 9]).unwrap(), "USDT 1.23");
+        assert_eq!(
+            format(1, 6, "USDT", &mut [0u8; 13]).unwrap(),
+            "USDT 0.000001"
+        );
+    }
+
+    #[test]
+    fn test_to_token_str_max_length() {
+        let max = U256::from_encoded_bytes(
+            &hex_to_bytes("dcffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff")
+                .unwrap(),
+        );
+        let symbol = b"TestTokenABC";
+        assert_eq!(symbol.len(), MAX_TOKEN_SYMBOL_LENGTH);
+        let mut max_length = 0;
+        for decimals in 0..=(u8::MAX as usize) {
+            let mut output = [0u8; MAX_TOKEN_AMOUNT_LENGTH];
+            let result = max
+                .to_token_str(&mut output, decimals, symbol, Some(THOUSANDS_SEPARATOR))
+                .unwrap();
+            max_length = max_length.max(result.len());
+        }
+        assert_eq!(max_length, MAX_TOKEN_AMOUNT_LENGTH);
+
+        let mut output = [0u8; MAX_TOKEN_AMOUNT_LENGTH];
+        let result = max
+            .to_token_str(&mut output, 1, symbol, Some(THOUSANDS_SEPARATOR))
+            .unwrap();
+        assert_eq!(
+            from_utf8(result).unwrap(),
+            "TestTokenABC 11,579,208,923,731,619,542,357,098,500,868,790,785,326,998,466,564,056,403,945,758,400,791,312,963,993.5"
+        );
+
+        let mut output = [0u8; MAX_TOKEN_AMOUNT_LENGTH - 1];
+        assert!(max
+            .to_token_str(
+                &mut output,
+                u8::MAX as usize,
+                symbol,
+                Some(THOUSANDS_SEPARATOR)
+            )
+            .is_none());
     }
 
     #[test]
@@ -445,4 +774,44 @@ pub mod tests {
         let result = u256.to_str(&mut output);
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_to_str_with_separator() {
+        let format = |number: u128, decimals: usize, separator: &[u8]| {
+            let mut output = [0u8; 64];
+            let u256 = U256::encode_u128(number);
+            let result = u256
+                .to_str_with_decimals(&mut output, decimals, Some(separator))
+                .unwrap();
+            String::from(from_utf8(result).unwrap())
+        };
+
+        assert_eq!(format(0, 0, THOUSANDS_SEPARATOR), "0");
+        assert_eq!(format(999, 0, THOUSANDS_SEPARATOR), "999");
+        assert_eq!(format(1000, 0, THOUSANDS_SEPARATOR), "1,000");
+        assert_eq!(format(123456, 0, THOUSANDS_SEPARATOR), "123,456");
+        assert_eq!(format(1234567, 0, THOUSANDS_SEPARATOR), "1,234,567");
+        assert_eq!(format(123456725, 2, THOUSANDS_SEPARATOR), "1,234,567.25");
+        assert_eq!(format(1234567, 7, THOUSANDS_SEPARATOR), "0.1234567");
+        assert_eq!(
+            format(1234567, 0, "\u{2009}".as_bytes()),
+            "1\u{2009}234\u{2009}567"
+        );
+
+        let mut output = [0u8; 40];
+        let u256 = U256::encode_u128(1234567250000000000000000);
+        let result = u256
+            .to_alph(&mut output, Some(THOUSANDS_SEPARATOR))
+            .unwrap();
+        assert_eq!(from_utf8(result).unwrap(), "ALPH 1,234,567.25");
+
+        // The separators do not fit in the output
+        let mut output = [0u8; 8];
+        let result = u256.to_str_with_decimals(&mut output, 0, Some(THOUSANDS_SEPARATOR));
+        assert!(result.is_none());
+        let mut output = [0u8; 9];
+        let u256 = U256::encode_u128(1234567);
+        let result = u256.to_str_with_decimals(&mut output, 0, Some(THOUSANDS_SEPARATOR));
+        assert_eq!(from_utf8(result.unwrap()).unwrap(), "1,234,567");
+    }
 }
@@ -1,3 +1,4 @@
+use super::public_key::PublicKeyLike;
 use super::*;
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
@@ -5,8 +6,13 @@ use crate::decode::*;
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Default)]
 pub struct PublicKeyWithIndex {
-    public_key: PublicKey,
-    index: U16,
+    pub public_key: PublicKey,
+    pub index: U16,
+}
+
+impl PublicKeyWithIndex {
+    // The public key followed by the key index in big-endian
+    pub const TEMP_DATA_SIZE: usize = PublicKey::ENCODED_LENGTH + 2;
 }
 
 impl Reset for PublicKeyWithIndex {
@@ -28,7 +34,91 @@ impl RawDecoder for PublicKeyWithIndex {
     ) -> DecodeResult<DecodeStage> {
         match stage.step {
             0 => self.public_key.decode(buffer, stage),
-            1 => self.index.decode(buffer, stage),
+            1 => {
+                let result = self.index.decode(buffer, stage)?;
+                if result.is_complete() {
+                    // Keep the revealed keys in temp data so that they can be reviewed
+                    buffer.write_bytes_to_temp_data(&self.public_key.0)?;
+                    buffer.write_bytes_to_temp_data(&self.index.inner.to_be_bytes())?;
+                }
+                Ok(result)
+            }
+            _ => Err(DecodeError::InternalError),
+        }
+    }
+}
+
+// The index of a signing key of a groupless multi-sig input
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Default)]
+pub struct SigningKeyIndex(pub U16);
+
+impl SigningKeyIndex {
+    // The key index in big-endian
+    pub const TEMP_DATA_SIZE: usize = 2;
+}
+
+impl Reset for SigningKeyIndex {
+    fn reset(&mut self) {
+        self.0.reset();
+    }
+}
+
+impl RawDecoder for SigningKeyIndex {
+    fn step_size(&self) -> u16 {
+        1
+    }
+
+    fn decode<W: Writable>(
+        &mut self,
+        buffer: &mut Buffer<'_, W>,
+        stage: &DecodeStage,
+    ) -> DecodeResult<DecodeStage> {
+        let result = self.0.decode(buffer, stage)?;
+        if result.is_complete() {
+            // Keep the signing key indexes in temp data so that they can be reviewed
+            buffer.write_bytes_to_temp_data(&self.0.inner.to_be_bytes())?;
+        }
+        Ok(result)
+    }
+}
+
+// The unlock script of a groupless multi-sig address reveals all the public keys of the address,
+// followed by the indexes of the signing keys
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Default)]
+pub struct P2HMPK {
+    pub public_keys: StreamingDecoder<AVector<PublicKeyLike>>,
+    pub key_indexes: StreamingDecoder<AVector<SigningKeyIndex>>,
+}
+
+impl P2HMPK {
+    #[inline]
+    pub fn key_size(&self) -> usize {
+        self.public_keys.inner.size()
+    }
+}
+
+impl Reset for P2HMPK {
+    fn reset(&mut self) {
+        self.public_keys.reset();
+        self.key_indexes.reset();
+    }
+}
+
+impl RawDecoder for P2HMPK {
+    fn step_size(&self) -> u16 {
+        2
+    }
+
+    fn decode<W: Writable>(
+        &mut self,
+        buffer: &mut Buffer<'_, W>,
+        stage: &DecodeStage,
+    ) -> DecodeResult<DecodeStage> {
+        match stage.step {
+            0 => self.public_keys.decode_children(buffer, stage),
+            1 => self.key_indexes.decode_children(buffer, stage),
             _ => Err(DecodeError::InternalError),
         }
     }
@@ -69,6 +159,9 @@ pub enum UnlockScript {
     P2MPKH(StreamingDecoder<AVector<PublicKeyWithIndex>>),
     P2SH(StreamingDecoder<P2SH>),
     SameAsPrevious,
+    // The public key of a groupless address is in the lockup script of the spent output
+    P2PK,
+    P2HMPK(StreamingDecoder<P2HMPK>),
     #[default]
     Unknown,
 }
@@ -86,6 +179,8 @@ impl UnlockScript {
             1 => Some(UnlockScript::P2MPKH(StreamingDecoder::default())),
             2 => Some(UnlockScript::P2SH(StreamingDecoder::default())),
             3 => Some(UnlockScript::SameAsPrevious),
+            5 => Some(UnlockScript::P2PK),
+            6 => Some(UnlockScript::P2HMPK(StreamingDecoder::default())),
             _ => None,
         }
     }
@@ -105,18 +200,15 @@ impl RawDecoder for UnlockScript {
             return Ok(DecodeStage { ..*stage });
         }
         if let UnlockScript::Unknown = self {
-            let tpe = buffer.consume_byte().unwrap();
-            let result = UnlockScript::from_type(tpe);
-            if result.is_none() {
-                return Err(DecodeError::InvalidData);
-            }
-            *self = result.unwrap();
+            let tpe = buffer.consume_byte().ok_or(DecodeError::InternalError)?;
+            *self = UnlockScript::from_type(tpe).ok_or(DecodeError::InvalidData)?;
         };
         match self {
             UnlockScript::P2PKH(public_key) => public_key.decode(buffer, stage),
             UnlockScript::P2MPKH(keys) => keys.decode_children(buffer, stage),
             UnlockScript::P2SH(script) => script.decode_children(buffer, stage),
-            UnlockScript::SameAsPrevious => Ok(DecodeStage::COMPLETE),
+            UnlockScript::P2HMPK(p2hmpk) => p2hmpk.decode_children(buffer, stage),
+            UnlockScript::SameAsPrevious | UnlockScript::P2PK => Ok(DecodeStage::COMPLETE),
             UnlockScript::Unknown => Err(DecodeError::InternalError),
         }
     }
@@ -130,9 +222,11 @@ mod tests {
     use crate::decode::{new_decoder, Decoder};
     use crate::types::byte32::tests::gen_bytes;
     use crate::types::i32::tests::random_usize;
+    use crate::types::unlock_script::PublicKeyWithIndex;
     use crate::types::{PublicKey, UnlockScript};
     use crate::TempData;
     use std::vec;
+    use std::vec::Vec;
 
     use super::u256::tests::hex_to_bytes;
 
@@ -198,4 +292,97 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_decode_p2mpkh() {
+        let public_keys = [gen_bytes(33, 33), gen_bytes(33, 33)];
+        let indexes = [0u16, 2];
+        let mut bytes = vec![1u8, public_keys.len() as u8];
+        let mut expected = Vec::new();
+        for (public_key, index) in public_keys.iter().zip(indexes) {
+            bytes.extend(public_key);
+            bytes.push(index as u8);
+            expected.extend(public_key);
+            expected.extend(index.to_be_bytes());
+        }
+        assert_eq!(
+            expected.len(),
+            public_keys.len() * PublicKeyWithIndex::TEMP_DATA_SIZE
+        );
+
+        let mut temp_data = TempData::new();
+        let mut length: usize = 0;
+        let mut decoder = new_decoder::<UnlockScript>();
+
+        while length < bytes.len() {
+            let remain = bytes.len() - length;
+            let size = random_usize(0, remain);
+            let mut buffer = Buffer::new(&bytes[length..(length + size)], &mut temp_data);
+            length += size;
+
+            let result = decoder.decode(&mut buffer).unwrap();
+            if length == bytes.len() {
+                assert!(result.is_some());
+                assert!(decoder.stage.is_complete());
+                assert_eq!(temp_data.get(), &expected);
+            } else {
+                assert_eq!(result, None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_p2hmpk() {
+        // A secp256k1 key, an ed25519 key and a secp256k1 key, signed by the first and the last
+        let public_keys = [
+            [vec![0u8], gen_bytes(33, 33)].concat(),
+            [vec![2u8], gen_bytes(32, 32)].concat(),
+            [vec![0u8], gen_bytes(33, 33)].concat(),
+        ];
+        let indexes = [0u16, 2];
+        let mut bytes = vec![6u8, public_keys.len() as u8];
+        bytes.extend(public_keys.concat());
+        bytes.push(indexes.len() as u8);
+        let mut expected = Vec::new();
+        for index in indexes {
+            bytes.push(index as u8);
+            expected.extend(index.to_be_bytes());
+        }
+
+        let mut temp_data = TempData::new();
+        let mut length: usize = 0;
+        let mut decoder = new_decoder::<UnlockScript>();
+
+        while length < bytes.len() {
+            let remain = bytes.len() - length;
+            let size = random_usize(0, remain);
+            let mut buffer = Buffer::new(&bytes[length..(length + size)], &mut temp_data);
+            length += size;
+
+            let result = decoder.decode(&mut buffer).unwrap();
+            if length == bytes.len() {
+                match result {
+                    Some(UnlockScript::P2HMPK(p2hmpk)) => {
+                        assert_eq!(p2hmpk.inner.key_size(), public_keys.len())
+                    }
+                    _ => panic!("expected a P2HMPK unlock script"),
+                }
+                assert!(decoder.stage.is_complete());
+                assert_eq!(temp_data.get(), &expected);
+            } else {
+                assert_eq!(result, None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_p2pk() {
+        let mut temp_data = TempData::new();
+        let bytes = [5u8];
+        let mut buffer = Buffer::new(&bytes, &mut temp_data);
+        let mut decoder = new_decoder::<UnlockScript>();
+        let result = decoder.decode(&mut buffer).unwrap();
+        assert_eq!(result, Some(&UnlockScript::P2PK));
+        assert!(decoder.stage.is_complete());
+    }
 }
@@ -2,11 +2,22 @@ use super::*;
 use crate::buffer::{Buffer, Writable};
 use crate::decode::*;
 
+// The range of the tx encoding versions that the decoder supports, txs of other versions are
+// rejected instead of being decoded with a different layout
+pub const MIN_TX_VERSION: u8 = 0;
+pub const MAX_TX_VERSION: u8 = 0;
+
+pub fn is_supported_tx_version(version: u8) -> bool {
+    (MIN_TX_VERSION..=MAX_TX_VERSION).contains(&version)
+}
+
+// Only the field that is being decoded is kept, the script step holds the approval tracker
+#[allow(clippy::large_enum_variant)]
 #[cfg_attr(test, derive(Debug))]
 pub enum UnsignedTx {
     Version(Byte),
     NetworkId(Byte),
-    ScriptOpt(StreamingDecoder<Option<Script>>),
+    ScriptOpt(StreamingDecoder<Option<TxScript>>),
     TxFee(StreamingDecoder<TxFee>),
     Inputs(AVector<TxInput>),
     FixedOutputs(AVector<AssetOutput>),
@@ -23,6 +34,18 @@ impl UnsignedTx {
         matches!(self, Self::FixedOutputs(outputs) if outputs.is_complete())
     }
 
+    // The index of the current tx field, in the order of decoding
+    pub fn step_index(&self) -> u8 {
+        match self {
+            Self::Version(_) => 0,
+            Self::NetworkId(_) => 1,
+            Self::ScriptOpt(_) => 2,
+            Self::TxFee(_) => 3,
+            Self::Inputs(_) => 4,
+            Self::FixedOutputs(_) => 5,
+        }
+    }
+
     #[inline]
     pub fn next_step(&mut self) {
         match self {
@@ -116,6 +139,7 @@ mod tests {
     use super::*;
     use crate::buffer::Buffer;
     use crate::decode::new_decoder;
+    use crate::types::asset_output::{MAX_ADDITIONAL_DATA_PREFIX_SIZE, MAX_OUTPUT_TOKENS};
     use crate::types::i32::tests::random_usize;
     use crate::types::u256::tests::hex_to_bytes;
     use crate::types::{AVector, Hash, Hint, LockupScript, PublicKey, UnlockScript, I32, U256};
@@ -183,6 +207,12 @@ mod tests {
         U256::from_encoded_bytes(&bytes)
     }
 
+    fn additional_data_prefix(bytes: &[u8]) -> [u8; MAX_ADDITIONAL_DATA_PREFIX_SIZE] {
+        let mut prefix = [0; MAX_ADDITIONAL_DATA_PREFIX_SIZE];
+        prefix[..bytes.len()].copy_from_slice(bytes);
+        prefix
+    }
+
     fn output(amount_str: &str, lockup_script: LockupScript) -> AssetOutput {
         let amount = u256_from_str(amount_str);
         AssetOutput {
@@ -190,7 +220,9 @@ mod tests {
             lockup_script,
             lock_time: TimeStamp(0),
             tokens: AVector::default(),
+            decoded_tokens: Default::default(),
             additional_data: ByteString::empty(),
+            additional_data_prefix: [0; MAX_ADDITIONAL_DATA_PREFIX_SIZE],
         }
     }
 
@@ -211,9 +243,12 @@ mod tests {
         token_amount: &str,
     ) -> AssetOutput {
         let output = p2pkh_output(amount_str, public_key_hash_hex);
+        let mut decoded_tokens: [Token; MAX_OUTPUT_TOKENS] = Default::default();
+        decoded_tokens[0] = token(token_id_hex, token_amount);
         let token = token(token_id_hex, token_amount);
         AssetOutput {
             tokens: AVector::from_item(token),
+            decoded_tokens,
             ..output
         }
     }
@@ -300,6 +335,13 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_supported_tx_version() {
+        assert!(is_supported_tx_version(0));
+        assert!(!is_supported_tx_version(MAX_TX_VERSION + 1));
+        assert!(!is_supported_tx_version(u8::MAX));
+    }
+
     #[test]
     fn test_decode_transfer_alph_tx() {
         let tx_id_hex = "c53f150bceb13c6ca1c13fee897e688c0ef86c73ad8113edf444b7b15ecf438b";
@@ -507,6 +549,9 @@ mod tests {
                 length: I32::from(10),
                 current_index: 10,
             },
+            additional_data_prefix: additional_data_prefix(
+                &hex_to_bytes("00000000018d1c8a8eec").unwrap(),
+            ),
             ..output
         }];
 
@@ -550,4 +595,56 @@ mod tests {
             &all_outputs,
         );
     }
+
+    // Real transactions from the explorer, new script types should be added here so that
+    // decoder refactors are checked against all of them
+    const DECODE_CORPUS: [(&str, &[u8]); 5] = [
+        (
+            "c53f150bceb13c6ca1c13fee897e688c0ef86c73ad8113edf444b7b15ecf438b",
+            include_bytes!("corpus/transfer_alph_tx.bin"),
+        ),
+        (
+            "668827ae5719d8acb7efa4e8684cd3968738736833369ad56482b7ccb6bad5c7",
+            include_bytes!("corpus/transfer_token_tx.bin"),
+        ),
+        (
+            "b4d93868e9b20c2757067334799ea815614fcec306eb254832dbbbd58eb8d42a",
+            include_bytes!("corpus/script_tx.bin"),
+        ),
+        (
+            "a720a161efca30b9378da93facf1fa5fc9340ffb17e1f859f1100fa1e0b61038",
+            include_bytes!("corpus/coinbase_tx.bin"),
+        ),
+        (
+            "7a6d9e62e4deeae2f85d0675ba3c371fc2c23102270adf69eaa41824df37d944",
+            include_bytes!("corpus/rhone_tx.bin"),
+        ),
+    ];
+
+    #[test]
+    fn test_decode_corpus() {
+        for (tx_id_hex, encoded_tx) in DECODE_CORPUS {
+            for frame_size in 1..=(u8::MAX as usize) {
+                let mut decoder = new_decoder::<UnsignedTx>();
+                let mut hasher = Blake2b256::new();
+                let mut temp_data = TempData::new();
+                for frame in encoded_tx.chunks(frame_size) {
+                    let mut buffer = Buffer::new(frame, &mut temp_data);
+                    while !decoder.inner.is_complete() {
+                        if !decode(&mut buffer, &mut decoder, &mut hasher).unwrap() {
+                            break;
+                        }
+                        if !decoder.inner.is_complete() {
+                            decoder.inner.next_step();
+                            decoder.reset_stage();
+                        }
+                    }
+                    assert!(buffer.is_empty());
+                }
+                assert!(decoder.inner.is_complete());
+                let tx_id = hasher.finalize().to_vec();
+                assert_eq!(tx_id, hex_to_bytes(tx_id_hex).unwrap());
+            }
+        }
+    }
 }
@@ -54,12 +54,8 @@ impl RawDecoder for Val {
             return Ok(DecodeStage { ..*stage });
         }
         if let Val::Unknown = self {
-            let tpe = buffer.consume_byte().unwrap();
-            let result = Val::from_type(tpe);
-            if result.is_none() {
-                return Err(DecodeError::InvalidData);
-            }
-            *self = result.unwrap();
+            let tpe = buffer.consume_byte().ok_or(DecodeError::InternalError)?;
+            *self = Val::from_type(tpe).ok_or(DecodeError::InvalidData)?;
         };
 
         match self {